@@ -1,18 +1,25 @@
 use {
-    crate::{ACCOUNTS, ACCOUNTS_BY_USER, CODE_HASHES, KEYS, MINIMUM_DEPOSIT, NEXT_ACCOUNT_INDEX},
+    crate::{
+        ACCOUNTS, ACCOUNTS_BY_USER, ALIASES, CODE_HASHES, DELETED_ACCOUNTS, KEYS, MINIMUM_DEPOSIT,
+        NEXT_ACCOUNT_INDEX, RECURRING_TRANSFER_QUEUE, RECURRING_TRANSFER_SCHEDULES, USERS_BY_ALIAS,
+    },
     anyhow::{bail, ensure},
     dango_types::{
-        account::{self, multi, single},
+        account::{self, margin::QueryHealthRequest, multi, single},
         account_factory::{
             Account, AccountParams, AccountType, ExecuteMsg, InstantiateMsg, NewUserSalt, Salt,
             Username,
         },
         auth::Key,
+        warp, DangoQuerier,
     },
     grug::{
-        Addr, AuthCtx, AuthMode, AuthResponse, Coins, Hash256, Inner, JsonDeExt, Message,
-        MsgExecute, MutableCtx, Op, Order, Response, StdResult, Storage, Tx,
+        Addr, Api, AuthCtx, AuthMode, AuthResponse, Coins, Empty, Hash256, Inner, JsonDeExt,
+        Message, MsgExecute, MutableCtx, Op, Order, QuerierExt, QuerierWrapper, Response,
+        StdResult, Storage, SubMessage, SubMsgResult, SudoCtx, Timestamp, Tx,
     },
+    hyperlane_types::Addr32,
+    k256::{elliptic_curve::sec1::ToEncodedPoint, PublicKey},
 };
 
 #[cfg_attr(not(feature = "library"), grug::export)]
@@ -28,14 +35,16 @@ pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response>
         .map(|(username, (key_hash, key))| {
             KEYS.save(ctx.storage, (&username, key_hash), &key)?;
             // Minimum deposit is not required for genesis users.
-            onboard_new_user(
+            let (_, msg) = onboard_new_user(
                 ctx.storage,
                 ctx.contract,
                 username,
                 key,
                 key_hash,
                 Coins::default(),
-            )
+            )?;
+
+            Ok(msg)
         })
         .collect::<StdResult<Vec<_>>>()?;
 
@@ -111,7 +120,57 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
         ExecuteMsg::RegisterAccount { params } => register_account(ctx, params),
         ExecuteMsg::ConfigureKey { key_hash, key } => configure_key(ctx, key_hash, key),
         ExecuteMsg::ConfigureSafe { updates } => configure_safe(ctx, updates),
+        ExecuteMsg::ChangeAccountType { account_type } => change_account_type(ctx, account_type),
+        ExecuteMsg::DeleteAccount {} => delete_account(ctx),
+        ExecuteMsg::TransferUsername { key_hash, key } => transfer_username(ctx, key_hash, key),
+        ExecuteMsg::SetAlias { alias } => set_alias(ctx, alias),
+        ExecuteMsg::ScheduleRecurringTransfers { next } => schedule_recurring_transfers(ctx, next),
+    }
+}
+
+/// Maximum number of Safes whose recurring transfers are executed in one
+/// cronjob. The rest are left for the next one.
+pub const MAX_SAFES_PER_SWEEP: usize = 30;
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn cron_execute(ctx: SudoCtx) -> StdResult<Response> {
+    let due = RECURRING_TRANSFER_QUEUE
+        .range(ctx.storage, None, None, Order::Ascending)
+        .take_while(|res| {
+            res.as_ref()
+                .map_or(true, |(next, _)| *next <= ctx.block.timestamp)
+        })
+        .take(MAX_SAFES_PER_SWEEP)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut submsgs = Vec::with_capacity(due.len());
+
+    for (next, safe) in due {
+        // The Safe reschedules itself after making the transfers.
+        RECURRING_TRANSFER_QUEUE.remove(ctx.storage, (next, safe));
+        RECURRING_TRANSFER_SCHEDULES.remove(ctx.storage, safe);
+
+        // Catch the error if the Safe fails, so that it doesn't hold up the
+        // other Safes.
+        submsgs.push(SubMessage::reply_on_error(
+            Message::execute(
+                safe,
+                &multi::ExecuteMsg::ExecuteRecurringTransfers {},
+                Coins::new(),
+            )?,
+            &Empty {},
+        )?);
     }
+
+    Ok(Response::new().add_submessages(submsgs))
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn reply(_ctx: SudoCtx, _msg: Empty, _res: SubMsgResult) -> StdResult<Response> {
+    // A Safe failed to make its recurring transfers in the cronjob. It's no
+    // longer scheduled; it's scheduled again once anyone executes its recurring
+    // transfers, or they're changed through a proposal.
+    Ok(Response::new())
 }
 
 fn register_user(
@@ -120,16 +179,8 @@ fn register_user(
     key: Key,
     key_hash: Hash256,
 ) -> anyhow::Result<Response> {
-    // The username must not already exist.
-    // We ensure this by asserting there isn't any key already associated with
-    // this username, since any existing username necessarily has at least one
-    // key associated with it. (However, this key isn't necessarily index 1.)
-    if KEYS
-        .prefix(&username)
-        .keys(ctx.storage, None, None, Order::Ascending)
-        .next()
-        .is_some()
-    {
+    // The username must not already exist, either as a username or an alias.
+    if is_name_taken(ctx.storage, &username) {
         bail!("username `{}` already exists", username);
     }
 
@@ -138,14 +189,76 @@ fn register_user(
 
     let minimum_deposit = MINIMUM_DEPOSIT.load(ctx.storage)?;
 
-    Ok(Response::new().add_message(onboard_new_user(
+    let (address, msg) = onboard_new_user(
         ctx.storage,
         ctx.contract,
         username,
         key,
         key_hash,
         minimum_deposit,
-    )?))
+    )?;
+
+    // Warp holds funds received for recipients that don't exist yet. Claim the
+    // ones held for the new account, as well as for the Ethereum address of the
+    // user's key, if any.
+    //
+    // Only Secp256k1 keys have an Ethereum address. Funds sent to an Ethereum
+    // address can't be claimed by users who register with a Secp256r1 (passkey)
+    // key, and remain held by Warp.
+    //
+    // Warp sends them to the account's address before it's instantiated, so
+    // they become orphaned transfers, which the account recovers and counts
+    // towards the minimum deposit upon instantiation.
+    let mut owners = vec![Addr32::from(address)];
+
+    if let Key::Secp256k1(pk) = key {
+        owners.extend(ethereum_address(ctx.api, &pk));
+    }
+
+    Ok(Response::new()
+        .add_messages(claim_deposits(&ctx.querier, owners, address)?)
+        .add_message(msg))
+}
+
+/// Create the messages that claim the funds Warp holds for each of the owners,
+/// sending them to the recipient.
+fn claim_deposits(
+    querier: &QuerierWrapper,
+    owners: Vec<Addr32>,
+    recipient: Addr,
+) -> StdResult<Vec<Message>> {
+    let warp_addr = querier.query_warp()?;
+
+    let mut msgs = Vec::new();
+
+    for owner in owners {
+        let deposit = querier.query_wasm_smart(warp_addr, warp::QueryDepositRequest { owner })?;
+
+        if deposit.is_non_empty() {
+            msgs.push(Message::execute(
+                warp_addr,
+                &warp::ExecuteMsg::ClaimDeposit { owner, recipient },
+                Coins::new(),
+            )?);
+        }
+    }
+
+    Ok(msgs)
+}
+
+/// Derive the Ethereum address of a Secp256k1 public key, i.e. the last 20
+/// bytes of the Keccak-256 hash of the uncompressed public key, left-padded
+/// into a Hyperlane address.
+///
+/// Returns `None` if the key isn't a valid point on the curve.
+fn ethereum_address(api: &dyn Api, pk: &[u8]) -> Option<Addr32> {
+    let pk = PublicKey::from_sec1_bytes(pk).ok()?.to_encoded_point(false);
+    let hash = api.keccak256(&pk.as_bytes()[1..]);
+
+    let mut addr = [0; 32];
+    addr[12..].copy_from_slice(&hash[12..]);
+
+    Some(Addr32::from_inner(addr))
 }
 
 // Onboarding a new user involves saving an initial key, and intantiate an
@@ -157,7 +270,7 @@ fn onboard_new_user(
     key: Key,
     key_hash: Hash256,
     minimum_receive: Coins,
-) -> StdResult<Message> {
+) -> StdResult<(Addr, Message)> {
     // A new user's 1st account is always a spot account.
     let code_hash = CODE_HASHES.load(storage, AccountType::Spot)?;
 
@@ -183,7 +296,7 @@ fn onboard_new_user(
     ACCOUNTS_BY_USER.insert(storage, (&username, address))?;
 
     // Create the message to instantiate this account.
-    Message::instantiate(
+    let msg = Message::instantiate(
         code_hash,
         &account::spot::InstantiateMsg {
             minimum_deposit: minimum_receive,
@@ -192,15 +305,17 @@ fn onboard_new_user(
         Some(format!("dango/account/{}/{}", AccountType::Spot, index)),
         Some(factory),
         Coins::default(),
-    )
+    )?;
+
+    Ok((address, msg))
 }
 
 fn register_account(ctx: MutableCtx, params: AccountParams) -> anyhow::Result<Response> {
     // Basic validations of the account.
     // - For single signature accounts (spot and margin), one can only register
     //   accounts for themself. They cannot register account for another user.
-    // - For multisig accounts (Safe), ensure voting threshold isn't greater
-    //   than total voting power.
+    // - For multisig accounts (Safe), ensure voting thresholds and quorums
+    //   aren't greater than total voting power.
     match &params {
         AccountParams::Spot(params) | AccountParams::Margin(params) => {
             ensure!(
@@ -209,10 +324,7 @@ fn register_account(ctx: MutableCtx, params: AccountParams) -> anyhow::Result<Re
             );
         },
         AccountParams::Safe(params) => {
-            ensure!(
-                params.threshold.into_inner() <= params.total_power(),
-                "threshold can't be greater than total power"
-            );
+            params.validate()?;
         },
     }
 
@@ -243,16 +355,23 @@ fn register_account(ctx: MutableCtx, params: AccountParams) -> anyhow::Result<Re
         },
     }
 
-    Ok(Response::new().add_message(Message::instantiate(
-        code_hash,
-        &account::spot::InstantiateMsg {
-            minimum_deposit: Coins::default(),
-        },
-        salt,
-        Some(format!("dango/account/{}/{}", account.params.ty(), index)),
-        Some(ctx.contract),
-        ctx.funds,
-    )?))
+    // Funds may have been sent to the account's address before it's registered,
+    // since it's predictable from the account index. Claim those held by Warp
+    // once the account is instantiated.
+    let claim_msgs = claim_deposits(&ctx.querier, vec![Addr32::from(address)], address)?;
+
+    Ok(Response::new()
+        .add_message(Message::instantiate(
+            code_hash,
+            &account::spot::InstantiateMsg {
+                minimum_deposit: Coins::default(),
+            },
+            salt,
+            Some(format!("dango/account/{}/{}", account.params.ty(), index)),
+            Some(ctx.contract),
+            ctx.funds,
+        )?)
+        .add_messages(claim_msgs))
 }
 
 fn configure_key(ctx: MutableCtx, key_hash: Hash256, key: Op<Key>) -> anyhow::Result<Response> {
@@ -279,11 +398,7 @@ fn configure_safe(ctx: MutableCtx, updates: multi::ParamUpdates) -> anyhow::Resu
         match &mut account.params {
             AccountParams::Safe(params) => {
                 params.apply_updates(updates);
-
-                ensure!(
-                    params.threshold.into_inner() <= params.total_power(),
-                    "threshold can't be greater than total power"
-                );
+                params.validate()?;
             },
             _ => bail!("account isn't a Safe"),
         }
@@ -294,6 +409,157 @@ fn configure_safe(ctx: MutableCtx, updates: multi::ParamUpdates) -> anyhow::Resu
     Ok(Response::new())
 }
 
+fn change_account_type(ctx: MutableCtx, account_type: AccountType) -> anyhow::Result<Response> {
+    let mut account = ACCOUNTS.load(ctx.storage, ctx.sender)?;
+
+    ensure!(
+        account.params.ty() != account_type,
+        "account is already of type `{account_type}`"
+    );
+
+    let params = match &account.params {
+        AccountParams::Spot(params) | AccountParams::Margin(params) => params.clone(),
+        AccountParams::Safe(_) => bail!("only spot and margin accounts can change type"),
+    };
+
+    ensure_no_debt(&ctx.querier, ctx.sender, &account)?;
+
+    account.params = match account_type {
+        AccountType::Spot => AccountParams::Spot(params),
+        AccountType::Margin => AccountParams::Margin(params),
+        AccountType::Safe => bail!("can't change a single-signature account into a Safe"),
+    };
+
+    ACCOUNTS.save(ctx.storage, ctx.sender, &account)?;
+
+    // The factory is the admin of all accounts, so it can migrate them.
+    let code_hash = CODE_HASHES.load(ctx.storage, account_type)?;
+
+    Ok(Response::new().add_message(Message::migrate(ctx.sender, code_hash, &Empty {})?))
+}
+
+fn schedule_recurring_transfers(
+    ctx: MutableCtx,
+    next: Option<Timestamp>,
+) -> anyhow::Result<Response> {
+    let account = ACCOUNTS.load(ctx.storage, ctx.sender)?;
+
+    ensure!(
+        matches!(account.params, AccountParams::Safe(_)),
+        "account isn't a Safe"
+    );
+
+    if let Some(previous) = RECURRING_TRANSFER_SCHEDULES.may_take(ctx.storage, ctx.sender)? {
+        RECURRING_TRANSFER_QUEUE.remove(ctx.storage, (previous, ctx.sender));
+    }
+
+    if let Some(next) = next {
+        RECURRING_TRANSFER_SCHEDULES.save(ctx.storage, ctx.sender, &next)?;
+        RECURRING_TRANSFER_QUEUE.insert(ctx.storage, (next, ctx.sender))?;
+    }
+
+    Ok(Response::new())
+}
+
+fn delete_account(ctx: MutableCtx) -> anyhow::Result<Response> {
+    let account = ACCOUNTS.load(ctx.storage, ctx.sender)?;
+
+    let owner = match &account.params {
+        AccountParams::Spot(params) | AccountParams::Margin(params) => params.owner.clone(),
+        AccountParams::Safe(_) => bail!("only spot and margin accounts can be deleted"),
+    };
+
+    ensure!(
+        ctx.querier
+            .query_balances(ctx.sender, None, Some(1))?
+            .is_empty(),
+        "account must be empty before it can be deleted"
+    );
+
+    ensure_no_debt(&ctx.querier, ctx.sender, &account)?;
+
+    // Ensure the user doesn't lock themself out by deleting their only account.
+    ensure!(
+        ACCOUNTS_BY_USER
+            .prefix(&owner)
+            .keys(ctx.storage, None, None, Order::Ascending)
+            .any(|res| res.is_ok_and(|address| address != ctx.sender)),
+        "can't delete the only account of user `{owner}`"
+    );
+
+    // Remove the account from the user's accounts. We keep it in `ACCOUNTS`,
+    // so that the address can't be mistaken for an unknown contract, and mark
+    // it as deleted, so that it rejects incoming transfers.
+    ACCOUNTS_BY_USER.remove(ctx.storage, (&owner, ctx.sender));
+    DELETED_ACCOUNTS.insert(ctx.storage, ctx.sender)?;
+
+    Ok(Response::new())
+}
+
+fn transfer_username(ctx: MutableCtx, key_hash: Hash256, key: Key) -> anyhow::Result<Response> {
+    let username = get_username_by_address(ctx.storage, ctx.sender)?;
+
+    // Remove all existing keys, so that only the new key set controls the
+    // username from now on.
+    KEYS.prefix(&username).clear(ctx.storage, None, None);
+    KEYS.save(ctx.storage, (&username, key_hash), &key)?;
+
+    Ok(Response::new())
+}
+
+fn set_alias(ctx: MutableCtx, alias: Option<Username>) -> anyhow::Result<Response> {
+    let username = get_username_by_address(ctx.storage, ctx.sender)?;
+
+    // Release the existing alias, if any.
+    if let Some(old_alias) = ALIASES.may_take(ctx.storage, &username)? {
+        USERS_BY_ALIAS.remove(ctx.storage, &old_alias);
+    }
+
+    if let Some(alias) = alias {
+        ensure!(
+            !is_name_taken(ctx.storage, &alias),
+            "`{alias}` is already taken by a username or an alias"
+        );
+
+        ALIASES.save(ctx.storage, &username, &alias)?;
+        USERS_BY_ALIAS.save(ctx.storage, &alias, &username)?;
+    }
+
+    Ok(Response::new())
+}
+
+// A name is taken if it's either an existing username or an existing alias.
+//
+// For usernames, we check this by asserting there isn't any key already
+// associated with it, since any existing username necessarily has at least one
+// key associated with it. (However, this key isn't necessarily index 1.)
+fn is_name_taken(storage: &dyn Storage, name: &Username) -> bool {
+    KEYS.prefix(name)
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
+        || USERS_BY_ALIAS.has(storage, name)
+}
+
+fn ensure_no_debt(
+    querier: &QuerierWrapper,
+    address: Addr,
+    account: &Account,
+) -> anyhow::Result<()> {
+    // Only margin accounts can borrow.
+    if let AccountParams::Margin(_) = account.params {
+        let health = querier.query_wasm_smart(address, QueryHealthRequest {})?;
+
+        ensure!(
+            health.debts.is_empty(),
+            "account has outstanding debt: {}",
+            health.debts
+        );
+    }
+
+    Ok(())
+}
+
 fn get_username_by_address(storage: &dyn Storage, address: Addr) -> anyhow::Result<Username> {
     if let AccountParams::Margin(params) | AccountParams::Spot(params) =
         ACCOUNTS.load(storage, address)?.params
@@ -1,9 +1,11 @@
 use {
-    crate::{ACCOUNTS, ACCOUNTS_BY_USER, CODE_HASHES, KEYS, NEXT_ACCOUNT_INDEX},
+    crate::{
+        ACCOUNTS, ACCOUNTS_BY_USER, ALIASES, CODE_HASHES, KEYS, NEXT_ACCOUNT_INDEX, USERS_BY_ALIAS,
+    },
     dango_types::{
         account_factory::{
-            Account, AccountIndex, AccountType, QueryKeyPaginateParam, QueryKeyResponseItem,
-            QueryMsg, User, Username,
+            Account, AccountIndex, AccountParams, AccountType, QueryKeyPaginateParam,
+            QueryKeyResponseItem, QueryMsg, User, Username,
         },
         auth::Key,
     },
@@ -56,6 +58,18 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
             let res = query_user(ctx.storage, username)?;
             res.to_json_value()
         },
+        QueryMsg::Alias { username } => {
+            let res = query_alias(ctx.storage, &username)?;
+            res.to_json_value()
+        },
+        QueryMsg::UserByAlias { alias } => {
+            let res = query_user_by_alias(ctx.storage, &alias)?;
+            res.to_json_value()
+        },
+        QueryMsg::UsernameByAddress { address } => {
+            let res = query_username_by_address(ctx.storage, address)?;
+            res.to_json_value()
+        },
     }
     .map_err(Into::into)
 }
@@ -154,6 +168,30 @@ fn query_accounts_by_user(
 fn query_user(storage: &dyn Storage, username: Username) -> StdResult<User> {
     let keys = query_keys_by_user(storage, &username)?;
     let accounts = query_accounts_by_user(storage, &username)?;
+    let alias = query_alias(storage, &username)?;
 
-    Ok(User { keys, accounts })
+    Ok(User {
+        keys,
+        accounts,
+        alias,
+    })
+}
+
+fn query_alias(storage: &dyn Storage, username: &Username) -> StdResult<Option<Username>> {
+    ALIASES.may_load(storage, username)
+}
+
+fn query_user_by_alias(storage: &dyn Storage, alias: &Username) -> StdResult<Username> {
+    USERS_BY_ALIAS.load(storage, alias)
+}
+
+fn query_username_by_address(storage: &dyn Storage, address: Addr) -> StdResult<Option<Username>> {
+    match ACCOUNTS.load(storage, address)?.params {
+        AccountParams::Spot(params) | AccountParams::Margin(params)
+            if ACCOUNTS_BY_USER.has(storage, (&params.owner, address)) =>
+        {
+            Ok(Some(params.owner))
+        },
+        _ => Ok(None),
+    }
 }
@@ -3,7 +3,7 @@ use {
         account_factory::{Account, AccountIndex, AccountType, Username},
         auth::Key,
     },
-    grug::{Addr, Coins, Counter, Hash256, Item, Map, Set},
+    grug::{Addr, Coins, Counter, Hash256, Item, Map, Set, Timestamp},
 };
 
 pub const MINIMUM_DEPOSIT: Item<Coins> = Item::new("minium_deposit");
@@ -17,3 +17,22 @@ pub const KEYS: Map<(&Username, Hash256), Key> = Map::new("key");
 pub const ACCOUNTS: Map<Addr, Account> = Map::new("account");
 
 pub const ACCOUNTS_BY_USER: Set<(&Username, Addr)> = Set::new("account__user");
+
+/// Accounts that have been deleted by their owners. These accounts reject all
+/// incoming transfers, as nobody can retrieve the funds from them.
+pub const DELETED_ACCOUNTS: Set<Addr> = Set::new("deleted_account");
+
+/// Display aliases, indexed by the usernames they belong to.
+pub const ALIASES: Map<&Username, Username> = Map::new("alias");
+
+/// Reverse lookup of usernames by their aliases.
+pub const USERS_BY_ALIAS: Map<&Username, Username> = Map::new("alias__user");
+
+/// The time each Safe's next recurring transfer is due.
+pub const RECURRING_TRANSFER_SCHEDULES: Map<Addr, Timestamp> =
+    Map::new("recurring_transfer_schedule");
+
+/// Safes indexed by the time their next recurring transfer is due, so that the
+/// cronjob can find the ones that are due.
+pub const RECURRING_TRANSFER_QUEUE: Set<(Timestamp, Addr)> =
+    Set::new("recurring_transfer_schedule__time");
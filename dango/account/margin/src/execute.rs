@@ -1,7 +1,10 @@
 use {
     crate::MarginQuerier,
     anyhow::{anyhow, ensure},
-    dango_auth::authenticate_tx,
+    dango_auth::{
+        authenticate_tx_with_authenticators, backrun_with_authenticators, deregister_authenticator,
+        ensure_not_deleted, register_authenticator,
+    },
     dango_oracle::OracleQuerier,
     dango_types::{
         account::{
@@ -12,8 +15,8 @@ use {
         lending, DangoQuerier,
     },
     grug::{
-        AuthCtx, AuthResponse, Coin, Coins, Denom, Fraction, Inner, IsZero, Message, MutableCtx,
-        Number, NumberConst, QuerierExt, Response, StdResult, Tx, Udec128,
+        AuthCtx, AuthResponse, Coin, Coins, Denom, Empty, Fraction, Inner, IsZero, Message,
+        MutableCtx, Number, NumberConst, QuerierExt, Response, StdResult, SudoCtx, Tx, Udec128,
     },
     std::cmp::{max, min},
 };
@@ -29,22 +32,36 @@ pub fn instantiate(ctx: MutableCtx, _msg: InstantiateMsg) -> anyhow::Result<Resp
     Ok(Response::new())
 }
 
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn migrate(_ctx: SudoCtx, _msg: Empty) -> StdResult<Response> {
+    // Invoked when the account factory changes the account's type. The
+    // storage layout is shared between spot and margin accounts, so there is
+    // nothing to do here.
+    Ok(Response::new())
+}
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
     match msg {
         ExecuteMsg::Liquidate { collateral } => liquidate(ctx, collateral),
+        ExecuteMsg::RegisterAuthenticator { authenticator } => {
+            register_authenticator(ctx, authenticator)
+        },
+        ExecuteMsg::DeregisterAuthenticator { authenticator } => {
+            deregister_authenticator(ctx, authenticator)
+        },
     }
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn authenticate(ctx: AuthCtx, tx: Tx) -> anyhow::Result<AuthResponse> {
-    authenticate_tx(ctx, tx, None)?;
+    authenticate_tx_with_authenticators(ctx, tx)?;
 
     Ok(AuthResponse::new().request_backrun(true))
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn backrun(ctx: AuthCtx, _tx: Tx) -> anyhow::Result<Response> {
+pub fn backrun(ctx: AuthCtx, tx: Tx) -> anyhow::Result<Response> {
     let health = ctx.querier.query_health(ctx.contract, None)?;
 
     // After executing all messages in the transactions, the account must have
@@ -58,12 +75,16 @@ pub fn backrun(ctx: AuthCtx, _tx: Tx) -> anyhow::Result<Response> {
         health.total_adjusted_collateral_value
     );
 
+    backrun_with_authenticators(ctx.storage, &ctx.querier, ctx.contract, &tx)?;
+
     Ok(Response::new())
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn receive(_ctx: MutableCtx) -> StdResult<Response> {
-    // Do nothing, accept all transfers.
+pub fn receive(ctx: MutableCtx) -> anyhow::Result<Response> {
+    // Accept all transfers, unless the account has been deleted.
+    ensure_not_deleted(&ctx.querier, ctx.contract)?;
+
     Ok(Response::new())
 }
 
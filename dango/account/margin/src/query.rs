@@ -1,6 +1,6 @@
 use {
     crate::MarginQuerier,
-    dango_auth::query_seen_nonces,
+    dango_auth::{query_authenticators, query_seen_nonces},
     dango_types::account::margin::QueryMsg,
     grug::{ImmutableCtx, Json, JsonSerExt},
 };
@@ -16,6 +16,10 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
             let res = ctx.querier.query_health(ctx.contract, None)?;
             res.to_json_value()
         },
+        QueryMsg::Authenticators {} => {
+            let res = query_authenticators(ctx.storage)?;
+            res.to_json_value()
+        },
     }
     .map_err(Into::into)
 }
@@ -1,20 +1,28 @@
 use {
-    crate::{NEXT_PROPOSAL_ID, PROPOSALS, VOTES},
+    crate::{
+        ALLOWANCES, NEXT_PROPOSAL_ID, NEXT_RECURRING_TRANSFER_ID, PROPOSALS, RECURRING_TRANSFERS,
+        VOTES,
+    },
     anyhow::{bail, ensure},
     dango_auth::{authenticate_tx, verify_nonce_and_signature},
     dango_types::{
         account::{
-            multi::{ExecuteMsg, Proposal, ProposalId, Status, Vote},
+            multi::{
+                Allowance, ExecuteMsg, Proposal, ProposalId, RecurringTransfer,
+                RecurringTransferId, Status, Vote,
+            },
             InstantiateMsg,
         },
-        account_factory::{QueryAccountRequest, Username},
+        account_factory::{self, QueryAccountRequest, Username},
         auth::Metadata,
         DangoQuerier,
     },
     grug::{
-        AuthCtx, AuthResponse, Inner, JsonDeExt, Message, MsgExecute, MutableCtx, QuerierExt,
-        Response, StdResult, Tx,
+        Addr, AuthCtx, AuthResponse, Coin, Coins, Duration, Inner, JsonDeExt, Message, MsgExecute,
+        MutableCtx, NonZero, Order, QuerierExt, QuerierWrapper, Response, StdResult, Storage,
+        Timestamp, Tx,
     },
+    std::collections::BTreeSet,
 };
 
 #[cfg_attr(not(feature = "library"), grug::export)]
@@ -35,20 +43,20 @@ pub fn authenticate(ctx: AuthCtx, tx: Tx) -> anyhow::Result<AuthResponse> {
 
     // The only type of transaction a Safe account is allowed to emit is to
     // execute itself. Everything else needs to be done through proposals.
-    // Additionally, if the action is proposing or voting, the proposer/voter's
-    // username must match the transaction signer's username.
+    // Additionally, if the action is proposing, voting, or cancelling, the
+    // proposer/voter's username must match the transaction signer's username.
     for msg in tx.msgs.iter() {
         match msg {
             Message::Execute(MsgExecute { contract, msg, .. }) if contract == ctx.contract => {
-                // If the action is to vote for a proposal:
-                //
-                // 1. The voter username in `ExecuteMsg::Vote` must batch
-                //    the signer username in `Metadata`.
-                //
-                // 2. The voter/signer must be a member _at the time the
-                //    proposal was created_. It doesn't matter whether they
-                //    are a member _now_.
                 match msg.clone().deserialize_json::<ExecuteMsg>()? {
+                    // If the action is to vote for a proposal:
+                    //
+                    // 1. The voter username in `ExecuteMsg::Vote` must batch
+                    //    the signer username in `Metadata`.
+                    //
+                    // 2. The voter/signer must be a member _at the time the
+                    //    proposal was created_. It doesn't matter whether they
+                    //    are a member _now_.
                     ExecuteMsg::Vote {
                         proposal_id, voter, ..
                     } => {
@@ -69,17 +77,59 @@ pub fn authenticate(ctx: AuthCtx, tx: Tx) -> anyhow::Result<AuthResponse> {
                             _ => bail!("proposal is not in voting period"),
                         }
                     },
-                    _ => {
+                    // If the action is to cancel a proposal, the signer must be
+                    // the proposal's proposer. Similar to voting, it doesn't
+                    // matter whether they are a member _now_.
+                    ExecuteMsg::Cancel { proposal_id } => {
+                        let proposal = PROPOSALS.load(ctx.storage, proposal_id)?;
+
+                        ensure!(
+                            proposal.proposer == metadata.username,
+                            "only the proposer can cancel a proposal"
+                        );
+                    },
+                    // If the action is to create a proposal, the proposer
+                    // username must match the signer username. Whether the
+                    // signer is a current member is checked below.
+                    ExecuteMsg::Propose { proposer, .. } => {
+                        ensure!(
+                            proposer == metadata.username,
+                            "can't propose with a different username"
+                        );
+
+                        has_non_voting = true;
+                    },
+                    // If the action is to spend from an allowance, the spender
+                    // username must match the signer username. Same as creating
+                    // a proposal, the signer must be a current member.
+                    ExecuteMsg::Spend { spender, .. } => {
+                        ensure!(
+                            spender == metadata.username,
+                            "can't spend with a different username"
+                        );
+
+                        has_non_voting = true;
+                    },
+                    ExecuteMsg::Execute { .. } | ExecuteMsg::ExecuteRecurringTransfers {} => {
                         has_non_voting = true;
                     },
+                    // Managing allowances and recurring transfers can only be
+                    // done through proposals.
+                    ExecuteMsg::SetAllowance { .. }
+                    | ExecuteMsg::RevokeAllowance { .. }
+                    | ExecuteMsg::CreateRecurringTransfer { .. }
+                    | ExecuteMsg::RevokeRecurringTransfer { .. } => {
+                        bail!("this action can only be done through a proposal");
+                    },
                 }
             },
             _ => bail!("the only action a Safe account can do is to execute itself"),
         }
     }
 
-    // If the transaction contains any message that's not voting (i.e. create or
-    // execute a proposal), then the signer must be a _current_ member.
+    // If the transaction contains any message that's not voting or cancelling
+    // (i.e. create or execute a proposal, or spend from an allowance), then the
+    // signer must be a _current_ member.
     if has_non_voting {
         authenticate_tx(ctx, tx, Some(metadata))?;
     } else {
@@ -96,17 +146,23 @@ pub fn receive(_ctx: MutableCtx) -> StdResult<Response> {
 
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
-    ensure!(
-        ctx.sender == ctx.contract,
-        "only the Safe account itself can execute itself"
-    );
+    // Paying out the recurring transfers that are due is permissionless, so
+    // that the account factory's cronjob can make them, and so can anyone else
+    // without the members' involvement.
+    if !matches!(msg, ExecuteMsg::ExecuteRecurringTransfers {}) {
+        ensure!(
+            ctx.sender == ctx.contract,
+            "only the Safe account itself can execute itself"
+        );
+    }
 
     match msg {
         ExecuteMsg::Propose {
+            proposer,
             title,
             description,
             messages,
-        } => propose(ctx, title, description, messages),
+        } => propose(ctx, proposer, title, description, messages),
         ExecuteMsg::Vote {
             proposal_id,
             voter,
@@ -114,11 +170,118 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             execute,
         } => do_vote(ctx, proposal_id, voter, vote, execute),
         ExecuteMsg::Execute { proposal_id } => execute_proposal(ctx, proposal_id),
+        ExecuteMsg::Cancel { proposal_id } => cancel_proposal(ctx, proposal_id),
+        ExecuteMsg::SetAllowance {
+            member,
+            budget,
+            period,
+        } => set_allowance(ctx, member, budget, period),
+        ExecuteMsg::RevokeAllowance { member } => revoke_allowance(ctx, member),
+        ExecuteMsg::Spend {
+            spender,
+            recipient,
+            coins,
+        } => spend(ctx, spender, recipient, coins),
+        ExecuteMsg::CreateRecurringTransfer {
+            recipient,
+            coins,
+            interval,
+        } => create_recurring_transfer(ctx, recipient, coins, interval),
+        ExecuteMsg::RevokeRecurringTransfer { id } => revoke_recurring_transfer(ctx, id),
+        ExecuteMsg::ExecuteRecurringTransfers {} => Ok(execute_recurring_transfers(
+            ctx.storage,
+            ctx.querier,
+            ctx.contract,
+            ctx.block.timestamp,
+        )?),
     }
 }
 
+fn execute_recurring_transfers(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    contract: Addr,
+    now: Timestamp,
+) -> StdResult<Response> {
+    // Find the recurring transfers that are due.
+    let due = RECURRING_TRANSFERS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|res| {
+            res.as_ref()
+                .map_or(true, |(_, transfer)| transfer.next_transfer <= now)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // Query the Safe's balances of the denoms involved, so that we can skip the
+    // transfers the Safe can't afford, instead of failing the whole cronjob.
+    let mut balances = Coins::new();
+
+    for denom in due
+        .iter()
+        .flat_map(|(_, transfer)| transfer.coins.inner().keys())
+        .collect::<BTreeSet<_>>()
+    {
+        let amount = querier.query_balance(contract, denom.clone())?;
+        balances.insert(Coin::new(denom.clone(), amount)?)?;
+    }
+
+    let mut msgs = Vec::with_capacity(due.len());
+
+    for (id, mut transfer) in due {
+        let mut remaining = balances.clone();
+
+        // The Safe can't afford this transfer. Skip it; it will be attempted
+        // again in the next cronjob.
+        if remaining.deduct_many(transfer.coins.clone()).is_err() {
+            continue;
+        }
+
+        balances = remaining;
+
+        msgs.push(Message::transfer(
+            transfer.recipient,
+            transfer.coins.clone(),
+        )?);
+
+        // Move the next transfer past the current time. If the Safe is behind
+        // by more than one interval, the missed transfers are skipped rather
+        // than made all at once.
+        let interval = transfer.interval.into_inner();
+        let missed = (now - transfer.next_transfer).into_nanos() / interval.into_nanos() + 1;
+
+        transfer.next_transfer = transfer.next_transfer + interval * missed;
+
+        RECURRING_TRANSFERS.save(storage, id, &transfer)?;
+    }
+
+    msgs.push(schedule_recurring_transfers(storage, querier)?);
+
+    Ok(Response::new().add_messages(msgs))
+}
+
+/// Tell the account factory when the next recurring transfer is due, so that
+/// its cronjob makes it.
+fn schedule_recurring_transfers(
+    storage: &dyn Storage,
+    querier: QuerierWrapper,
+) -> StdResult<Message> {
+    let next = RECURRING_TRANSFERS
+        .values(storage, None, None, Order::Ascending)
+        .map(|res| res.map(|transfer| transfer.next_transfer))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .min();
+
+    Message::execute(
+        querier.query_account_factory()?,
+        &account_factory::ExecuteMsg::ScheduleRecurringTransfers { next },
+        Coins::new(),
+    )
+}
+
 fn propose(
     ctx: MutableCtx,
+    proposer: Username,
     title: String,
     description: Option<String>,
     messages: Vec<Message>,
@@ -141,7 +304,12 @@ fn propose(
         .params
         .as_safe();
 
+    // Determine the voting rules based on the kinds of messages the proposal
+    // contains.
+    let policy = params.policy_for(&messages);
+
     let proposal = Proposal {
+        proposer,
         title,
         description,
         messages,
@@ -150,7 +318,10 @@ fn propose(
             until: ctx.block.timestamp + params.voting_period.into_inner(),
             yes: 0,
             no: 0,
+            abstain: 0,
+            veto: 0,
             params,
+            policy,
         },
     };
 
@@ -181,33 +352,58 @@ fn do_vote(
     );
 
     // Update vote count in the proposal status.
-    let (params, yes, no) = match &mut proposal.status {
+    let (params, policy, yes, no, abstain, veto) = match &mut proposal.status {
         Status::Voting {
             params,
+            policy,
             until,
             yes,
             no,
+            abstain,
+            veto,
         } => {
             // Ensure voting period hasn't ended yet.
             ensure!(ctx.block.timestamp < *until, "voting period already ended");
 
             // Update the vote count.
+            let power = params.power_of(&voter)?;
+
             match vote {
                 Vote::Yes => {
-                    *yes += params.power_of(&voter)?;
+                    *yes += power;
                 },
                 Vote::No => {
-                    *no += params.power_of(&voter)?;
+                    *no += power;
+                },
+                Vote::Abstain => {
+                    *abstain += power;
+                },
+                Vote::NoWithVeto => {
+                    *veto += power;
                 },
             }
 
-            (&*params, *yes, *no)
+            (&*params, *policy, *yes, *no, *abstain, *veto)
         },
         _ => bail!("proposal is not in voting period"),
     };
 
+    let vetoed = params
+        .veto_threshold
+        .is_some_and(|veto_threshold| veto >= veto_threshold.into_inner());
+
+    let quorum_reached = policy.quorum.map_or(true, |quorum| {
+        yes + no + abstain + veto >= quorum.into_inner()
+    });
+
     // Update the proposal's status, if possible.
-    let msgs = if yes >= params.threshold.into_inner() {
+    let msgs = if vetoed {
+        // The proposal has received enough NO_WITH_VETO votes. It fails,
+        // regardless of how many YES votes it has.
+        proposal.status = Status::Failed;
+
+        vec![]
+    } else if yes >= policy.threshold.into_inner() && quorum_reached {
         // The proposal has received sufficient number of YES votes. It passed.
         // If there's no timelock, and the voter requests to execute the proposal,
         // then execute it.
@@ -241,8 +437,9 @@ fn do_vote(
                 bail!("proposal passes but can't be executed due to timelock");
             },
         }
-    } else if no + params.threshold.into_inner() > params.total_power() {
-        // The proposal has received enough NO vote that it can't pass.
+    } else if no + veto + abstain + policy.threshold.into_inner() > params.total_power() {
+        // The proposal has received enough NO and ABSTAIN votes that the
+        // remaining voting power isn't enough for it to pass.
         proposal.status = Status::Failed;
 
         vec![]
@@ -278,6 +475,117 @@ fn execute_proposal(ctx: MutableCtx, proposal_id: ProposalId) -> anyhow::Result<
     Ok(Response::new().add_messages(msgs))
 }
 
+fn cancel_proposal(ctx: MutableCtx, proposal_id: ProposalId) -> anyhow::Result<Response> {
+    let mut proposal = PROPOSALS.load(ctx.storage, proposal_id)?;
+
+    // The proposal can only be cancelled if it's still being voted on, or it
+    // has passed but not yet been executed.
+    match proposal.status {
+        Status::Voting { until, .. } if ctx.block.timestamp < until => {},
+        Status::Passed { .. } => {},
+        _ => bail!("proposal is not in voting period or pending execution"),
+    }
+
+    proposal.status = Status::Cancelled;
+
+    // Save the updated proposal.
+    PROPOSALS.save(ctx.storage, proposal_id, &proposal)?;
+
+    Ok(Response::new())
+}
+
+fn set_allowance(
+    ctx: MutableCtx,
+    member: Username,
+    budget: Coins,
+    period: NonZero<Duration>,
+) -> anyhow::Result<Response> {
+    ensure!(budget.is_non_empty(), "allowance budget can't be empty");
+
+    let allowance = Allowance {
+        remaining: budget.clone(),
+        budget,
+        period,
+        period_start: ctx.block.timestamp,
+    };
+
+    ALLOWANCES.save(ctx.storage, &member, &allowance)?;
+
+    Ok(Response::new())
+}
+
+fn revoke_allowance(ctx: MutableCtx, member: Username) -> anyhow::Result<Response> {
+    ensure!(
+        ALLOWANCES.has(ctx.storage, &member),
+        "user `{member}` doesn't have an allowance"
+    );
+
+    ALLOWANCES.remove(ctx.storage, &member);
+
+    Ok(Response::new())
+}
+
+fn spend(
+    ctx: MutableCtx,
+    spender: Username,
+    recipient: Addr,
+    coins: Coins,
+) -> anyhow::Result<Response> {
+    let Some(mut allowance) = ALLOWANCES.may_load(ctx.storage, &spender)? else {
+        bail!("user `{spender}` doesn't have an allowance");
+    };
+
+    // Start a new period if the current one has elapsed.
+    allowance.refresh(ctx.block.timestamp);
+
+    for coin in &coins {
+        ensure!(
+            allowance.remaining.amount_of(coin.denom) >= *coin.amount,
+            "insufficient allowance of `{}`: {} < {}",
+            coin.denom,
+            allowance.remaining.amount_of(coin.denom),
+            coin.amount
+        );
+    }
+
+    allowance.remaining.deduct_many(coins.clone())?;
+
+    ALLOWANCES.save(ctx.storage, &spender, &allowance)?;
+
+    Ok(Response::new().add_message(Message::transfer(recipient, coins)?))
+}
+
+fn create_recurring_transfer(
+    ctx: MutableCtx,
+    recipient: Addr,
+    coins: Coins,
+    interval: NonZero<Duration>,
+) -> anyhow::Result<Response> {
+    ensure!(coins.is_non_empty(), "recurring transfer can't be empty");
+
+    let (id, _) = NEXT_RECURRING_TRANSFER_ID.increment(ctx.storage)?;
+
+    RECURRING_TRANSFERS.save(ctx.storage, id, &RecurringTransfer {
+        recipient,
+        coins,
+        interval,
+        next_transfer: ctx.block.timestamp + interval.into_inner(),
+    })?;
+
+    Ok(Response::new().add_message(schedule_recurring_transfers(ctx.storage, ctx.querier)?))
+}
+
+fn revoke_recurring_transfer(ctx: MutableCtx, id: RecurringTransferId) -> anyhow::Result<Response> {
+    ensure!(
+        RECURRING_TRANSFERS.has(ctx.storage, id),
+        "recurring transfer `{id}` doesn't exist"
+    );
+
+    RECURRING_TRANSFERS.remove(ctx.storage, id);
+
+    Ok(Response::new().add_message(schedule_recurring_transfers(ctx.storage, ctx.querier)?))
+}
+
 // ----------------------------------- tests -----------------------------------
 
 #[cfg(test)]
@@ -286,14 +594,14 @@ mod tests {
         super::*,
         dango_account_factory::ACCOUNTS_BY_USER,
         dango_types::{
-            account::multi::{self, Params},
+            account::multi::{self, MessageKind, Params, Policy, Power},
             account_factory::{self, Account, AccountParams},
             config::{AppAddresses, AppConfig},
         },
         grug::{
-            btree_map, Addr, AuthMode, Coins, Duration, GenericResult, GenericResultExt, Json,
-            JsonSerExt, MockContext, MockQuerier, NonEmpty, NonZero, ResultExt, Timestamp,
-            MOCK_BLOCK,
+            btree_map, Addr, AuthMode, Coins, Duration, Empty, GenericResult, GenericResultExt,
+            Hash256, Json, JsonSerExt, MockContext, MockQuerier, NonEmpty, NonZero, ResultExt,
+            Timestamp, MOCK_BLOCK,
         },
         std::{collections::BTreeMap, str::FromStr},
         test_case::test_case,
@@ -382,6 +690,11 @@ mod tests {
                     chain_id,
                     nonce: 0,
                     expiry: None,
+                    referrer: None,
+                    fee_denom: None,
+                    granter: None,
+                    max_fee_rate: None,
+                    tip_rate: None,
                 }
                 .to_json_value()
                 .unwrap(),
@@ -415,6 +728,11 @@ mod tests {
                     chain_id: "".to_string(),
                     nonce: 0,
                     expiry: None,
+                    referrer: None,
+                    fee_denom: None,
+                    granter: None,
+                    max_fee_rate: None,
+                    tip_rate: None,
                 }
                 .to_json_value()
                 .unwrap(),
@@ -442,6 +760,9 @@ mod tests {
             },
             voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
             threshold: NonZero::new(2).unwrap(),
+            quorum: None,
+            veto_threshold: None,
+            policies: btree_map! {},
             timelock: None,
         };
 
@@ -478,15 +799,26 @@ mod tests {
 
         // Create the 1st proposal.
         {
-            propose(ctx.as_mutable(), "first".to_string(), None, vec![]).unwrap();
+            propose(
+                ctx.as_mutable(),
+                m1.clone(),
+                "first".to_string(),
+                None,
+                vec![],
+            )
+            .unwrap();
 
             let proposal = PROPOSALS.load(&ctx.storage, 1).unwrap();
 
+            assert_eq!(proposal.proposer, m1);
             assert_eq!(proposal.status, Status::Voting {
                 params: params.clone(),
+                policy: params.default_policy(),
                 until: ctx.block.timestamp + params.voting_period.into_inner(),
                 yes: 0,
-                no: 0
+                no: 0,
+                abstain: 0,
+                veto: 0,
             });
         }
 
@@ -513,7 +845,14 @@ mod tests {
 
         // Create the 2nd proposal. It should use the updated params.
         {
-            propose(ctx.as_mutable(), "second".to_string(), None, vec![]).unwrap();
+            propose(
+                ctx.as_mutable(),
+                m2.clone(),
+                "second".to_string(),
+                None,
+                vec![],
+            )
+            .unwrap();
 
             let proposal = PROPOSALS.load(&ctx.storage, 2).unwrap();
 
@@ -532,11 +871,20 @@ mod tests {
                 members: btree_map! {},
                 voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
                 threshold: NonZero::new(2).unwrap(),
+                quorum: None,
+                veto_threshold: None,
+                policies: btree_map! {},
                 timelock: None,
             },
+            policy: Policy {
+                threshold: NonZero::new(2).unwrap(),
+                quorum: None,
+            },
             until: Timestamp::from_seconds(200),
             yes: 0,
             no: 0,
+            abstain: 0,
+            veto: 0,
         },
         |result| result.is_err_and(|err| {
             err.to_string().contains("voting period already ended")
@@ -580,6 +928,7 @@ mod tests {
         // Save the proposal.
         PROPOSALS
             .save(&mut ctx.storage, proposal_id, &Proposal {
+                proposer: Username::from_str("member1").unwrap(),
                 title: "title".to_string(),
                 description: None,
                 messages: vec![],
@@ -702,6 +1051,30 @@ mod tests {
         |proposal| matches!(proposal.status, Status::Voting { yes: 1, no: 1, .. });
         "not enough vote to either pass or fail yet"
     )]
+    #[test_case(
+        btree_map! {
+            Username::from_str("member1").unwrap() => Vote::Abstain,
+        },
+        Username::from_str("member2").unwrap(),
+        Vote::NoWithVeto,
+        false,
+        None,
+        |result| result.is_ok_and(|res| res.submsgs.is_empty()),
+        |proposal| proposal.status == Status::Failed;
+        "proposal fails prematurely due to abstentions"
+    )]
+    #[test_case(
+        btree_map! {
+            Username::from_str("member1").unwrap() => Vote::Yes,
+        },
+        Username::from_str("member2").unwrap(),
+        Vote::Abstain,
+        false,
+        None,
+        |result| result.is_ok_and(|res| res.submsgs.is_empty()),
+        |proposal| matches!(proposal.status, Status::Voting { yes: 1, abstain: 1, .. });
+        "abstained, not enough vote to either pass or fail yet"
+    )]
     fn voting(
         previous_votes: BTreeMap<Username, Vote>,
         voter: Username,
@@ -721,27 +1094,18 @@ mod tests {
             member3.clone() => NonZero::new(1).unwrap(),
         };
 
-        let previous_yes_votes = previous_votes
-            .iter()
-            .map(|(voter, vote)| {
-                if *vote == Vote::Yes {
-                    members.get(voter).unwrap().into_inner()
-                } else {
-                    0
-                }
-            })
-            .sum();
-
-        let previous_no_votes = previous_votes
-            .iter()
-            .map(|(voter, vote)| {
-                if *vote == Vote::No {
-                    members.get(voter).unwrap().into_inner()
-                } else {
-                    0
-                }
-            })
-            .sum();
+        let previous_votes_of = |option: Vote| -> Power {
+            previous_votes
+                .iter()
+                .filter(|(_, vote)| **vote == option)
+                .map(|(voter, _)| members.get(voter).unwrap().into_inner())
+                .sum()
+        };
+
+        let previous_yes_votes = previous_votes_of(Vote::Yes);
+        let previous_no_votes = previous_votes_of(Vote::No);
+        let previous_abstain_votes = previous_votes_of(Vote::Abstain);
+        let previous_veto_votes = previous_votes_of(Vote::NoWithVeto);
 
         let mut ctx = MockContext::new()
             .with_sender(SAFE)
@@ -752,6 +1116,7 @@ mod tests {
         // Save the proposal.
         PROPOSALS
             .save(&mut ctx.storage, proposal_id, &Proposal {
+                proposer: Username::from_str("member1").unwrap(),
                 title: "title".to_string(),
                 description: None,
                 messages: vec![Message::transfer(
@@ -764,11 +1129,20 @@ mod tests {
                         members,
                         voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
                         threshold: NonZero::new(2).unwrap(),
+                        quorum: None,
+                        veto_threshold: None,
+                        policies: btree_map! {},
                         timelock: timelock.map(|d| NonZero::new(d).unwrap()),
                     },
+                    policy: Policy {
+                        threshold: NonZero::new(2).unwrap(),
+                        quorum: None,
+                    },
                     until: Timestamp::from_seconds(200),
                     yes: previous_yes_votes,
                     no: previous_no_votes,
+                    abstain: previous_abstain_votes,
+                    veto: previous_veto_votes,
                 },
             })
             .unwrap();
@@ -801,11 +1175,20 @@ mod tests {
                 members: btree_map! {},
                 voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
                 threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+                veto_threshold: None,
+                policies: btree_map! {},
                 timelock: None,
             },
+            policy: Policy {
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+            },
             until: Timestamp::from_seconds(100),
             yes: 0,
             no: 0,
+            abstain: 0,
+            veto: 0,
         },
         GenericResult::Err("proposal isn't passed or timelock hasn't elapsed".to_string());
         "proposal still voting"
@@ -844,6 +1227,7 @@ mod tests {
 
         PROPOSALS
             .save(&mut ctx.storage, proposal_id, &Proposal {
+                proposer: Username::from_str("member1").unwrap(),
                 title: "title".to_string(),
                 description: None,
                 messages: vec![],
@@ -853,4 +1237,372 @@ mod tests {
 
         execute_proposal(ctx.as_mutable(), proposal_id).should_match(expect);
     }
+
+    #[test]
+    fn determining_policy() {
+        let params = Params {
+            members: btree_map! {
+                Username::from_str("member1").unwrap() => NonZero::new(1).unwrap(),
+                Username::from_str("member2").unwrap() => NonZero::new(1).unwrap(),
+                Username::from_str("member3").unwrap() => NonZero::new(1).unwrap(),
+            },
+            voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
+            threshold: NonZero::new(2).unwrap(),
+            quorum: None,
+            veto_threshold: None,
+            policies: btree_map! {
+                MessageKind::Transfer => Policy {
+                    threshold: NonZero::new(1).unwrap(),
+                    quorum: None,
+                },
+                MessageKind::Migrate => Policy {
+                    threshold: NonZero::new(3).unwrap(),
+                    quorum: Some(NonZero::new(3).unwrap()),
+                },
+            },
+            timelock: None,
+        };
+
+        let transfer = Message::transfer(Addr::mock(123), Coins::one("uusdc", 1).unwrap()).unwrap();
+        let migrate = Message::migrate(Addr::mock(123), Hash256::ZERO, &Empty {}).unwrap();
+        let execute = Message::execute(Addr::mock(123), &Empty {}, Coins::new()).unwrap();
+
+        // No message. The default policy applies.
+        assert_eq!(params.policy_for(&[]), params.default_policy());
+
+        // Only transfers. The transfer policy applies.
+        assert_eq!(
+            params.policy_for(&[transfer.clone(), transfer.clone()]),
+            Policy {
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+            }
+        );
+
+        // Transfer and execute. The default policy is more restrictive.
+        assert_eq!(
+            params.policy_for(&[transfer.clone(), execute]),
+            params.default_policy()
+        );
+
+        // Transfer and migrate. The migrate policy is more restrictive.
+        assert_eq!(params.policy_for(&[transfer, migrate]), Policy {
+            threshold: NonZero::new(3).unwrap(),
+            quorum: Some(NonZero::new(3).unwrap()),
+        });
+    }
+
+    #[test]
+    fn voting_with_quorum_and_veto() {
+        let members = ["member1", "member2", "member3", "member4", "member5"]
+            .into_iter()
+            .map(|name| (Username::from_str(name).unwrap(), NonZero::new(1).unwrap()))
+            .collect::<BTreeMap<_, _>>();
+
+        let params = Params {
+            members,
+            voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
+            threshold: NonZero::new(2).unwrap(),
+            quorum: Some(NonZero::new(4).unwrap()),
+            veto_threshold: Some(NonZero::new(2).unwrap()),
+            policies: btree_map! {},
+            timelock: None,
+        };
+
+        let mut ctx = MockContext::new()
+            .with_block_timestamp(Timestamp::from_seconds(100))
+            .with_sender(SAFE)
+            .with_funds(Coins::new());
+
+        for proposal_id in [1, 2] {
+            PROPOSALS
+                .save(&mut ctx.storage, proposal_id, &Proposal {
+                    proposer: Username::from_str("member1").unwrap(),
+                    title: "title".to_string(),
+                    description: None,
+                    messages: vec![],
+                    status: Status::Voting {
+                        params: params.clone(),
+                        policy: params.default_policy(),
+                        until: Timestamp::from_seconds(200),
+                        yes: 0,
+                        no: 0,
+                        abstain: 0,
+                        veto: 0,
+                    },
+                })
+                .unwrap();
+        }
+
+        // Proposal 1: the threshold is reached early, but the proposal can only
+        // pass once the quorum is also reached.
+        for (voter, vote, passed) in [
+            ("member1", Vote::Yes, false),
+            ("member2", Vote::Yes, false),
+            ("member3", Vote::Abstain, false),
+            ("member4", Vote::No, true),
+        ] {
+            do_vote(
+                ctx.as_mutable(),
+                1,
+                Username::from_str(voter).unwrap(),
+                vote,
+                false,
+            )
+            .unwrap();
+
+            let proposal = PROPOSALS.load(&ctx.storage, 1).unwrap();
+            assert_eq!(matches!(proposal.status, Status::Passed { .. }), passed);
+        }
+
+        // Proposal 2: the proposal fails once the veto threshold is reached,
+        // even though the remaining members could still vote YES.
+        for (voter, vote, failed) in [
+            ("member1", Vote::Yes, false),
+            ("member2", Vote::NoWithVeto, false),
+            ("member3", Vote::NoWithVeto, true),
+        ] {
+            do_vote(
+                ctx.as_mutable(),
+                2,
+                Username::from_str(voter).unwrap(),
+                vote,
+                false,
+            )
+            .unwrap();
+
+            let proposal = PROPOSALS.load(&ctx.storage, 2).unwrap();
+            assert_eq!(proposal.status == Status::Failed, failed);
+        }
+    }
+
+    #[test_case(
+        Status::Voting {
+            params: Params {
+                members: btree_map! {},
+                voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+                veto_threshold: None,
+                policies: btree_map! {},
+                timelock: None,
+            },
+            policy: Policy {
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+            },
+            until: Timestamp::from_seconds(300),
+            yes: 0,
+            no: 0,
+            abstain: 0,
+            veto: 0,
+        },
+        GenericResult::Ok(Response::new());
+        "proposal still voting"
+    )]
+    #[test_case(
+        Status::Voting {
+            params: Params {
+                members: btree_map! {},
+                voting_period: NonZero::new(Duration::from_seconds(100)).unwrap(),
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+                veto_threshold: None,
+                policies: btree_map! {},
+                timelock: None,
+            },
+            policy: Policy {
+                threshold: NonZero::new(1).unwrap(),
+                quorum: None,
+            },
+            until: Timestamp::from_seconds(100),
+            yes: 0,
+            no: 0,
+            abstain: 0,
+            veto: 0,
+        },
+        GenericResult::Err("proposal is not in voting period or pending execution".to_string());
+        "voting period ended"
+    )]
+    #[test_case(
+        Status::Passed {
+            execute_after: Timestamp::from_seconds(500),
+        },
+        GenericResult::Ok(Response::new());
+        "proposal passed but not yet executed"
+    )]
+    #[test_case(
+        Status::Failed,
+        GenericResult::Err("proposal is not in voting period or pending execution".to_string());
+        "proposal failed"
+    )]
+    #[test_case(
+        Status::Executed,
+        GenericResult::Err("proposal is not in voting period or pending execution".to_string());
+        "proposal already executed"
+    )]
+    #[test_case(
+        Status::Cancelled,
+        GenericResult::Err("proposal is not in voting period or pending execution".to_string());
+        "proposal already cancelled"
+    )]
+    fn cancelling(status: Status, expect: GenericResult<Response>) {
+        let mut ctx = MockContext::new()
+            .with_block_timestamp(Timestamp::from_seconds(200))
+            .with_sender(SAFE)
+            .with_funds(Coins::new());
+
+        let proposal_id = 123;
+
+        PROPOSALS
+            .save(&mut ctx.storage, proposal_id, &Proposal {
+                proposer: Username::from_str("member1").unwrap(),
+                title: "title".to_string(),
+                description: None,
+                messages: vec![],
+                status,
+            })
+            .unwrap();
+
+        let succeeded = expect.is_ok();
+
+        cancel_proposal(ctx.as_mutable(), proposal_id).should_match(expect);
+
+        if succeeded {
+            let proposal = PROPOSALS.load(&ctx.storage, proposal_id).unwrap();
+            assert_eq!(proposal.status, Status::Cancelled);
+        }
+    }
+
+    #[test]
+    fn spending_allowance() {
+        let member = Username::from_str("member1").unwrap();
+
+        let mut ctx = MockContext::new()
+            .with_block_timestamp(Timestamp::from_seconds(100))
+            .with_contract(SAFE)
+            .with_sender(SAFE)
+            .with_funds(Coins::new());
+
+        set_allowance(
+            ctx.as_mutable(),
+            member.clone(),
+            Coins::one("uusdc", 100).unwrap(),
+            NonZero::new(Duration::from_seconds(50)).unwrap(),
+        )
+        .should_succeed();
+
+        // Spend within the budget. Should succeed.
+        spend(
+            ctx.as_mutable(),
+            member.clone(),
+            Addr::mock(123),
+            Coins::one("uusdc", 60).unwrap(),
+        )
+        .should_succeed_and(|res| res.submsgs.len() == 1);
+
+        // Spend more than what's remaining in the period. Should fail.
+        spend(
+            ctx.as_mutable(),
+            member.clone(),
+            Addr::mock(123),
+            Coins::one("uusdc", 50).unwrap(),
+        )
+        .should_fail_with_error("insufficient allowance of `uusdc`: 40 < 50");
+
+        // Spend a denom that isn't in the budget. Should fail.
+        spend(
+            ctx.as_mutable(),
+            member.clone(),
+            Addr::mock(123),
+            Coins::one("uatom", 1).unwrap(),
+        )
+        .should_fail_with_error("insufficient allowance of `uatom`: 0 < 1");
+
+        // Once the period has elapsed, the full budget is available again.
+        ctx.set_block_timestamp(Timestamp::from_seconds(150));
+
+        spend(
+            ctx.as_mutable(),
+            member.clone(),
+            Addr::mock(123),
+            Coins::one("uusdc", 100).unwrap(),
+        )
+        .should_succeed();
+
+        // Revoke the allowance. Spending should fail.
+        revoke_allowance(ctx.as_mutable(), member.clone()).should_succeed();
+
+        spend(
+            ctx.as_mutable(),
+            member,
+            Addr::mock(123),
+            Coins::one("uusdc", 1).unwrap(),
+        )
+        .should_fail_with_error("user `member1` doesn't have an allowance");
+    }
+
+    #[test]
+    fn executing_recurring_transfers() {
+        // The Safe only has enough balance for one of the two transfers.
+        let querier = MockQuerier::new()
+            .with_app_config(AppConfig {
+                addresses: AppAddresses {
+                    account_factory: ACCOUNT_FACTORY,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap()
+            .with_balance(SAFE, "uusdc", 150)
+            .unwrap();
+
+        let mut ctx = MockContext::new()
+            .with_querier(querier)
+            .with_contract(SAFE)
+            .with_sender(Addr::mock(123))
+            .with_funds(Coins::new());
+
+        for (id, recipient, interval) in [(1, Addr::mock(1), 10), (2, Addr::mock(2), 20)] {
+            RECURRING_TRANSFERS
+                .save(&mut ctx.storage, id, &RecurringTransfer {
+                    recipient,
+                    coins: Coins::one("uusdc", 100).unwrap(),
+                    interval: NonZero::new(Duration::from_seconds(interval)).unwrap(),
+                    next_transfer: Timestamp::from_seconds(100 + interval),
+                })
+                .unwrap();
+        }
+
+        // No transfer is due yet. The only message is the one scheduling the
+        // next transfer with the account factory.
+        ctx.set_block_timestamp(Timestamp::from_seconds(105));
+
+        execute(ctx.as_mutable(), ExecuteMsg::ExecuteRecurringTransfers {})
+            .should_succeed_and(|res| res.submsgs.len() == 1);
+
+        // Both transfers are due, but the Safe can only afford the first one.
+        // The second one should be skipped, and not have its time advanced.
+        // The first one is two intervals behind, but is only made once, and its
+        // time is advanced past the current time.
+        ctx.set_block_timestamp(Timestamp::from_seconds(120));
+
+        execute(ctx.as_mutable(), ExecuteMsg::ExecuteRecurringTransfers {})
+            .should_succeed_and(|res| res.submsgs.len() == 2);
+
+        assert_eq!(
+            RECURRING_TRANSFERS
+                .load(&ctx.storage, 1)
+                .unwrap()
+                .next_transfer,
+            Timestamp::from_seconds(130)
+        );
+        assert_eq!(
+            RECURRING_TRANSFERS
+                .load(&ctx.storage, 2)
+                .unwrap()
+                .next_transfer,
+            Timestamp::from_seconds(120)
+        );
+    }
 }
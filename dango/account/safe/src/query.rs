@@ -1,8 +1,11 @@
 use {
-    crate::{PROPOSALS, VOTES},
+    crate::{ALLOWANCES, PROPOSALS, RECURRING_TRANSFERS, VOTES},
     dango_auth::query_seen_nonces,
     dango_types::{
-        account::multi::{Proposal, ProposalId, QueryMsg, Status, Vote},
+        account::multi::{
+            Allowance, Proposal, ProposalId, QueryMsg, RecurringTransfer, RecurringTransferId,
+            Status, Vote,
+        },
         account_factory::Username,
     },
     grug::{Bound, ImmutableCtx, Json, JsonSerExt, Order, StdResult, Storage},
@@ -37,6 +40,22 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
             let res = query_votes(ctx.storage, proposal_id)?;
             res.to_json_value()
         },
+        QueryMsg::Allowance { member } => {
+            let res = query_allowance(ctx, member)?;
+            res.to_json_value()
+        },
+        QueryMsg::Allowances { start_after, limit } => {
+            let res = query_allowances(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::RecurringTransfer { id } => {
+            let res = query_recurring_transfer(ctx.storage, id)?;
+            res.to_json_value()
+        },
+        QueryMsg::RecurringTransfers { start_after, limit } => {
+            let res = query_recurring_transfers(ctx.storage, start_after, limit)?;
+            res.to_json_value()
+        },
     }
 }
 
@@ -97,13 +116,66 @@ fn query_votes(
         .collect()
 }
 
+fn query_allowance(ctx: ImmutableCtx, member: Username) -> StdResult<Option<Allowance>> {
+    let maybe_allowance = ALLOWANCES.may_load(ctx.storage, &member)?;
+
+    // If the current period has elapsed, the member's allowance is effectively
+    // reset to the full budget.
+    Ok(maybe_allowance.map(|mut allowance| {
+        allowance.refresh(ctx.block.timestamp);
+        allowance
+    }))
+}
+
+fn query_allowances(
+    ctx: ImmutableCtx,
+    start_after: Option<Username>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Username, Allowance>> {
+    let start = start_after.as_ref().map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    ALLOWANCES
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| {
+            let (member, mut allowance) = res?;
+
+            allowance.refresh(ctx.block.timestamp);
+
+            Ok((member, allowance))
+        })
+        .collect()
+}
+
+fn query_recurring_transfer(
+    storage: &dyn Storage,
+    id: RecurringTransferId,
+) -> StdResult<RecurringTransfer> {
+    RECURRING_TRANSFERS.load(storage, id)
+}
+
+fn query_recurring_transfers(
+    storage: &dyn Storage,
+    start_after: Option<RecurringTransferId>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<RecurringTransferId, RecurringTransfer>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    RECURRING_TRANSFERS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
 // ----------------------------------- tests -----------------------------------
 
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        dango_types::account::multi::Params,
+        dango_types::account::multi::{Params, Policy},
         grug::{btree_map, MockContext, NonZero, Timestamp},
         std::str::FromStr,
     };
@@ -115,6 +187,7 @@ mod tests {
         let proposal_id = 123;
 
         let proposal = Proposal {
+            proposer: Username::from_str("member1").unwrap(),
             title: "title".to_string(),
             description: None,
             messages: vec![],
@@ -127,11 +200,20 @@ mod tests {
                     },
                     voting_period: NonZero::new(Timestamp::from_seconds(100)).unwrap(),
                     threshold: NonZero::new(2).unwrap(),
+                    quorum: None,
+                    veto_threshold: None,
+                    policies: btree_map! {},
                     timelock: None,
                 },
+                policy: Policy {
+                    threshold: NonZero::new(2).unwrap(),
+                    quorum: None,
+                },
                 until: Timestamp::from_seconds(100),
                 yes: 1,
                 no: 1,
+                abstain: 0,
+                veto: 0,
             },
         };
 
@@ -1,6 +1,8 @@
 use {
     dango_types::{
-        account::multi::{Proposal, ProposalId, Vote},
+        account::multi::{
+            Allowance, Proposal, ProposalId, RecurringTransfer, RecurringTransferId, Vote,
+        },
         account_factory::Username,
     },
     grug::{Counter, Map, Serde},
@@ -13,3 +15,11 @@ pub const NEXT_PROPOSAL_ID: Counter<ProposalId> = Counter::new("next_proposal_id
 pub const PROPOSALS: Map<ProposalId, Proposal, Serde> = Map::new("proposal");
 
 pub const VOTES: Map<(ProposalId, &Username), Vote> = Map::new("vote");
+
+pub const ALLOWANCES: Map<&Username, Allowance> = Map::new("allowance");
+
+pub const NEXT_RECURRING_TRANSFER_ID: Counter<RecurringTransferId> =
+    Counter::new("next_recurring_transfer_id", 1, 1);
+
+pub const RECURRING_TRANSFERS: Map<RecurringTransferId, RecurringTransfer> =
+    Map::new("recurring_transfer");
@@ -2,10 +2,16 @@
 use grug::ResultExt;
 use {
     anyhow::ensure,
-    dango_auth::authenticate_tx,
-    dango_types::{account::spot::InstantiateMsg, bank, DangoQuerier},
+    dango_auth::{
+        authenticate_tx_with_authenticators, backrun_with_authenticators, deregister_authenticator,
+        ensure_not_deleted, register_authenticator,
+    },
+    dango_types::{
+        account::spot::{ExecuteMsg, InstantiateMsg},
+        bank, DangoQuerier,
+    },
     grug::{
-        AuthCtx, AuthResponse, Coins, Message, MutableCtx, QuerierExt, Response, StdResult,
+        AuthCtx, AuthResponse, Coins, Empty, Message, MutableCtx, QuerierExt, Response, StdResult,
         SubMessage, SubMsgResult, SudoCtx, Tx,
     },
 };
@@ -40,16 +46,46 @@ pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> anyhow::Result<Respo
     )
 }
 
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn migrate(_ctx: SudoCtx, _msg: Empty) -> StdResult<Response> {
+    // Invoked when the account factory changes the account's type. The
+    // storage layout is shared between spot and margin accounts, so there is
+    // nothing to do here.
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::RegisterAuthenticator { authenticator } => {
+            register_authenticator(ctx, authenticator)
+        },
+        ExecuteMsg::DeregisterAuthenticator { authenticator } => {
+            deregister_authenticator(ctx, authenticator)
+        },
+    }
+}
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn authenticate(ctx: AuthCtx, tx: Tx) -> anyhow::Result<AuthResponse> {
-    authenticate_tx(ctx, tx, None)?;
+    let has_authenticators = authenticate_tx_with_authenticators(ctx, tx)?;
 
-    Ok(AuthResponse::new().request_backrun(false))
+    // Only request a backrun if there are authenticators to consult.
+    Ok(AuthResponse::new().request_backrun(has_authenticators))
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn receive(_ctx: MutableCtx) -> StdResult<Response> {
-    // Do nothing, accept all transfers.
+pub fn backrun(ctx: AuthCtx, tx: Tx) -> anyhow::Result<Response> {
+    backrun_with_authenticators(ctx.storage, &ctx.querier, ctx.contract, &tx)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn receive(ctx: MutableCtx) -> anyhow::Result<Response> {
+    // Accept all transfers, unless the account has been deleted.
+    ensure_not_deleted(&ctx.querier, ctx.contract)?;
+
     Ok(Response::new())
 }
 
@@ -1,5 +1,5 @@
 use {
-    dango_auth::query_seen_nonces,
+    dango_auth::{query_authenticators, query_seen_nonces},
     dango_types::account::spot::QueryMsg,
     grug::{ImmutableCtx, Json, JsonSerExt, StdResult},
 };
@@ -11,5 +11,9 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
             let res = query_seen_nonces(ctx.storage)?;
             res.to_json_value()
         },
+        QueryMsg::Authenticators {} => {
+            let res = query_authenticators(ctx.storage)?;
+            res.to_json_value()
+        },
     }
 }
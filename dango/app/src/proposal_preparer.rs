@@ -1,16 +1,18 @@
 use {
     dango_types::{
+        auth::Metadata,
         config::AppConfig,
         oracle::{ExecuteMsg, PriceSource, QueryPriceSourcesRequest},
+        taxman::QueryBaseFeeRequest,
     },
     grug::{
-        Binary, Coins, Json, JsonSerExt, Message, NonEmpty, QuerierExt, QuerierWrapper, StdError,
-        Tx,
+        Binary, Coins, Json, JsonDeExt, JsonSerExt, Message, NonEmpty, Number, NumberConst,
+        QuerierExt, QuerierWrapper, StdError, Tx, Udec128,
     },
     grug_app::{AppError, Shared},
     prost::bytes::Bytes,
     std::{
-        cmp::min,
+        cmp::{min, Reverse},
         ops::Mul,
         thread::{self, JoinHandle},
         time::Duration,
@@ -174,6 +176,12 @@ impl grug_app::ProposalPreparer for ProposalPreparer {
         _max_tx_bytes: usize,
     ) -> Result<Vec<Bytes>, Self::Error> {
         let cfg: AppConfig = querier.query_app_config()?;
+        let taxman = querier.query_config()?.taxman;
+        let base_fee = querier.query_wasm_smart(taxman, QueryBaseFeeRequest {})?;
+
+        // Order the transactions by the tips they pay on top of the base fee,
+        // highest first. Transactions with the same tip keep their order.
+        txs.sort_by_cached_key(|tx| Reverse(tip_rate_of(tx, base_fee)));
 
         // Retrieve the price ids from the oracle and prepare the query params.
         // TODO: optimize this by using the raw WasmScan query.
@@ -231,11 +239,94 @@ impl grug_app::ProposalPreparer for ProposalPreparer {
     }
 }
 
+/// Returns the tip per gas unit a transaction effectively pays on top of the
+/// given base fee: the tip specified in its metadata, capped at its maximum fee
+/// rate minus the base fee. Zero if not specified, or if the transaction can't
+/// be decoded.
+fn tip_rate_of(tx: &Bytes, base_fee: Udec128) -> Udec128 {
+    let Ok(metadata) = tx
+        .deserialize_json::<Tx>()
+        .and_then(|tx| tx.data.deserialize_json::<Metadata>())
+    else {
+        return Udec128::ZERO;
+    };
+
+    let tip_rate = metadata.tip_rate.unwrap_or(Udec128::ZERO);
+
+    match metadata.max_fee_rate {
+        Some(max_fee_rate) => min(
+            tip_rate,
+            max_fee_rate.checked_sub(base_fee).unwrap_or(Udec128::ZERO),
+        ),
+        None => tip_rate,
+    }
+}
+
 // ----------------------------------- tests -----------------------------------
 
 #[cfg(test)]
 mod test {
-    use {super::LatestVaaResponse, grug::JsonDeExt};
+    use {
+        super::{tip_rate_of, LatestVaaResponse},
+        dango_types::{account_factory::Username, auth::Metadata},
+        grug::{Addr, Coins, Json, JsonDeExt, JsonSerExt, Message, NonEmpty, Tx, Udec128},
+        prost::bytes::Bytes,
+        std::{cmp::Reverse, str::FromStr},
+    };
+
+    fn mock_tx(tip_rate: Option<Udec128>, max_fee_rate: Option<Udec128>) -> Bytes {
+        Tx {
+            sender: Addr::mock(1),
+            gas_limit: 1_000_000,
+            msgs: NonEmpty::new_unchecked(vec![
+                Message::transfer(Addr::mock(2), Coins::new()).unwrap()
+            ]),
+            data: Metadata {
+                username: Username::from_str("alice").unwrap(),
+                chain_id: "dev-1".to_string(),
+                nonce: 0,
+                expiry: None,
+                referrer: None,
+                fee_denom: None,
+                granter: None,
+                max_fee_rate,
+                tip_rate,
+            }
+            .to_json_value()
+            .unwrap(),
+            credential: Json::null(),
+        }
+        .to_json_vec()
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn ordering_txs_by_tip() {
+        let base_fee = Udec128::new_percent(10);
+        let no_tip = mock_tx(None, None);
+        let low_tip = mock_tx(Some(Udec128::new_percent(2)), None);
+        let high_tip = mock_tx(Some(Udec128::new_percent(5)), None);
+        // Specifies the highest tip, but its maximum fee rate leaves room for
+        // only 1% on top of the base fee.
+        let capped_tip = mock_tx(
+            Some(Udec128::new_percent(8)),
+            Some(Udec128::new_percent(11)),
+        );
+        let invalid = Bytes::from_static(b"invalid");
+
+        let mut txs = vec![
+            no_tip.clone(),
+            capped_tip.clone(),
+            low_tip.clone(),
+            invalid.clone(),
+            high_tip.clone(),
+        ];
+
+        txs.sort_by_cached_key(|tx| Reverse(tip_rate_of(tx, base_fee)));
+
+        assert_eq!(txs, vec![high_tip, low_tip, capped_tip, no_tip, invalid]);
+    }
 
     #[test]
     fn deserializing_pyth_response() {
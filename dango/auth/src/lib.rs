@@ -3,19 +3,21 @@ use {
         dyn_abi::{Eip712Domain, TypedData},
         primitives::U160,
     },
-    anyhow::{bail, ensure},
+    anyhow::{anyhow, bail, ensure},
     base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine},
-    dango_account_factory::{ACCOUNTS_BY_USER, KEYS},
+    dango_account_factory::{ACCOUNTS_BY_USER, DELETED_ACCOUNTS, KEYS},
     dango_types::{
+        account::spot,
         auth::{
-            ClientData, Credential, Key, Metadata, Nonce, SessionInfo, SignDoc, Signature,
-            StandardCredential,
+            ClientData, Credential, Key, Metadata, Nonce, QueryAuthenticateRequest,
+            QueryBackrunRequest, SessionInfo, SignDoc, Signature, StandardCredential,
         },
         DangoQuerier,
     },
     grug::{
-        json, Addr, Api, AuthCtx, AuthMode, Inner, Item, JsonDeExt, JsonSerExt, QuerierExt,
-        StdResult, Storage, StorageQuerier, Tx,
+        json, Addr, Api, AuthCtx, AuthMode, Inner, Item, JsonDeExt, JsonSerExt, Message,
+        MsgExecute, MutableCtx, Order, QuerierExt, QuerierWrapper, Response, Set, StdResult,
+        Storage, StorageQuerier, Tx,
     },
     std::collections::BTreeSet,
 };
@@ -36,6 +38,212 @@ pub fn query_seen_nonces(storage: &dyn Storage) -> StdResult<BTreeSet<Nonce>> {
         .map(|opt| opt.unwrap_or_default())
 }
 
+/// Max number of authenticator contracts an account may register.
+pub const MAX_AUTHENTICATORS: usize = 5;
+
+/// Additional contracts that are consulted when authenticating transactions.
+///
+/// Spot and margin accounts store their authenticators in this same storage
+/// slot.
+pub const AUTHENTICATORS: Set<Addr> = Set::new("authenticators");
+
+/// Query the set of authenticator contracts registered by the account.
+pub fn query_authenticators(storage: &dyn Storage) -> StdResult<BTreeSet<Addr>> {
+    AUTHENTICATORS
+        .range(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// Register an additional authenticator contract.
+///
+/// Only the account itself can do this, meaning the action must be signed by
+/// the account's owner and pass all existing authenticators.
+pub fn register_authenticator(ctx: MutableCtx, authenticator: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.contract,
+        "only the account itself can register authenticators"
+    );
+
+    ensure!(
+        authenticator != ctx.contract,
+        "an account can't be its own authenticator"
+    );
+
+    ensure!(
+        !AUTHENTICATORS.has(ctx.storage, authenticator),
+        "authenticator {authenticator} is already registered"
+    );
+
+    ensure!(
+        AUTHENTICATORS
+            .range_raw(ctx.storage, None, None, Order::Ascending)
+            .count()
+            < MAX_AUTHENTICATORS,
+        "too many authenticators! max: {MAX_AUTHENTICATORS}"
+    );
+
+    // Make sure the address is a contract, so that a typo doesn't lock the
+    // account out.
+    ctx.querier.query_contract(authenticator)?;
+
+    AUTHENTICATORS.insert(ctx.storage, authenticator)?;
+
+    Ok(Response::new())
+}
+
+/// Deregister an authenticator contract.
+pub fn deregister_authenticator(ctx: MutableCtx, authenticator: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.contract,
+        "only the account itself can deregister authenticators"
+    );
+
+    ensure!(
+        AUTHENTICATORS.has(ctx.storage, authenticator),
+        "authenticator {authenticator} isn't registered"
+    );
+
+    AUTHENTICATORS.remove(ctx.storage, authenticator);
+
+    Ok(Response::new())
+}
+
+/// The authenticators that were consulted when authenticating the transaction
+/// currently being processed, to be consulted again in the backrun.
+///
+/// We don't load the authenticators from [`AUTHENTICATORS`](crate::AUTHENTICATORS)
+/// in the backrun, as the transaction may have deregistered some of them, which
+/// would let it skip their checks.
+const BACKRUN_AUTHENTICATORS: Item<BTreeSet<Addr>> = Item::new("backrun_authenticators");
+
+/// Authenticate a transaction the same way as [`authenticate_tx`](crate::authenticate_tx),
+/// then consult each of the account's authenticators. Any of them may veto the
+/// transaction by returning an error.
+///
+/// The authenticators are only consulted once the signature is verified.
+///
+/// A transaction that does nothing but deregister an authenticator isn't
+/// subject to the authenticators' vetoes, so that an authenticator that always
+/// errors can't lock the account. This means the owner's key alone can always
+/// remove an authenticator.
+///
+/// Returns whether any authenticator was consulted, in which case a backrun
+/// should be requested.
+pub fn authenticate_tx_with_authenticators(ctx: AuthCtx, tx: Tx) -> anyhow::Result<bool> {
+    let AuthCtx {
+        storage,
+        api,
+        querier,
+        chain_id,
+        block,
+        contract,
+        mode,
+    } = ctx;
+
+    authenticate_tx(
+        AuthCtx {
+            storage: &mut *storage,
+            api,
+            querier: QuerierWrapper::new(&querier),
+            chain_id,
+            block,
+            contract,
+            mode,
+        },
+        tx.clone(),
+        None,
+    )?;
+
+    let authenticators = query_authenticators(storage)?;
+
+    // Also clear what may have been left over by a previous transaction whose
+    // messages failed, in which case the backrun wasn't run.
+    if authenticators.is_empty() || is_deregistering_authenticator(contract, &tx) {
+        BACKRUN_AUTHENTICATORS.remove(storage);
+
+        return Ok(false);
+    }
+
+    for authenticator in &authenticators {
+        querier
+            .query_wasm_smart(*authenticator, QueryAuthenticateRequest {
+                account: contract,
+                tx: tx.clone(),
+            })
+            .map_err(|err| {
+                anyhow!("authenticator {authenticator} rejected the transaction: {err}")
+            })?;
+    }
+
+    BACKRUN_AUTHENTICATORS.save(storage, &authenticators)?;
+
+    Ok(true)
+}
+
+/// Whether the transaction consists of exactly one message, which is for the
+/// account to deregister one of its authenticators.
+fn is_deregistering_authenticator(account: Addr, tx: &Tx) -> bool {
+    let mut msgs = tx.msgs.iter();
+
+    let (Some(Message::Execute(MsgExecute { contract, msg, .. })), None) =
+        (msgs.next(), msgs.next())
+    else {
+        return false;
+    };
+
+    // Spot and margin accounts share the same `DeregisterAuthenticator` message,
+    // so we use the spot account's type to parse it for both.
+    *contract == account
+        && matches!(
+            msg.clone().deserialize_json(),
+            Ok(spot::ExecuteMsg::DeregisterAuthenticator { .. })
+        )
+}
+
+/// Consult the authenticators that were consulted before the transaction was
+/// executed, once again after it has been executed. Any of them may veto the
+/// transaction by returning an error.
+pub fn backrun_with_authenticators(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    account: Addr,
+    tx: &Tx,
+) -> anyhow::Result<()> {
+    let authenticators = BACKRUN_AUTHENTICATORS
+        .may_take(storage)?
+        .unwrap_or_default();
+
+    for authenticator in authenticators {
+        querier
+            .query_wasm_smart(authenticator, QueryBackrunRequest {
+                account,
+                tx: tx.clone(),
+            })
+            .map_err(|err| {
+                anyhow!("authenticator {authenticator} rejected the transaction: {err}")
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Ensure the account hasn't been deleted by its owner.
+///
+/// Spot and margin accounts call this when receiving transfers, so that funds
+/// aren't sent to an account nobody can retrieve them from.
+pub fn ensure_not_deleted(querier: &QuerierWrapper, account: Addr) -> anyhow::Result<()> {
+    let factory = querier.query_account_factory()?;
+
+    ensure!(
+        querier
+            .query_wasm_raw(factory, DELETED_ACCOUNTS.path(account))?
+            .is_none(),
+        "account {account} has been deleted and can't receive funds"
+    );
+
+    Ok(())
+}
+
 /// Authenticate a transaction by ensuring:
 ///
 /// - the username is associated with the sender account;
@@ -239,19 +447,46 @@ fn verify_signature(
                     sign_doc,
                     chain_id,
                     nonce,
-                } => (
-                    Some(U160::from_be_bytes(sign_doc.sender.into_inner()).into()),
-                    json!({
-                        "gas_limit": sign_doc.gas_limit,
-                        "metadata": {
-                            "username": sign_doc.data.username,
-                            "chain_id": chain_id,
-                            "nonce": nonce,
-                            "expiry": sign_doc.data.expiry,
-                        },
-                        "messages": sign_doc.messages,
-                    }),
-                ),
+                } => {
+                    let mut metadata = json!({
+                        "username": sign_doc.data.username,
+                        "chain_id": chain_id,
+                        "nonce": nonce,
+                        "expiry": sign_doc.data.expiry,
+                    })
+                    .into_inner();
+
+                    // Same as in the standard sign doc, the optional fields are
+                    // only part of the signed data if provided.
+                    if let Some(referrer) = sign_doc.data.referrer {
+                        metadata["referrer"] = json!(referrer).into_inner();
+                    }
+
+                    if let Some(fee_denom) = &sign_doc.data.fee_denom {
+                        metadata["fee_denom"] = json!(fee_denom).into_inner();
+                    }
+
+                    if let Some(granter) = sign_doc.data.granter {
+                        metadata["granter"] = json!(granter).into_inner();
+                    }
+
+                    if let Some(max_fee_rate) = sign_doc.data.max_fee_rate {
+                        metadata["max_fee_rate"] = json!(max_fee_rate).into_inner();
+                    }
+
+                    if let Some(tip_rate) = sign_doc.data.tip_rate {
+                        metadata["tip_rate"] = json!(tip_rate).into_inner();
+                    }
+
+                    (
+                        Some(U160::from_be_bytes(sign_doc.sender.into_inner()).into()),
+                        json!({
+                            "gas_limit": sign_doc.gas_limit,
+                            "metadata": metadata,
+                            "messages": sign_doc.messages,
+                        }),
+                    )
+                },
                 VerifyData::Session(session_info) => (
                     None,
                     json!({
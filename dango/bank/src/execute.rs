@@ -1,14 +1,37 @@
 use {
-    crate::{BALANCES, METADATAS, NAMESPACE_OWNERS, ORPHANED_TRANSFERS, SUPPLIES},
-    anyhow::{bail, ensure},
-    dango_types::bank::{ExecuteMsg, InstantiateMsg, Metadata},
+    crate::{
+        ALLOWANCES, BALANCES, BALANCE_SNAPSHOTS, HOOKS, LEGACY_NAMESPACE_OWNERS, METADATAS,
+        NAMESPACES_BY_OWNER, NAMESPACE_OWNERS, ORPHANED_TRANSFERS, ORPHANED_TRANSFERS_BY_EXPIRY,
+        ORPHANED_TRANSFERS_BY_RECIPIENT, ORPHANED_TRANSFER_EXPIRIES, ORPHANED_TRANSFER_EXPIRY,
+        SNAPSHOTTED_DENOMS, SUPPLIES,
+    },
+    anyhow::{anyhow, bail, ensure},
+    dango_account_factory::ACCOUNTS,
+    dango_types::{
+        account::spot::QueryAuthenticatorsRequest,
+        account_factory::AccountParams,
+        bank::{
+            Allowance, ApprovalRevoked, Approved, BeforeSendResponse, ExecuteMsg, HookQueryMsg,
+            InstantiateMsg, Metadata, MigrateMsg, NamespaceGranted, NamespaceRenounced,
+            NamespaceRevoked, NamespaceTransferred, OrphanedTransferReturned, TransferTagged,
+        },
+        DangoQuerier,
+    },
     grug::{
-        Addr, BankMsg, Coin, Coins, Denom, IsZero, MutableCtx, Number, NumberConst, Part,
-        QuerierExt, Response, StdError, StdResult, Storage, SudoCtx, Uint128,
+        Addr, BankMsg, Coin, Coins, Denom, IsZero, JsonDeExt, Message, MutableCtx, Number,
+        NumberConst, Order, Part, Querier, QuerierExt, QuerierWrapper, Query, Response, StdError,
+        StdResult, Storage, StorageQuerier, SudoCtx, Timestamp, Uint128,
     },
     std::collections::{BTreeMap, HashMap},
 };
 
+/// The maximum amount of gas a namespace's hook may consume per transfer.
+pub const HOOK_GAS_LIMIT: u64 = 1_000_000;
+
+/// The maximum number of expired orphaned transfers to return per cronjob
+/// execution, so that the cronjob's gas consumption is bounded.
+pub const MAX_ORPHANED_TRANSFERS_PER_SWEEP: usize = 30;
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> anyhow::Result<Response> {
     let mut supplies = HashMap::<Denom, Uint128>::new();
@@ -33,13 +56,51 @@ pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> anyhow::Result<Respo
     }
 
     for (namespace, owner) in msg.namespaces {
-        NAMESPACE_OWNERS.save(ctx.storage, &namespace, &owner)?;
+        add_namespace_owner(ctx.storage, &namespace, owner)?;
     }
 
     for (denom, metadata) in msg.metadatas {
         METADATAS.save(ctx.storage, &denom, &metadata)?;
     }
 
+    ORPHANED_TRANSFER_EXPIRY.save(ctx.storage, &msg.orphaned_transfer_expiry)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn migrate(ctx: SudoCtx, msg: MigrateMsg) -> StdResult<Response> {
+    // Move the namespace owners over from the single-owner storage layout.
+    let legacy_owners = LEGACY_NAMESPACE_OWNERS
+        .range(ctx.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (namespace, owner) in legacy_owners {
+        LEGACY_NAMESPACE_OWNERS.remove(ctx.storage, &namespace);
+        add_namespace_owner(ctx.storage, &namespace, owner)?;
+    }
+
+    ORPHANED_TRANSFER_EXPIRY.save(ctx.storage, &msg.orphaned_transfer_expiry)?;
+
+    // Index the orphaned transfers made before they could expire, as if they
+    // were made just now.
+    let unindexed = ORPHANED_TRANSFERS
+        .keys(ctx.storage, None, None, Order::Ascending)
+        .filter(|res| {
+            res.as_ref().map_or(true, |key| {
+                !ORPHANED_TRANSFER_EXPIRIES.has(ctx.storage, *key)
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let expiry = ctx.block.timestamp + msg.orphaned_transfer_expiry;
+
+    for (sender, recipient) in unindexed {
+        ORPHANED_TRANSFER_EXPIRIES.save(ctx.storage, (sender, recipient), &expiry)?;
+        ORPHANED_TRANSFERS_BY_EXPIRY.insert(ctx.storage, (expiry, sender, recipient))?;
+        ORPHANED_TRANSFERS_BY_RECIPIENT.insert(ctx.storage, (recipient, sender))?;
+    }
+
     Ok(Response::new())
 }
 
@@ -49,7 +110,12 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
 
     match msg {
         ExecuteMsg::GrantNamespace { namespace, owner } => grant_namespace(ctx, namespace, owner),
+        ExecuteMsg::RevokeNamespace { namespace, owner } => revoke_namespace(ctx, namespace, owner),
+        ExecuteMsg::TransferNamespace { namespace, to } => transfer_namespace(ctx, namespace, to),
+        ExecuteMsg::RenounceNamespace { namespace } => renounce_namespace(ctx, namespace),
+        ExecuteMsg::SetHook { namespace, hook } => set_hook(ctx, namespace, hook),
         ExecuteMsg::SetMetadata { denom, metadata } => set_metadata(ctx, denom, metadata),
+        ExecuteMsg::EnableSnapshots { denom } => enable_snapshots(ctx, denom),
         ExecuteMsg::Mint { to, denom, amount } => mint(ctx, to, denom, amount),
         ExecuteMsg::Burn {
             from,
@@ -63,6 +129,19 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             amount,
         } => force_transfer(ctx, from, to, denom, amount),
         ExecuteMsg::BatchTransfer(transfers) => batch_transfer(ctx, transfers),
+        ExecuteMsg::Approve {
+            spender,
+            denom,
+            amount,
+            expires_at,
+        } => approve(ctx, spender, denom, amount, expires_at),
+        ExecuteMsg::RevokeApproval { spender, denom } => revoke_approval(ctx, spender, denom),
+        ExecuteMsg::TransferFrom {
+            owner,
+            to,
+            denom,
+            amount,
+        } => transfer_from(ctx, owner, to, denom, amount),
         ExecuteMsg::RecoverTransfer { sender, recipient } => {
             recover_transfer(ctx, sender, recipient)
         },
@@ -76,15 +155,97 @@ fn grant_namespace(ctx: MutableCtx, namespace: Part, owner: Addr) -> anyhow::Res
         "you don't have the right, O you don't have the right"
     );
 
-    NAMESPACE_OWNERS.may_update(ctx.storage, &namespace, |maybe_owner| {
-        // TODO: for now, we don't support granting a namespace to multiple
-        // owners or overwriting an existing owner.
-        if let Some(existing_owner) = maybe_owner {
-            bail!("namespace `{namespace}` already granted to `{existing_owner}`");
-        }
+    ensure!(
+        !NAMESPACE_OWNERS.has(ctx.storage, (&namespace, owner)),
+        "namespace `{namespace}` already granted to `{owner}`"
+    );
 
-        Ok(owner)
-    })?;
+    add_namespace_owner(ctx.storage, &namespace, owner)?;
+
+    Ok(Response::new().add_event(NamespaceGranted { namespace, owner })?)
+}
+
+fn revoke_namespace(ctx: MutableCtx, namespace: Part, owner: Addr) -> anyhow::Result<Response> {
+    // Only chain owner can revoke namespace.
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "you don't have the right, O you don't have the right"
+    );
+
+    ensure!(
+        NAMESPACE_OWNERS.has(ctx.storage, (&namespace, owner)),
+        "`{owner}` does not own the namespace `{namespace}`"
+    );
+
+    remove_namespace_owner(ctx.storage, &namespace, owner);
+
+    Ok(Response::new().add_event(NamespaceRevoked { namespace, owner })?)
+}
+
+fn transfer_namespace(ctx: MutableCtx, namespace: Part, to: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        NAMESPACE_OWNERS.has(ctx.storage, (&namespace, ctx.sender)),
+        "sender does not own the namespace `{namespace}`"
+    );
+
+    ensure!(
+        !NAMESPACE_OWNERS.has(ctx.storage, (&namespace, to)),
+        "`{to}` already owns the namespace `{namespace}`"
+    );
+
+    remove_namespace_owner(ctx.storage, &namespace, ctx.sender);
+    add_namespace_owner(ctx.storage, &namespace, to)?;
+
+    Ok(Response::new().add_event(NamespaceTransferred {
+        namespace,
+        from: ctx.sender,
+        to,
+    })?)
+}
+
+fn renounce_namespace(ctx: MutableCtx, namespace: Part) -> anyhow::Result<Response> {
+    ensure!(
+        NAMESPACE_OWNERS.has(ctx.storage, (&namespace, ctx.sender)),
+        "sender does not own the namespace `{namespace}`"
+    );
+
+    remove_namespace_owner(ctx.storage, &namespace, ctx.sender);
+
+    Ok(Response::new().add_event(NamespaceRenounced {
+        namespace,
+        owner: ctx.sender,
+    })?)
+}
+
+fn add_namespace_owner(storage: &mut dyn Storage, namespace: &Part, owner: Addr) -> StdResult<()> {
+    NAMESPACE_OWNERS.insert(storage, (namespace, owner))?;
+    NAMESPACES_BY_OWNER.insert(storage, (owner, namespace))
+}
+
+fn remove_namespace_owner(storage: &mut dyn Storage, namespace: &Part, owner: Addr) {
+    NAMESPACE_OWNERS.remove(storage, (namespace, owner));
+    NAMESPACES_BY_OWNER.remove(storage, (owner, namespace));
+}
+
+fn set_hook(ctx: MutableCtx, namespace: Part, hook: Option<Addr>) -> anyhow::Result<Response> {
+    // Only the namespace owner can set the hook.
+    ensure!(
+        NAMESPACE_OWNERS.has(ctx.storage, (&namespace, ctx.sender)),
+        "sender does not own the namespace `{namespace}`"
+    );
+
+    match hook {
+        Some(hook) => {
+            // Make sure the hook is a contract, otherwise all transfers of
+            // denoms under the namespace would fail.
+            ctx.querier.query_contract(hook)?;
+
+            HOOKS.save(ctx.storage, &namespace, &hook)?;
+        },
+        None => {
+            HOOKS.remove(ctx.storage, &namespace);
+        },
+    }
 
     Ok(Response::new())
 }
@@ -97,40 +258,54 @@ fn set_metadata(ctx: MutableCtx, denom: Denom, metadata: Metadata) -> anyhow::Re
     Ok(Response::default())
 }
 
+fn enable_snapshots(ctx: MutableCtx, denom: Denom) -> anyhow::Result<Response> {
+    ensure_namespace_owner(&ctx, &denom)?;
+
+    ensure!(
+        !SNAPSHOTTED_DENOMS.has(ctx.storage, &denom),
+        "snapshots are already enabled for denom `{denom}`"
+    );
+
+    SNAPSHOTTED_DENOMS.save(ctx.storage, &denom, &ctx.block.height)?;
+
+    Ok(Response::new())
+}
+
 fn mint(ctx: MutableCtx, to: Addr, denom: Denom, amount: Uint128) -> anyhow::Result<Response> {
     ensure_namespace_owner(&ctx, &denom)?;
 
     increase_supply(ctx.storage, &denom, amount)?;
 
     if ctx.querier.query_contract(to).is_ok() {
-        increase_balance(ctx.storage, &to, &denom, amount)?;
+        increase_balance(ctx.storage, ctx.block.height, &to, &denom, amount)?;
     } else {
-        ORPHANED_TRANSFERS.may_update(ctx.storage, (ctx.sender, to), |coins| {
-            let mut coins = coins.unwrap_or_default();
-            coins.insert(Coin::new(denom, amount)?)?;
-            Ok::<_, StdError>(coins)
-        })?;
+        let mut coins = Coins::new();
+        coins.insert(Coin::new(denom, amount)?)?;
+
+        add_orphaned_transfer(ctx.storage, ctx.block.timestamp, ctx.sender, to, coins)?;
     }
 
     Ok(Response::new())
 }
 
 fn burn(ctx: MutableCtx, from: Addr, denom: Denom, amount: Uint128) -> anyhow::Result<Response> {
-    ensure_namespace_owner(&ctx, &denom)?;
+    // The taxman can burn the fees it has collected, regardless of denom.
+    if !(from == ctx.sender && ctx.sender == ctx.querier.query_taxman()?) {
+        ensure_namespace_owner(&ctx, &denom)?;
+    }
 
     decrease_supply(ctx.storage, &denom, amount)?;
-    decrease_balance(ctx.storage, &from, &denom, amount)?;
+    decrease_balance(ctx.storage, ctx.block.height, &from, &denom, amount)?;
 
     Ok(Response::new())
 }
 
 fn ensure_namespace_owner(ctx: &MutableCtx, denom: &Denom) -> anyhow::Result<()> {
     match denom.namespace() {
-        // The denom has a namespace. The namespace's owner can mint/burn.
+        // The denom has a namespace. The namespace's owners can mint/burn.
         Some(part) => {
-            let maybe_owner = NAMESPACE_OWNERS.may_load(ctx.storage, part)?;
             ensure!(
-                maybe_owner == Some(ctx.sender),
+                NAMESPACE_OWNERS.has(ctx.storage, (part, ctx.sender)),
                 "sender does not own the namespace `{part}`"
             );
         },
@@ -160,21 +335,190 @@ fn force_transfer(
         "you don't have the right, O you don't have the right"
     );
 
-    decrease_balance(ctx.storage, &from, &denom, amount)?;
-    increase_balance(ctx.storage, &to, &denom, amount)?;
+    let mut coins = Coins::new();
+    coins.insert(Coin::new(denom.clone(), amount)?)?;
 
-    Ok(Response::new())
+    let events = call_hooks(ctx.storage, &ctx.querier, from, to, &coins)?;
+
+    decrease_balance(ctx.storage, ctx.block.height, &from, &denom, amount)?;
+    increase_balance(ctx.storage, ctx.block.height, &to, &denom, amount)?;
+
+    add_events(Response::new(), events)
 }
 
 fn batch_transfer(ctx: MutableCtx, transfers: BTreeMap<Addr, Coins>) -> anyhow::Result<Response> {
+    let mut events = Vec::new();
+
     for (recipient, coins) in transfers {
+        events.extend(call_hooks(
+            ctx.storage,
+            &ctx.querier,
+            ctx.sender,
+            recipient,
+            &coins,
+        )?);
+
         for coin in coins {
-            decrease_balance(ctx.storage, &ctx.sender, &coin.denom, coin.amount)?;
-            increase_balance(ctx.storage, &recipient, &coin.denom, coin.amount)?;
+            decrease_balance(
+                ctx.storage,
+                ctx.block.height,
+                &ctx.sender,
+                &coin.denom,
+                coin.amount,
+            )?;
+            increase_balance(
+                ctx.storage,
+                ctx.block.height,
+                &recipient,
+                &coin.denom,
+                coin.amount,
+            )?;
         }
     }
 
-    Ok(Response::new())
+    add_events(Response::new(), events)
+}
+
+fn approve(
+    ctx: MutableCtx,
+    spender: Addr,
+    denom: Denom,
+    amount: Uint128,
+    expires_at: Option<Timestamp>,
+) -> anyhow::Result<Response> {
+    ensure!(spender != ctx.sender, "can't approve self as spender");
+
+    ensure!(
+        amount.is_non_zero(),
+        "allowance amount can't be zero; use `revoke_approval` instead"
+    );
+
+    if let Some(expires_at) = expires_at {
+        ensure!(
+            expires_at > ctx.block.timestamp,
+            "allowance expiry must be in the future"
+        );
+    }
+
+    ALLOWANCES.save(ctx.storage, (ctx.sender, spender, &denom), &Allowance {
+        amount,
+        expires_at,
+    })?;
+
+    Ok(Response::new().add_event(Approved {
+        owner: ctx.sender,
+        spender,
+        denom,
+        amount,
+        expires_at,
+    })?)
+}
+
+fn revoke_approval(ctx: MutableCtx, spender: Addr, denom: Denom) -> anyhow::Result<Response> {
+    ensure!(
+        ALLOWANCES.has(ctx.storage, (ctx.sender, spender, &denom)),
+        "no allowance of `{denom}` to revoke from `{spender}`"
+    );
+
+    ALLOWANCES.remove(ctx.storage, (ctx.sender, spender, &denom));
+
+    Ok(Response::new().add_event(ApprovalRevoked {
+        owner: ctx.sender,
+        spender,
+        denom,
+    })?)
+}
+
+fn transfer_from(
+    ctx: MutableCtx,
+    owner: Addr,
+    to: Addr,
+    denom: Denom,
+    amount: Uint128,
+) -> anyhow::Result<Response> {
+    let allowance = ALLOWANCES
+        .may_load(ctx.storage, (owner, ctx.sender, &denom))?
+        .ok_or_else(|| anyhow!("no allowance of `{denom}` from `{owner}`"))?;
+
+    if let Some(expires_at) = allowance.expires_at {
+        ensure!(
+            ctx.block.timestamp < expires_at,
+            "allowance of `{denom}` from `{owner}` has expired"
+        );
+    }
+
+    ensure!(
+        amount <= allowance.amount,
+        "insufficient allowance of `{denom}` from `{owner}`: {} < {amount}",
+        allowance.amount
+    );
+
+    // Reduce the allowance. Delete it if reduced to zero.
+    let remaining = allowance.amount.checked_sub(amount)?;
+    if remaining.is_zero() {
+        ALLOWANCES.remove(ctx.storage, (owner, ctx.sender, &denom));
+    } else {
+        ALLOWANCES.save(ctx.storage, (owner, ctx.sender, &denom), &Allowance {
+            amount: remaining,
+            expires_at: allowance.expires_at,
+        })?;
+    }
+
+    ensure_can_transfer_from(&ctx.querier, owner)?;
+
+    let mut coins = Coins::new();
+    coins.insert(Coin::new(denom.clone(), amount)?)?;
+
+    let events = call_hooks(ctx.storage, &ctx.querier, owner, to, &coins)?;
+
+    decrease_balance(ctx.storage, ctx.block.height, &owner, &denom, amount)?;
+
+    // Credit the recipient the same way as a regular transfer. If it doesn't
+    // exist, record an orphaned transfer from the owner, so that the owner can
+    // recover the funds. Otherwise, relay the funds through the bank with a
+    // transfer message, so that the recipient's `receive` entry point is
+    // called, and can reject them.
+    let relay_msg = if ctx.querier.query_contract(to).is_ok() {
+        increase_balance(ctx.storage, ctx.block.height, &ctx.contract, &denom, amount)?;
+        Some(Message::transfer(to, coins)?)
+    } else {
+        add_orphaned_transfer(ctx.storage, ctx.block.timestamp, owner, to, coins)?;
+        None
+    };
+
+    Ok(add_events(Response::new(), events)?.may_add_message(relay_msg))
+}
+
+/// Ensure the owner is an account whose post-transaction checks can't be
+/// bypassed by moving its funds outside of a transaction it has sent.
+///
+/// Margin accounts check their health in the backrun, and accounts with
+/// authenticators let them veto the transaction in the backrun. Neither runs
+/// when funds are pulled by a spender, so such accounts can't be drawn from.
+fn ensure_can_transfer_from(querier: &QuerierWrapper, owner: Addr) -> anyhow::Result<()> {
+    let account_factory = querier.query_account_factory()?;
+
+    // As an optimization, use raw instead of smart query.
+    let Some(account) = querier.may_query_wasm_path(account_factory, ACCOUNTS.path(owner))? else {
+        return Ok(());
+    };
+
+    match account.params {
+        AccountParams::Margin(_) => {
+            bail!("can't transfer from margin account `{owner}`");
+        },
+        AccountParams::Spot(_) => {
+            let authenticators = querier.query_wasm_smart(owner, QueryAuthenticatorsRequest {})?;
+
+            ensure!(
+                authenticators.is_empty(),
+                "can't transfer from account `{owner}` that has authenticators"
+            );
+        },
+        AccountParams::Safe(_) => {},
+    }
+
+    Ok(())
 }
 
 fn recover_transfer(ctx: MutableCtx, sender: Addr, recipient: Addr) -> anyhow::Result<Response> {
@@ -183,33 +527,209 @@ fn recover_transfer(ctx: MutableCtx, sender: Addr, recipient: Addr) -> anyhow::R
         "only the sender or the recipient can recover an orphaned transfer"
     );
 
-    for coin in ORPHANED_TRANSFERS.take(ctx.storage, (sender, recipient))? {
-        increase_balance(ctx.storage, &ctx.sender, &coin.denom, coin.amount)?;
+    for coin in take_orphaned_transfer(ctx.storage, sender, recipient)? {
+        increase_balance(
+            ctx.storage,
+            ctx.block.height,
+            &ctx.sender,
+            &coin.denom,
+            coin.amount,
+        )?;
     }
 
     Ok(Response::new())
 }
 
+/// Return expired orphaned transfers to their senders.
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn bank_execute(ctx: SudoCtx, msg: BankMsg) -> StdResult<Response> {
+pub fn cron_execute(ctx: SudoCtx) -> StdResult<Response> {
+    let expired = ORPHANED_TRANSFERS_BY_EXPIRY
+        .range(ctx.storage, None, None, Order::Ascending)
+        .take_while(|res| {
+            res.as_ref()
+                .map_or(true, |(expiry, ..)| *expiry <= ctx.block.timestamp)
+        })
+        .take(MAX_ORPHANED_TRANSFERS_PER_SWEEP)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut response = Response::new();
+
+    for (_, sender, recipient) in expired {
+        let coins = take_orphaned_transfer(ctx.storage, sender, recipient)?;
+
+        for coin in &coins {
+            increase_balance(
+                ctx.storage,
+                ctx.block.height,
+                &sender,
+                coin.denom,
+                *coin.amount,
+            )?;
+        }
+
+        response = response.add_event(OrphanedTransferReturned {
+            sender,
+            recipient,
+            coins,
+        })?;
+    }
+
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn bank_execute(ctx: SudoCtx, msg: BankMsg) -> anyhow::Result<Response> {
+    // Transfers from the bank itself relay `TransferFrom`s, whose hooks have
+    // already been called with the owner as the sender.
+    let events = if msg.from == ctx.contract {
+        Vec::new()
+    } else {
+        call_hooks(ctx.storage, &ctx.querier, msg.from, msg.to, &msg.coins)?
+    };
+
     let recipient_exists = ctx.querier.query_contract(msg.to).is_ok();
 
     for coin in &msg.coins {
-        decrease_balance(ctx.storage, &msg.from, coin.denom, *coin.amount)?;
+        decrease_balance(
+            ctx.storage,
+            ctx.block.height,
+            &msg.from,
+            coin.denom,
+            *coin.amount,
+        )?;
         if recipient_exists {
-            increase_balance(ctx.storage, &msg.to, coin.denom, *coin.amount)?;
+            increase_balance(
+                ctx.storage,
+                ctx.block.height,
+                &msg.to,
+                coin.denom,
+                *coin.amount,
+            )?;
         }
     }
 
     if !recipient_exists {
-        ORPHANED_TRANSFERS.may_update(ctx.storage, (msg.from, msg.to), |coins| {
-            let mut coins = coins.unwrap_or_default();
-            coins.insert_many(msg.coins)?;
-            Ok::<_, StdError>(coins)
-        })?;
+        add_orphaned_transfer(
+            ctx.storage,
+            ctx.block.timestamp,
+            msg.from,
+            msg.to,
+            msg.coins,
+        )?;
     }
 
-    Ok(Response::new())
+    add_events(Response::new(), events)
+}
+
+/// Record funds sent to a non-existing recipient, and reset the expiry of the
+/// orphaned transfer.
+fn add_orphaned_transfer(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    sender: Addr,
+    recipient: Addr,
+    coins: Coins,
+) -> StdResult<()> {
+    ORPHANED_TRANSFERS.may_update(storage, (sender, recipient), |existing| {
+        let mut existing = existing.unwrap_or_default();
+        existing.insert_many(coins)?;
+        Ok::<_, StdError>(existing)
+    })?;
+
+    if let Some(expiry) = ORPHANED_TRANSFER_EXPIRIES.may_load(storage, (sender, recipient))? {
+        ORPHANED_TRANSFERS_BY_EXPIRY.remove(storage, (expiry, sender, recipient));
+    }
+
+    let expiry = now + ORPHANED_TRANSFER_EXPIRY.load(storage)?;
+
+    ORPHANED_TRANSFER_EXPIRIES.save(storage, (sender, recipient), &expiry)?;
+    ORPHANED_TRANSFERS_BY_EXPIRY.insert(storage, (expiry, sender, recipient))?;
+    ORPHANED_TRANSFERS_BY_RECIPIENT.insert(storage, (recipient, sender))?;
+
+    Ok(())
+}
+
+fn take_orphaned_transfer(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    recipient: Addr,
+) -> StdResult<Coins> {
+    let coins = ORPHANED_TRANSFERS.take(storage, (sender, recipient))?;
+    let expiry = ORPHANED_TRANSFER_EXPIRIES.take(storage, (sender, recipient))?;
+
+    ORPHANED_TRANSFERS_BY_EXPIRY.remove(storage, (expiry, sender, recipient));
+    ORPHANED_TRANSFERS_BY_RECIPIENT.remove(storage, (recipient, sender));
+
+    Ok(coins)
+}
+
+/// Consult the hooks of the namespaces of the coins being transferred.
+///
+/// Each hook is called once, with all coins under its namespace, and under a
+/// bounded amount of gas. Return the events to be emitted for transfers that
+/// the hooks have tagged.
+fn call_hooks(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    from: Addr,
+    to: Addr,
+    coins: &Coins,
+) -> anyhow::Result<Vec<TransferTagged>> {
+    let mut coins_by_namespace = BTreeMap::<&Part, Coins>::new();
+
+    for coin in coins {
+        if let Some(namespace) = coin.denom.namespace() {
+            coins_by_namespace
+                .entry(namespace)
+                .or_default()
+                .insert(Coin::new(coin.denom.clone(), *coin.amount)?)?;
+        }
+    }
+
+    let mut events = Vec::new();
+
+    for (namespace, coins) in coins_by_namespace {
+        let Some(hook) = HOOKS.may_load(storage, namespace)? else {
+            continue;
+        };
+
+        let query = Query::wasm_smart_with_gas_limit(
+            hook,
+            &HookQueryMsg::BeforeSend {
+                from,
+                to,
+                coins: coins.clone(),
+            },
+            HOOK_GAS_LIMIT,
+        )?;
+
+        let res: BeforeSendResponse = querier
+            .query_chain(query)
+            .and_then(|res| res.as_wasm_smart().deserialize_json())
+            .map_err(|err| {
+                anyhow!("transfer rejected by the hook of namespace `{namespace}`: {err}")
+            })?;
+
+        if let Some(tag) = res.tag {
+            events.push(TransferTagged {
+                namespace: namespace.clone(),
+                from,
+                to,
+                coins,
+                tag,
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+fn add_events(mut response: Response, events: Vec<TransferTagged>) -> anyhow::Result<Response> {
+    for event in events {
+        response = response.add_event(event)?;
+    }
+
+    Ok(response)
 }
 
 fn increase_supply(
@@ -246,11 +766,12 @@ fn decrease_supply(
 
 fn increase_balance(
     storage: &mut dyn Storage,
+    height: u64,
     address: &Addr,
     denom: &Denom,
     amount: Uint128,
 ) -> StdResult<Option<Uint128>> {
-    BALANCES.may_modify(storage, (address, denom), |maybe_balance| {
+    let maybe_balance = BALANCES.may_modify(storage, (address, denom), |maybe_balance| {
         let balance = maybe_balance.unwrap_or(Uint128::ZERO).checked_add(amount)?;
         // Only write to storage if the balance is non-zero.
         if balance.is_zero() {
@@ -258,16 +779,30 @@ fn increase_balance(
         } else {
             Ok(Some(balance))
         }
-    })
+    })?;
+
+    let balance = maybe_balance.unwrap_or(Uint128::ZERO);
+
+    record_snapshot(
+        storage,
+        height,
+        address,
+        denom,
+        balance.checked_sub(amount)?,
+        balance,
+    )?;
+
+    Ok(maybe_balance)
 }
 
 fn decrease_balance(
     storage: &mut dyn Storage,
+    height: u64,
     address: &Addr,
     denom: &Denom,
     amount: Uint128,
 ) -> StdResult<Option<Uint128>> {
-    BALANCES.may_modify(storage, (address, denom), |maybe_balance| {
+    let maybe_balance = BALANCES.may_modify(storage, (address, denom), |maybe_balance| {
         let balance = maybe_balance.unwrap_or(Uint128::ZERO).checked_sub(amount)?;
         // If balance is reduced to zero, delete it, to save disk space.
         if balance.is_zero() {
@@ -275,5 +810,156 @@ fn decrease_balance(
         } else {
             Ok(Some(balance))
         }
-    })
+    })?;
+
+    let balance = maybe_balance.unwrap_or(Uint128::ZERO);
+
+    record_snapshot(
+        storage,
+        height,
+        address,
+        denom,
+        balance.checked_add(amount)?,
+        balance,
+    )?;
+
+    Ok(maybe_balance)
+}
+
+/// If snapshots are enabled for the denom, record the account's balance at the
+/// end of the current block.
+fn record_snapshot(
+    storage: &mut dyn Storage,
+    height: u64,
+    address: &Addr,
+    denom: &Denom,
+    balance_before: Uint128,
+    balance_after: Uint128,
+) -> StdResult<()> {
+    let Some(enabled_at) = SNAPSHOTTED_DENOMS.may_load(storage, denom)? else {
+        return Ok(());
+    };
+
+    // If this is the account's first balance change since snapshots were
+    // enabled, also record the balance prior to the change, which has held
+    // since the enablement.
+    let is_first_change = BALANCE_SNAPSHOTS
+        .prefix(address)
+        .append(denom)
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_none();
+
+    if is_first_change {
+        BALANCE_SNAPSHOTS.save(storage, (address, denom, enabled_at), &balance_before)?;
+    }
+
+    BALANCE_SNAPSHOTS.save(storage, (address, denom, height), &balance_after)
+}
+
+// ----------------------------------- tests -----------------------------------
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        grug::{Duration, MockContext, ResultExt},
+        std::str::FromStr,
+    };
+
+    #[test]
+    fn migrating_namespace_owners() {
+        let mut ctx = MockContext::new();
+
+        let rwa = Part::from_str("rwa").unwrap();
+        let usd = Part::from_str("usd").unwrap();
+
+        LEGACY_NAMESPACE_OWNERS
+            .save(&mut ctx.storage, &rwa, &Addr::mock(1))
+            .unwrap();
+        LEGACY_NAMESPACE_OWNERS
+            .save(&mut ctx.storage, &usd, &Addr::mock(2))
+            .unwrap();
+
+        migrate(ctx.as_sudo(), MigrateMsg {
+            orphaned_transfer_expiry: Duration::from_days(30),
+        })
+        .should_succeed();
+
+        assert!(NAMESPACE_OWNERS.has(&ctx.storage, (&rwa, Addr::mock(1))));
+        assert!(NAMESPACE_OWNERS.has(&ctx.storage, (&usd, Addr::mock(2))));
+        assert!(NAMESPACES_BY_OWNER.has(&ctx.storage, (Addr::mock(1), &rwa)));
+        assert!(NAMESPACES_BY_OWNER.has(&ctx.storage, (Addr::mock(2), &usd)));
+
+        assert!(LEGACY_NAMESPACE_OWNERS
+            .range(&ctx.storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn migrating_orphaned_transfers() {
+        let mut ctx = MockContext::new()
+            .with_sender(Addr::mock(1))
+            .with_funds(Coins::new());
+
+        ctx.set_block_timestamp(Timestamp::from_days(1));
+
+        // Two orphaned transfers made before they could expire.
+        for sender in [Addr::mock(1), Addr::mock(2)] {
+            ORPHANED_TRANSFERS
+                .save(
+                    &mut ctx.storage,
+                    (sender, Addr::mock(3)),
+                    &Coins::one("uusdc", 100).unwrap(),
+                )
+                .unwrap();
+        }
+
+        migrate(ctx.as_sudo(), MigrateMsg {
+            orphaned_transfer_expiry: Duration::from_days(30),
+        })
+        .should_succeed();
+
+        // The transfers should have been indexed, expiring 30 days from now.
+        for sender in [Addr::mock(1), Addr::mock(2)] {
+            let expiry = ORPHANED_TRANSFER_EXPIRIES
+                .load(&ctx.storage, (sender, Addr::mock(3)))
+                .unwrap();
+
+            assert_eq!(expiry, Timestamp::from_days(31));
+            assert!(ORPHANED_TRANSFERS_BY_EXPIRY.has(&ctx.storage, (expiry, sender, Addr::mock(3))));
+            assert!(ORPHANED_TRANSFERS_BY_RECIPIENT.has(&ctx.storage, (Addr::mock(3), sender)));
+        }
+
+        // The first sender recovers their transfer.
+        recover_transfer(ctx.as_mutable(), Addr::mock(1), Addr::mock(3)).should_succeed();
+
+        assert_eq!(
+            BALANCES
+                .load(
+                    &ctx.storage,
+                    (&Addr::mock(1), &Denom::from_str("uusdc").unwrap())
+                )
+                .unwrap(),
+            Uint128::new(100)
+        );
+        assert!(!ORPHANED_TRANSFERS.has(&ctx.storage, (Addr::mock(1), Addr::mock(3))));
+
+        // The other transfer is returned to its sender once expired.
+        ctx.set_block_timestamp(Timestamp::from_days(31));
+
+        cron_execute(ctx.as_sudo()).should_succeed();
+
+        assert_eq!(
+            BALANCES
+                .load(
+                    &ctx.storage,
+                    (&Addr::mock(2), &Denom::from_str("uusdc").unwrap())
+                )
+                .unwrap(),
+            Uint128::new(100)
+        );
+        assert!(!ORPHANED_TRANSFERS.has(&ctx.storage, (Addr::mock(2), Addr::mock(3))));
+    }
 }
@@ -1,27 +1,40 @@
 use {
-    crate::{BALANCES, METADATAS, NAMESPACE_OWNERS, SUPPLIES},
-    dango_types::bank::{Metadata, QueryMsg},
+    crate::{
+        ALLOWANCES, BALANCES, BALANCE_SNAPSHOTS, HOOKS, METADATAS, NAMESPACES_BY_OWNER,
+        NAMESPACE_OWNERS, ORPHANED_TRANSFERS, ORPHANED_TRANSFERS_BY_RECIPIENT,
+        ORPHANED_TRANSFER_EXPIRIES, SNAPSHOTTED_DENOMS, SUPPLIES,
+    },
+    anyhow::{anyhow, bail, ensure},
+    dango_types::bank::{Allowance, Metadata, OrphanedTransfer, QueryMsg},
     grug::{
         Addr, BankQuery, BankQueryResponse, Bound, Coin, Coins, Denom, ImmutableCtx, Json,
-        JsonSerExt, NumberConst, Order, Part, QueryBalanceRequest, QueryBalancesRequest,
-        QuerySuppliesRequest, QuerySupplyRequest, StdResult, Uint128,
+        JsonSerExt, NumberConst, Order, Part, PrefixBound, QueryBalanceRequest,
+        QueryBalancesRequest, QuerySuppliesRequest, QuerySupplyRequest, StdResult, Uint128,
     },
-    std::collections::BTreeMap,
+    std::collections::{BTreeMap, BTreeSet},
 };
 
 const DEFAULT_PAGE_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
     match msg {
         QueryMsg::Namespace { namespace } => {
             let res = query_namespace(ctx, namespace)?;
             res.to_json_value()
         },
+        QueryMsg::NamespaceOwners { namespace } => {
+            let res = query_namespace_owners(ctx, namespace)?;
+            res.to_json_value()
+        },
         QueryMsg::Namespaces { start_after, limit } => {
             let res = query_namespaces(ctx, start_after, limit)?;
             res.to_json_value()
         },
+        QueryMsg::NamespacesByOwner { owner } => {
+            let res = query_namespaces_by_owner(ctx, owner)?;
+            res.to_json_value()
+        },
         QueryMsg::Metadata { denom } => {
             let res = query_metadata(ctx, denom)?;
             res.to_json_value()
@@ -30,22 +43,133 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
             let res = query_metadatas(ctx, start_after, limit)?;
             res.to_json_value()
         },
+        QueryMsg::Hook { namespace } => {
+            let res = query_hook(ctx, namespace)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hooks { start_after, limit } => {
+            let res = query_hooks(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::BalanceAt {
+            address,
+            denom,
+            height,
+        } => {
+            let res = query_balance_at(ctx, address, denom, height)?;
+            res.to_json_value()
+        },
+        QueryMsg::SnapshottedDenoms { start_after, limit } => {
+            let res = query_snapshotted_denoms(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::Allowance {
+            owner,
+            spender,
+            denom,
+        } => {
+            let res = query_allowance(ctx, owner, spender, denom)?;
+            res.to_json_value()
+        },
+        QueryMsg::Allowances {
+            owner,
+            spender,
+            start_after,
+            limit,
+        } => {
+            let res = query_allowances(ctx, owner, spender, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::OrphanedTransfer { sender, recipient } => {
+            let res = query_orphaned_transfer(ctx, sender, recipient)?;
+            res.to_json_value()
+        },
+        QueryMsg::OrphanedTransfersBySender {
+            sender,
+            start_after,
+            limit,
+        } => {
+            let res = query_orphaned_transfers_by_sender(ctx, sender, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::OrphanedTransfersByRecipient {
+            recipient,
+            start_after,
+            limit,
+        } => {
+            let res = query_orphaned_transfers_by_recipient(ctx, recipient, start_after, limit)?;
+            res.to_json_value()
+        },
     }
+    .map_err(Into::into)
+}
+
+fn query_namespace(ctx: ImmutableCtx, namespace: Part) -> anyhow::Result<Addr> {
+    let mut owners = query_namespace_owners(ctx, namespace.clone())?.into_iter();
+
+    let Some(owner) = owners.next() else {
+        bail!("namespace `{namespace}` has no owner");
+    };
+
+    ensure!(
+        owners.next().is_none(),
+        "namespace `{namespace}` has more than one owner"
+    );
+
+    Ok(owner)
 }
 
-fn query_namespace(ctx: ImmutableCtx, namespace: Part) -> StdResult<Addr> {
-    NAMESPACE_OWNERS.load(ctx.storage, &namespace)
+fn query_namespace_owners(ctx: ImmutableCtx, namespace: Part) -> StdResult<BTreeSet<Addr>> {
+    NAMESPACE_OWNERS
+        .prefix(&namespace)
+        .keys(ctx.storage, None, None, Order::Ascending)
+        .collect()
 }
 
 fn query_namespaces(
     ctx: ImmutableCtx,
     start_after: Option<Part>,
     limit: Option<u32>,
+) -> StdResult<BTreeMap<Part, BTreeSet<Addr>>> {
+    let start = start_after.as_ref().map(PrefixBound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    let mut namespaces = BTreeMap::<Part, BTreeSet<Addr>>::new();
+
+    for res in NAMESPACE_OWNERS.prefix_range(ctx.storage, start, None, Order::Ascending) {
+        let (namespace, owner) = res?;
+
+        // Stop once we've collected enough namespaces and encounter a new one.
+        if !namespaces.contains_key(&namespace) && namespaces.len() >= limit {
+            break;
+        }
+
+        namespaces.entry(namespace).or_default().insert(owner);
+    }
+
+    Ok(namespaces)
+}
+
+fn query_namespaces_by_owner(ctx: ImmutableCtx, owner: Addr) -> StdResult<BTreeSet<Part>> {
+    NAMESPACES_BY_OWNER
+        .prefix(owner)
+        .keys(ctx.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+fn query_hook(ctx: ImmutableCtx, namespace: Part) -> StdResult<Option<Addr>> {
+    HOOKS.may_load(ctx.storage, &namespace)
+}
+
+fn query_hooks(
+    ctx: ImmutableCtx,
+    start_after: Option<Part>,
+    limit: Option<u32>,
 ) -> StdResult<BTreeMap<Part, Addr>> {
     let start = start_after.as_ref().map(Bound::Exclusive);
     let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
 
-    NAMESPACE_OWNERS
+    HOOKS
         .range(ctx.storage, start, None, Order::Ascending)
         .take(limit)
         .collect()
@@ -69,6 +193,152 @@ fn query_metadatas(
         .collect()
 }
 
+fn query_balance_at(
+    ctx: ImmutableCtx,
+    address: Addr,
+    denom: Denom,
+    height: u64,
+) -> anyhow::Result<Uint128> {
+    let enabled_at = SNAPSHOTTED_DENOMS
+        .may_load(ctx.storage, &denom)?
+        .ok_or_else(|| anyhow!("snapshots aren't enabled for denom `{denom}`"))?;
+
+    ensure!(
+        height >= enabled_at,
+        "snapshots of denom `{denom}` were enabled at height {enabled_at}, after height {height}"
+    );
+
+    ensure!(
+        height <= ctx.block.height,
+        "height {height} is greater than the current height {}",
+        ctx.block.height
+    );
+
+    // Find the latest snapshot at or before the height.
+    if let Some(res) = BALANCE_SNAPSHOTS
+        .prefix(&address)
+        .append(&denom)
+        .values(
+            ctx.storage,
+            None,
+            Some(Bound::Inclusive(height)),
+            Order::Descending,
+        )
+        .next()
+    {
+        return Ok(res?);
+    }
+
+    // The account's first snapshot is recorded at the enablement height, so if
+    // there's none at or before the height, there is none at all, meaning the
+    // balance hasn't changed since snapshots were enabled.
+    Ok(BALANCES
+        .may_load(ctx.storage, (&address, &denom))?
+        .unwrap_or(Uint128::ZERO))
+}
+
+fn query_snapshotted_denoms(
+    ctx: ImmutableCtx,
+    start_after: Option<Denom>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Denom, u64>> {
+    let start = start_after.as_ref().map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    SNAPSHOTTED_DENOMS
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn query_allowance(
+    ctx: ImmutableCtx,
+    owner: Addr,
+    spender: Addr,
+    denom: Denom,
+) -> StdResult<Option<Allowance>> {
+    ALLOWANCES.may_load(ctx.storage, (owner, spender, &denom))
+}
+
+fn query_allowances(
+    ctx: ImmutableCtx,
+    owner: Addr,
+    spender: Addr,
+    start_after: Option<Denom>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Denom, Allowance>> {
+    let start = start_after.as_ref().map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    ALLOWANCES
+        .prefix(owner)
+        .append(spender)
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn query_orphaned_transfer(
+    ctx: ImmutableCtx,
+    sender: Addr,
+    recipient: Addr,
+) -> StdResult<OrphanedTransfer> {
+    load_orphaned_transfer(&ctx, sender, recipient)
+}
+
+fn query_orphaned_transfers_by_sender(
+    ctx: ImmutableCtx,
+    sender: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Addr, OrphanedTransfer>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    ORPHANED_TRANSFERS
+        .prefix(sender)
+        .keys(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| {
+            let recipient = res?;
+            let transfer = load_orphaned_transfer(&ctx, sender, recipient)?;
+            Ok((recipient, transfer))
+        })
+        .collect()
+}
+
+fn query_orphaned_transfers_by_recipient(
+    ctx: ImmutableCtx,
+    recipient: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Addr, OrphanedTransfer>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    ORPHANED_TRANSFERS_BY_RECIPIENT
+        .prefix(recipient)
+        .keys(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|res| {
+            let sender = res?;
+            let transfer = load_orphaned_transfer(&ctx, sender, recipient)?;
+            Ok((sender, transfer))
+        })
+        .collect()
+}
+
+fn load_orphaned_transfer(
+    ctx: &ImmutableCtx,
+    sender: Addr,
+    recipient: Addr,
+) -> StdResult<OrphanedTransfer> {
+    Ok(OrphanedTransfer {
+        coins: ORPHANED_TRANSFERS.load(ctx.storage, (sender, recipient))?,
+        expires_at: ORPHANED_TRANSFER_EXPIRIES.load(ctx.storage, (sender, recipient))?,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn bank_query(ctx: ImmutableCtx, msg: BankQuery) -> StdResult<BankQueryResponse> {
     match msg {
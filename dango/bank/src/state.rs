@@ -1,9 +1,20 @@
 use {
-    dango_types::bank::Metadata,
-    grug::{Addr, Coins, Denom, Map, Part, Uint128},
+    dango_types::bank::{Allowance, Metadata},
+    grug::{Addr, Coins, Denom, Duration, Item, Map, Part, Set, Timestamp, Uint128},
 };
 
-pub const NAMESPACE_OWNERS: Map<&Part, Addr> = Map::new("namespace_owner");
+pub const ORPHANED_TRANSFER_EXPIRY: Item<Duration> = Item::new("orphaned_transfer_expiry");
+
+/// The owners of namespaces from before a namespace could have more than one
+/// owner. Only read by the `migrate` entry point, which moves them over to
+/// `NAMESPACE_OWNERS`.
+pub const LEGACY_NAMESPACE_OWNERS: Map<&Part, Addr> = Map::new("namespace_owner");
+
+pub const NAMESPACE_OWNERS: Set<(&Part, Addr)> = Set::new("namespace_owners");
+
+pub const NAMESPACES_BY_OWNER: Set<(Addr, &Part)> = Set::new("namespace__owner");
+
+pub const HOOKS: Map<&Part, Addr> = Map::new("hook");
 
 pub const METADATAS: Map<&Denom, Metadata> = Map::new("metadata");
 
@@ -11,5 +22,26 @@ pub const SUPPLIES: Map<&Denom, Uint128> = Map::new("supply");
 
 pub const BALANCES: Map<(&Addr, &Denom), Uint128> = Map::new("balance");
 
+// denom -> block height at which snapshots were enabled
+pub const SNAPSHOTTED_DENOMS: Map<&Denom, u64> = Map::new("snapshotted_denom");
+
+// (address, denom, block height) -> balance at the end of the block
+pub const BALANCE_SNAPSHOTS: Map<(&Addr, &Denom, u64), Uint128> = Map::new("balance_snapshot");
+
+// (owner, spender, denom) -> allowance
+pub const ALLOWANCES: Map<(Addr, Addr, &Denom), Allowance> = Map::new("allowance");
+
 // (sender, recipient) -> coins
 pub const ORPHANED_TRANSFERS: Map<(Addr, Addr), Coins> = Map::new("orphaned_deposit");
+
+// (recipient, sender)
+pub const ORPHANED_TRANSFERS_BY_RECIPIENT: Set<(Addr, Addr)> =
+    Set::new("orphaned_deposit__recipient");
+
+// (sender, recipient) -> expiry
+pub const ORPHANED_TRANSFER_EXPIRIES: Map<(Addr, Addr), Timestamp> =
+    Map::new("orphaned_deposit_expiry");
+
+// (expiry, sender, recipient)
+pub const ORPHANED_TRANSFERS_BY_EXPIRY: Set<(Timestamp, Addr, Addr)> =
+    Set::new("orphaned_deposit__expiry");
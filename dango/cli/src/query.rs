@@ -217,7 +217,11 @@ impl QueryCmd {
             SubCmd::WasmSmart { contract, msg } => {
                 // The input should be a JSON string, e.g. `{"config":{}}`
                 let msg = msg.deserialize_json()?;
-                Query::WasmSmart(QueryWasmSmartRequest { contract, msg })
+                Query::WasmSmart(QueryWasmSmartRequest {
+                    contract,
+                    msg,
+                    gas_limit: None,
+                })
             },
             SubCmd::Store { key, prove } => {
                 return query_store(&client, key, self.height, prove).await;
@@ -120,6 +120,11 @@ impl Signer for SingleSigner<Defined<u32>> {
                 chain_id: chain_id.to_string(),
                 nonce: self.nonce.into_inner(),
                 expiry: None, // TODO
+                referrer: None,
+                fee_denom: None,
+                granter: None,
+                max_fee_rate: None,
+                tip_rate: None,
             }
             .to_json_value()?,
         })
@@ -139,6 +144,11 @@ impl Signer for SingleSigner<Defined<u32>> {
             chain_id: chain_id.to_string(),
             nonce,
             expiry: None, // TODO
+            referrer: None,
+            fee_denom: None,
+            granter: None,
+            max_fee_rate: None,
+            tip_rate: None,
         };
 
         let sign_doc = SignDoc {
@@ -11,7 +11,8 @@ use {
         taxman,
     },
     grug::{
-        btree_map, coins, Coins, Duration, HashExt, Inner, Json, JsonDeExt, JsonSerExt, Udec128,
+        btree_map, btree_set, coins, Coins, Duration, HashExt, Inner, Json, JsonDeExt, JsonSerExt,
+        Udec128,
     },
     hex_literal::hex,
     home::home_dir,
@@ -113,9 +114,14 @@ fn main() {
         fee_cfg: taxman::Config {
             fee_denom: USDC_DENOM.clone(),
             fee_rate: Udec128::new_percent(25), // 0.25 uusdc per gas unit
+            alt_fee_denoms: btree_set! { ETH_DENOM.clone() },
+            alt_fee_premium: Udec128::new_percent(5), // 5% premium for fees paid in ETH
+            referral_fee_rate: Udec128::new_percent(20), // 20% of fee goes to the referrer
+            dynamic_fee: None,
         },
         max_orphan_age: Duration::from_weeks(1),
         metadatas: btree_map! {},
+        orphaned_transfer_expiry: Duration::from_weeks(4),
         pairs: vec![
             PairUpdate {
                 base_denom: DANGO_DENOM.clone(),
@@ -82,6 +82,9 @@ pub struct GenesisConfig<T> {
     pub max_orphan_age: Duration,
     /// Metadata of tokens.
     pub metadatas: BTreeMap<Denom, bank::Metadata>,
+    /// Time after which funds sent to a non-existing recipient are returned to
+    /// the sender.
+    pub orphaned_transfer_expiry: Duration,
     /// Initial Dango DEX trading pairs.
     pub pairs: Vec<PairUpdate>,
     /// Initial Dango lending markets.
@@ -109,12 +112,15 @@ pub fn build_rust_codes() -> Codes<ContractWrapper> {
         .with_execute(Box::new(dango_account_factory::execute))
         .with_query(Box::new(dango_account_factory::query))
         .with_authenticate(Box::new(dango_account_factory::authenticate))
+        .with_cron_execute(Box::new(dango_account_factory::cron_execute))
+        .with_reply(Box::new(dango_account_factory::reply))
         .build();
 
     let account_margin = ContractBuilder::new(Box::new(dango_account_margin::instantiate))
         .with_execute(Box::new(dango_account_margin::execute))
         .with_authenticate(Box::new(dango_account_margin::authenticate))
         .with_backrun(Box::new(dango_account_margin::backrun))
+        .with_migrate(Box::new(dango_account_margin::migrate))
         .with_receive(Box::new(dango_account_margin::receive))
         .with_query(Box::new(dango_account_margin::query))
         .build();
@@ -127,7 +133,10 @@ pub fn build_rust_codes() -> Codes<ContractWrapper> {
         .build();
 
     let account_spot = ContractBuilder::new(Box::new(dango_account_spot::instantiate))
+        .with_execute(Box::new(dango_account_spot::execute))
         .with_authenticate(Box::new(dango_account_spot::authenticate))
+        .with_backrun(Box::new(dango_account_spot::backrun))
+        .with_migrate(Box::new(dango_account_spot::migrate))
         .with_receive(Box::new(dango_account_spot::receive))
         .with_query(Box::new(dango_account_spot::query))
         .with_reply(Box::new(dango_account_spot::reply))
@@ -138,6 +147,8 @@ pub fn build_rust_codes() -> Codes<ContractWrapper> {
         .with_query(Box::new(dango_bank::query))
         .with_bank_execute(Box::new(dango_bank::bank_execute))
         .with_bank_query(Box::new(dango_bank::bank_query))
+        .with_cron_execute(Box::new(dango_bank::cron_execute))
+        .with_migrate(Box::new(dango_bank::migrate))
         .build();
 
     let dex = ContractBuilder::new(Box::new(dango_dex::instantiate))
@@ -158,6 +169,7 @@ pub fn build_rust_codes() -> Codes<ContractWrapper> {
 
     let mailbox = ContractBuilder::new(Box::new(hyperlane_mailbox::instantiate))
         .with_execute(Box::new(hyperlane_mailbox::execute))
+        .with_reply(Box::new(hyperlane_mailbox::reply))
         .with_query(Box::new(hyperlane_mailbox::query))
         .build();
 
@@ -187,6 +199,7 @@ pub fn build_rust_codes() -> Codes<ContractWrapper> {
         .with_query(Box::new(dango_taxman::query))
         .with_withhold_fee(Box::new(dango_taxman::withhold_fee))
         .with_finalize_fee(Box::new(dango_taxman::finalize_fee))
+        .with_cron_execute(Box::new(dango_taxman::cron_execute))
         .build();
 
     let vesting = ContractBuilder::new(Box::new(dango_vesting::instantiate))
@@ -275,6 +288,7 @@ pub fn build_genesis<T>(
         fee_cfg,
         max_orphan_age,
         metadatas,
+        orphaned_transfer_expiry,
         pairs,
         markets,
         price_sources,
@@ -359,7 +373,10 @@ where
     let fee = instantiate(
         &mut msgs,
         hyperlane_fee_code_hash,
-        &hooks::fee::InstantiateMsg { mailbox },
+        &hooks::fee::InstantiateMsg {
+            mailbox,
+            fees: BTreeMap::new(),
+        },
         "hyperlane/hook/fee",
         "hyperlane/hook/fee",
     )?;
@@ -462,6 +479,7 @@ where
                 warp::ALLOY_NAMESPACE.clone()    => warp,
             },
             metadatas,
+            orphaned_transfer_expiry,
         },
         "dango/bank",
         "dango/bank",
@@ -527,7 +545,15 @@ where
         bank,
         taxman,
         // Important: DEX cronjob is to be invoked at end of every block.
-        cronjobs: btree_map! { dex => Duration::ZERO },
+        // Bank cronjob returns expired orphaned transfers to their senders.
+        // Taxman cronjob adjusts the base fee according to the block's gas usage.
+        // Account factory cronjob makes the Safes' recurring transfers that are due.
+        cronjobs: btree_map! {
+            dex             => Duration::ZERO,
+            bank            => Duration::ZERO,
+            taxman          => Duration::ZERO,
+            account_factory => Duration::ZERO,
+        },
         permissions,
         max_orphan_age,
     };
@@ -1,19 +1,27 @@
 use {
-    crate::{CONFIG, WITHHELD_FEE},
-    anyhow::ensure,
+    crate::{
+        WithheldFee, BASE_FEE, BASE_FEES_COLLECTED, BLOCK_GAS_USED, CONFIG, FEE_GRANTS,
+        GRANT_DEPOSITS, REFERRAL_FEES, REFERRERS, WITHHELD_FEE,
+    },
+    anyhow::{anyhow, ensure},
+    dango_oracle::OracleQuerier,
     dango_types::{
+        auth::Metadata,
         bank,
-        taxman::{Config, ExecuteMsg, InstantiateMsg},
+        taxman::{BaseFeeDestination, Config, ExecuteMsg, FeeAllowance, FeeGrant, InstantiateMsg},
         DangoQuerier,
     },
     grug::{
-        Addr, AuthCtx, AuthMode, Coins, IsZero, Message, MultiplyFraction, MutableCtx, Number,
-        NumberConst, QuerierExt, Response, StdResult, Tx, TxOutcome, Uint128,
+        Addr, AuthCtx, AuthMode, Coin, Coins, Denom, IsZero, JsonDeExt, Message, MsgExecute,
+        MultiplyFraction, MultiplyRatio, MutableCtx, Number, NumberConst, QuerierExt, Response,
+        StdError, StdResult, Storage, SudoCtx, Timestamp, Tx, TxOutcome, Udec128, Uint128,
     },
+    std::cmp::{max, min},
 };
 
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    BASE_FEE.save(ctx.storage, &msg.config.fee_rate)?;
     CONFIG.save(ctx.storage, &msg.config)?;
 
     Ok(Response::new())
@@ -24,6 +32,15 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
     match msg {
         ExecuteMsg::Configure { new_cfg } => configure(ctx, new_cfg),
         ExecuteMsg::Pay { payer } => pay(ctx, payer),
+        ExecuteMsg::RegisterReferrer { referrer } => register_referrer(ctx, referrer),
+        ExecuteMsg::DeregisterReferrer { referrer } => deregister_referrer(ctx, referrer),
+        ExecuteMsg::ClaimReferralFees {} => claim_referral_fees(ctx),
+        ExecuteMsg::DepositGrantFunds {} => deposit_grant_funds(ctx),
+        ExecuteMsg::WithdrawGrantFunds { coins } => withdraw_grant_funds(ctx, coins),
+        ExecuteMsg::GrantFeeAllowance { grantee, allowance } => {
+            grant_fee_allowance(ctx, grantee, allowance)
+        },
+        ExecuteMsg::RevokeFeeAllowance { grantee } => revoke_fee_allowance(ctx, grantee),
     }
 }
 
@@ -34,6 +51,15 @@ fn configure(ctx: MutableCtx, new_cfg: Config) -> anyhow::Result<Response> {
         "you don't have the right, O you don't have the right"
     );
 
+    // If dynamic fees are enabled, keep the current base fee, as long as it's
+    // not below the new minimum. Otherwise, the base fee is fixed at the fee rate.
+    let base_fee = if new_cfg.dynamic_fee.is_some() {
+        max(BASE_FEE.load(ctx.storage)?, new_cfg.fee_rate)
+    } else {
+        new_cfg.fee_rate
+    };
+
+    BASE_FEE.save(ctx.storage, &base_fee)?;
     CONFIG.save(ctx.storage, &new_cfg)?;
 
     Ok(Response::new())
@@ -41,14 +67,157 @@ fn configure(ctx: MutableCtx, new_cfg: Config) -> anyhow::Result<Response> {
 
 fn pay(_ctx: MutableCtx, _payer: Addr) -> anyhow::Result<Response> {
     // For now, nothing to do.
-    // In the future, we will implement affiliate fees.
     Ok(Response::new())
 }
 
-// TODO: exempt the account factory from paying fee.
+fn register_referrer(ctx: MutableCtx, referrer: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "you don't have the right, O you don't have the right"
+    );
+
+    ensure!(
+        !REFERRERS.has(ctx.storage, referrer),
+        "`{referrer}` is already a registered referrer"
+    );
+
+    REFERRERS.insert(ctx.storage, referrer)?;
+
+    Ok(Response::new())
+}
+
+fn deregister_referrer(ctx: MutableCtx, referrer: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "you don't have the right, O you don't have the right"
+    );
+
+    ensure!(
+        REFERRERS.has(ctx.storage, referrer),
+        "`{referrer}` is not a registered referrer"
+    );
+
+    REFERRERS.remove(ctx.storage, referrer);
+
+    Ok(Response::new())
+}
+
+fn claim_referral_fees(ctx: MutableCtx) -> anyhow::Result<Response> {
+    let fees = REFERRAL_FEES
+        .may_take(ctx.storage, ctx.sender)?
+        .ok_or_else(|| anyhow!("no referral fees to claim"))?;
+
+    Ok(Response::new().add_message(Message::transfer(ctx.sender, fees)?))
+}
+
+fn deposit_grant_funds(ctx: MutableCtx) -> anyhow::Result<Response> {
+    ensure!(ctx.funds.is_non_empty(), "no funds to deposit");
+
+    GRANT_DEPOSITS.may_update(ctx.storage, ctx.sender, |maybe_deposit| {
+        let mut deposit = maybe_deposit.unwrap_or_default();
+        deposit.insert_many(ctx.funds)?;
+        Ok::<_, StdError>(deposit)
+    })?;
+
+    Ok(Response::new())
+}
+
+fn withdraw_grant_funds(ctx: MutableCtx, coins: Coins) -> anyhow::Result<Response> {
+    ensure!(coins.is_non_empty(), "no funds to withdraw");
+
+    let mut deposit = GRANT_DEPOSITS
+        .may_load(ctx.storage, ctx.sender)?
+        .ok_or_else(|| anyhow!("no grant deposit to withdraw from"))?;
+
+    deposit.deduct_many(coins.clone())?;
+
+    if deposit.is_empty() {
+        GRANT_DEPOSITS.remove(ctx.storage, ctx.sender);
+    } else {
+        GRANT_DEPOSITS.save(ctx.storage, ctx.sender, &deposit)?;
+    }
+
+    Ok(Response::new().add_message(Message::transfer(ctx.sender, coins)?))
+}
+
+fn grant_fee_allowance(
+    ctx: MutableCtx,
+    grantee: Addr,
+    allowance: FeeAllowance,
+) -> anyhow::Result<Response> {
+    ensure!(ctx.sender != grantee, "can't grant fee allowance to self");
+
+    if let Some(period_limit) = &allowance.period_limit {
+        ensure!(
+            period_limit.period.into_nanos() > 0,
+            "period of fee allowance can't be zero"
+        );
+    }
+
+    if let Some(expires_at) = allowance.expires_at {
+        ensure!(
+            expires_at > ctx.block.timestamp,
+            "expiration time must be in the future"
+        );
+    }
+
+    FEE_GRANTS.save(ctx.storage, (ctx.sender, grantee), &FeeGrant {
+        allowance,
+        spent: Uint128::ZERO,
+        period_start: ctx.block.timestamp,
+        period_spent: Uint128::ZERO,
+    })?;
+
+    Ok(Response::new())
+}
+
+fn revoke_fee_allowance(ctx: MutableCtx, grantee: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        FEE_GRANTS.has(ctx.storage, (ctx.sender, grantee)),
+        "no fee allowance granted to `{grantee}`"
+    );
+
+    FEE_GRANTS.remove(ctx.storage, (ctx.sender, grantee));
+
+    Ok(Response::new())
+}
+
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn withhold_fee(ctx: AuthCtx, tx: Tx) -> StdResult<Response> {
+pub fn withhold_fee(ctx: AuthCtx, tx: Tx) -> anyhow::Result<Response> {
     let fee_cfg = CONFIG.load(ctx.storage)?;
+    let base_fee = BASE_FEE.load(ctx.storage)?;
+
+    // Read the fee-related options from the transaction's metadata. Transactions
+    // without metadata, such as those sent by the oracle, use the defaults.
+    let (fee_denom, granter, max_fee_rate, tip_rate) = match tx.data.clone().deserialize_json() {
+        Ok(Metadata {
+            fee_denom,
+            granter,
+            max_fee_rate,
+            tip_rate,
+            ..
+        }) => (
+            fee_denom.unwrap_or_else(|| fee_cfg.fee_denom.clone()),
+            granter,
+            max_fee_rate,
+            tip_rate.unwrap_or(Udec128::ZERO),
+        ),
+        Err(_) => (fee_cfg.fee_denom.clone(), None, None, Udec128::ZERO),
+    };
+
+    // The fee per gas unit is the base fee plus the tip, capped at the maximum
+    // the sender is willing to pay.
+    let fee_rate = base_fee.checked_add(tip_rate)?;
+    let fee_rate = if let Some(max_fee_rate) = max_fee_rate {
+        ensure!(
+            max_fee_rate >= base_fee,
+            "max fee rate {max_fee_rate} is below the base fee {base_fee}"
+        );
+
+        min(fee_rate, max_fee_rate)
+    } else {
+        fee_rate
+    };
 
     // Compute the maximum amount of fee this transaction may incur.
     // Note that we ceil this amount, instead of flooring.
@@ -62,29 +231,67 @@ pub fn withhold_fee(ctx: AuthCtx, tx: Tx) -> StdResult<Response> {
     //    onboarding. We don't charge gas fee this in case.
     // 3. Sender is the oracle contract. Validators supply Pyth price feeds by
     //    using the oracle contract as sender during `PrepareProposal`.
-    let withhold_amount = if ctx.mode == AuthMode::Simulate || {
-        let app_cfg = ctx.querier.query_dango_config()?;
-        tx.sender == app_cfg.addresses.account_factory || tx.sender == app_cfg.addresses.oracle
-    } {
+    let app_cfg = ctx.querier.query_dango_config()?;
+    let base_amount = if ctx.mode == AuthMode::Simulate
+        || tx.sender == app_cfg.addresses.account_factory
+        || tx.sender == app_cfg.addresses.oracle
+    {
         Uint128::ZERO
     } else {
-        Uint128::new(tx.gas_limit as u128).checked_mul_dec_ceil(fee_cfg.fee_rate)?
+        Uint128::new(tx.gas_limit as u128).checked_mul_dec_ceil(fee_rate)?
+    };
+
+    // If it's an alternative fee denom, convert the fee amount into that denom.
+    let withhold_amount = if fee_denom == fee_cfg.fee_denom || base_amount.is_zero() {
+        base_amount
+    } else {
+        ensure!(
+            fee_cfg.alt_fee_denoms.contains(&fee_denom),
+            "fee denom `{fee_denom}` is not accepted"
+        );
+
+        convert_fee(
+            &ctx,
+            app_cfg.addresses.oracle,
+            &fee_cfg,
+            &fee_denom,
+            base_amount,
+        )?
     };
 
-    // If the withhold amount is non-zero, we force transfer this amount from
-    // the sender to taxman.
+    // If a granter is named, the fee is withheld from the granter's deposit,
+    // which the taxman already holds.
+    //
+    // Otherwise, if the withhold amount is non-zero, we force transfer this
+    // amount from the sender to taxman.
     //
     // If the sender doesn't have enough fund to cover the maximum amount of fee
     // the tx may incur, this submessage fails, causing the tx to be rejected
     // from entering the mempool.
-    let withhold_msg = if withhold_amount.is_non_zero() {
+    let withhold_msg = if let Some(granter) = granter {
+        ensure!(
+            fee_denom == fee_cfg.fee_denom,
+            "fee grants can only pay fees in `{}`",
+            fee_cfg.fee_denom
+        );
+
+        withhold_from_grant(
+            ctx.storage,
+            ctx.block.timestamp,
+            granter,
+            &tx,
+            Coin::new(fee_denom.clone(), withhold_amount)?,
+        )?;
+
+        None
+    } else if withhold_amount.is_non_zero() {
         let bank = ctx.querier.query_bank()?;
         Some(Message::execute(
             bank,
             &bank::ExecuteMsg::ForceTransfer {
                 from: tx.sender,
                 to: ctx.contract,
-                denom: fee_cfg.fee_denom.clone(),
+                denom: fee_denom.clone(),
                 amount: withhold_amount,
             },
             Coins::new(),
@@ -94,43 +301,123 @@ pub fn withhold_fee(ctx: AuthCtx, tx: Tx) -> StdResult<Response> {
     };
 
     // Save the withheld fee in storage, which we will use in `finalize_fee`.
-    WITHHELD_FEE.save(ctx.storage, &(fee_cfg, withhold_amount))?;
+    WITHHELD_FEE.save(ctx.storage, &WithheldFee {
+        config: fee_cfg,
+        base_fee,
+        fee_rate,
+        denom: fee_denom,
+        amount: withhold_amount,
+        base_amount,
+        granter,
+    })?;
 
     Ok(Response::new().may_add_message(withhold_msg))
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn finalize_fee(ctx: AuthCtx, tx: Tx, outcome: TxOutcome) -> StdResult<Response> {
-    let (fee_cfg, withheld_amount) = WITHHELD_FEE.take(ctx.storage)?;
+    let WithheldFee {
+        config: fee_cfg,
+        base_fee,
+        fee_rate,
+        denom: fee_denom,
+        amount: withheld_amount,
+        base_amount: withheld_base_amount,
+        granter,
+    } = WITHHELD_FEE.take(ctx.storage)?;
 
     // Compute how much fee to charge the sender, based on the actual amount of
     // gas consumed.
     //
     // Again, during simulation, or any tx sent by the account factory, is
     // exempt from gas fees.
-    let charge_amount = if ctx.mode == AuthMode::Simulate || {
+    let charge_base_amount = if ctx.mode == AuthMode::Simulate || {
         let app_cfg = ctx.querier.query_dango_config()?;
         tx.sender == app_cfg.addresses.account_factory || tx.sender == app_cfg.addresses.oracle
     } {
         Uint128::ZERO
     } else {
-        Uint128::new(outcome.gas_used as u128).checked_mul_dec_ceil(fee_cfg.fee_rate)?
+        Uint128::new(outcome.gas_used as u128).checked_mul_dec_ceil(fee_rate)?
+    };
+
+    // Convert the charge into the denom the fee was withheld in, at the same
+    // rate as the withheld amount was converted.
+    let charge_amount = if fee_denom == fee_cfg.fee_denom {
+        charge_base_amount
+    } else if withheld_base_amount.is_zero() {
+        Uint128::ZERO
+    } else {
+        withheld_amount.checked_multiply_ratio_ceil(charge_base_amount, withheld_base_amount)?
     };
 
     // If we have withheld more funds than the actual charge amount, we need to
     // refund the difference.
+    let collected_amount = min(withheld_amount, charge_amount);
     let refund_amount = withheld_amount.saturating_sub(charge_amount);
 
-    // Use ForceTransfer instead of Transfer so that we don't need to invoke the
-    // sender's `receive` method (unnecessary).
-    let refund_msg = if refund_amount.is_non_zero() {
+    // Record the gas used, by which the base fee is adjusted at the end of the
+    // block.
+    BLOCK_GAS_USED.may_update(ctx.storage, |maybe_gas_used| {
+        Ok::<_, StdError>(maybe_gas_used.unwrap_or(0).saturating_add(outcome.gas_used))
+    })?;
+
+    // If dynamic fees are enabled, set aside the base fee portion of the fee
+    // collected, to be burned or sent to the treasury at the end of the block.
+    // Only the rest, i.e. the tip, is retained by the taxman.
+    let retained_amount = if fee_cfg.dynamic_fee.is_some() && fee_rate.is_non_zero() {
+        let base_fee_amount =
+            collected_amount.checked_mul_dec_floor(base_fee.checked_div(fee_rate)?)?;
+
+        if base_fee_amount.is_non_zero() {
+            BASE_FEES_COLLECTED.may_update(ctx.storage, |maybe_fees| {
+                let mut fees = maybe_fees.unwrap_or_default();
+                fees.insert(Coin::new(fee_denom.clone(), base_fee_amount)?)?;
+                Ok::<_, StdError>(fees)
+            })?;
+        }
+
+        collected_amount - base_fee_amount
+    } else {
+        collected_amount
+    };
+
+    // If the transaction names a registered referrer, credit the referrer its
+    // share of the fee retained. The share stays in the taxman until the
+    // referrer claims it.
+    if let Ok(Metadata {
+        referrer: Some(referrer),
+        ..
+    }) = tx.data.deserialize_json()
+    {
+        let collected = Coin::new(fee_denom.clone(), retained_amount)?;
+
+        accrue_referral_fee(ctx.storage, &fee_cfg, tx.sender, referrer, collected)?;
+    }
+
+    // If the fee was paid by a granter, record the spending against the grant,
+    // and return the refund to the granter's deposit.
+    //
+    // Otherwise, use ForceTransfer instead of Transfer so that we don't need to
+    // invoke the sender's `receive` method (unnecessary).
+    let refund_msg = if let Some(granter) = granter {
+        settle_grant(
+            ctx.storage,
+            ctx.block.timestamp,
+            granter,
+            tx.sender,
+            collected_amount,
+            Coin::new(fee_denom, refund_amount)?,
+        )?;
+
+        None
+    } else if refund_amount.is_non_zero() {
         let bank = ctx.querier.query_bank()?;
         Some(Message::execute(
             bank,
             &bank::ExecuteMsg::ForceTransfer {
                 from: ctx.contract,
                 to: tx.sender,
-                denom: fee_cfg.fee_denom,
+                denom: fee_denom,
                 amount: refund_amount,
             },
             Coins::new(),
@@ -141,3 +428,224 @@ pub fn finalize_fee(ctx: AuthCtx, tx: Tx, outcome: TxOutcome) -> StdResult<Respo
 
     Ok(Response::new().may_add_message(refund_msg))
 }
+
+/// Adjust the base fee according to the gas used in the block, and dispose of
+/// the base fees collected.
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn cron_execute(ctx: SudoCtx) -> StdResult<Response> {
+    let fee_cfg = CONFIG.load(ctx.storage)?;
+    let gas_used = BLOCK_GAS_USED.may_take(ctx.storage)?.unwrap_or(0);
+
+    let Some(params) = fee_cfg.dynamic_fee else {
+        return Ok(Response::new());
+    };
+
+    // The base fee changes proportionally to how much the gas used deviates
+    // from the target, by up to the maximum change rate.
+    let base_fee = BASE_FEE.load(ctx.storage)?;
+    let target = params.target_gas_per_block.max(1);
+    let deviation = Udec128::checked_from_ratio(
+        Uint128::new(gas_used.abs_diff(target) as u128),
+        Uint128::new(target as u128),
+    )?;
+    let change = base_fee
+        .checked_mul(min(deviation, Udec128::ONE))?
+        .checked_mul(params.max_change_rate)?;
+
+    let new_base_fee = if gas_used > target {
+        base_fee.checked_add(change)?
+    } else {
+        max(base_fee.saturating_sub(change), fee_cfg.fee_rate)
+    };
+
+    BASE_FEE.save(ctx.storage, &new_base_fee)?;
+
+    let fees = BASE_FEES_COLLECTED
+        .may_take(ctx.storage)?
+        .unwrap_or_default();
+
+    if fees.is_empty() {
+        return Ok(Response::new());
+    }
+
+    let msgs = match params.base_fee_destination {
+        BaseFeeDestination::Burn => {
+            let bank = ctx.querier.query_bank()?;
+            fees.into_iter()
+                .map(|coin| {
+                    Message::execute(
+                        bank,
+                        &bank::ExecuteMsg::Burn {
+                            from: ctx.contract,
+                            denom: coin.denom,
+                            amount: coin.amount,
+                        },
+                        Coins::new(),
+                    )
+                })
+                .collect::<StdResult<Vec<_>>>()?
+        },
+        BaseFeeDestination::Treasury(treasury) => vec![Message::transfer(treasury, fees)?],
+    };
+
+    Ok(Response::new().add_messages(msgs))
+}
+
+/// Ensure the transaction is covered by the fee grant, and withhold the fee
+/// from the granter's deposit.
+fn withhold_from_grant(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    granter: Addr,
+    tx: &Tx,
+    fee: Coin,
+) -> anyhow::Result<()> {
+    let grant = FEE_GRANTS
+        .may_load(storage, (granter, tx.sender))?
+        .ok_or_else(|| {
+            anyhow!(
+                "`{granter}` has not granted a fee allowance to `{}`",
+                tx.sender
+            )
+        })?;
+
+    if let Some(expires_at) = grant.allowance.expires_at {
+        ensure!(now < expires_at, "fee allowance has expired");
+    }
+
+    if let Some(contracts) = &grant.allowance.allowed_contracts {
+        ensure!(
+            tx.msgs.iter().all(|msg| match msg {
+                Message::Execute(MsgExecute { contract, .. }) => contracts.contains(contract),
+                _ => false,
+            }),
+            "fee allowance doesn't cover this transaction"
+        );
+    }
+
+    if let Some(limit) = grant.allowance.total_limit {
+        ensure!(
+            grant.spent.checked_add(fee.amount)? <= limit,
+            "fee allowance exceeded"
+        );
+    }
+
+    if let Some(period_limit) = &grant.allowance.period_limit {
+        let period_spent = if now >= grant.period_start + period_limit.period {
+            Uint128::ZERO
+        } else {
+            grant.period_spent
+        };
+
+        ensure!(
+            period_spent.checked_add(fee.amount)? <= period_limit.limit,
+            "periodic fee allowance exceeded"
+        );
+    }
+
+    if fee.amount.is_non_zero() {
+        let mut deposit = GRANT_DEPOSITS
+            .may_load(storage, granter)?
+            .unwrap_or_default();
+
+        ensure!(
+            deposit.amount_of(&fee.denom) >= fee.amount,
+            "insufficient grant deposit of `{granter}`"
+        );
+
+        deposit.deduct(fee)?;
+
+        GRANT_DEPOSITS.save(storage, granter, &deposit)?;
+    }
+
+    Ok(())
+}
+
+/// Record the fee actually paid against the grant, and return the excess to
+/// the granter's deposit.
+fn settle_grant(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    granter: Addr,
+    grantee: Addr,
+    spent_amount: Uint128,
+    refund: Coin,
+) -> StdResult<()> {
+    // The grant may have been revoked during the transaction, in which case
+    // there's nothing to record.
+    FEE_GRANTS.may_modify(storage, (granter, grantee), |maybe_grant| {
+        maybe_grant
+            .map(|mut grant| {
+                if let Some(period_limit) = &grant.allowance.period_limit {
+                    if now >= grant.period_start + period_limit.period {
+                        grant.period_start = now;
+                        grant.period_spent = Uint128::ZERO;
+                    }
+                }
+
+                grant.spent.checked_add_assign(spent_amount)?;
+                grant.period_spent.checked_add_assign(spent_amount)?;
+
+                Ok::<_, StdError>(grant)
+            })
+            .transpose()
+    })?;
+
+    if refund.amount.is_non_zero() {
+        GRANT_DEPOSITS.may_update(storage, granter, |maybe_deposit| {
+            let mut deposit = maybe_deposit.unwrap_or_default();
+            deposit.insert(refund)?;
+            Ok::<_, StdError>(deposit)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Convert an amount of the base fee denom into the given alternative fee denom,
+/// according to the oracle prices, plus the premium.
+fn convert_fee(
+    ctx: &AuthCtx,
+    oracle: Addr,
+    fee_cfg: &Config,
+    alt_denom: &Denom,
+    base_amount: Uint128,
+) -> anyhow::Result<Uint128> {
+    let base_price = ctx.querier.query_price(oracle, &fee_cfg.fee_denom)?;
+    let alt_price = ctx.querier.query_price(oracle, alt_denom)?;
+
+    let value = base_price
+        .value_of_unit_amount(base_amount)?
+        .checked_mul(Udec128::ONE.checked_add(fee_cfg.alt_fee_premium)?)?;
+
+    Ok(alt_price.unit_amount_from_value_ceil(value)?)
+}
+
+fn accrue_referral_fee(
+    storage: &mut dyn Storage,
+    fee_cfg: &Config,
+    payer: Addr,
+    referrer: Addr,
+    fee: Coin,
+) -> StdResult<()> {
+    // Unregistered referrers are ignored, and so are senders naming themselves.
+    if referrer == payer || !REFERRERS.has(storage, referrer) {
+        return Ok(());
+    }
+
+    let referral_amount = fee
+        .amount
+        .checked_mul_dec_floor(fee_cfg.referral_fee_rate)?;
+
+    if referral_amount.is_zero() {
+        return Ok(());
+    }
+
+    REFERRAL_FEES.may_update(storage, referrer, |maybe_fees| {
+        let mut fees = maybe_fees.unwrap_or_default();
+        fees.insert(Coin::new(fee.denom, referral_amount)?)?;
+        Ok::<_, StdError>(fees)
+    })?;
+
+    Ok(())
+}
@@ -1,16 +1,100 @@
 use {
-    crate::CONFIG,
-    dango_types::taxman::{Config, QueryMsg},
-    grug::{ImmutableCtx, Json, JsonSerExt, StdResult},
+    crate::{BASE_FEE, CONFIG, FEE_GRANTS, GRANT_DEPOSITS, REFERRAL_FEES, REFERRERS},
+    dango_types::taxman::{Config, FeeGrant, QueryMsg},
+    grug::{Addr, Bound, Coins, ImmutableCtx, Json, JsonSerExt, Order, StdResult, Udec128},
+    std::collections::{BTreeMap, BTreeSet},
 };
 
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
     match msg {
         QueryMsg::Config {} => query_config(ctx)?.to_json_value(),
+        QueryMsg::BaseFee {} => query_base_fee(ctx)?.to_json_value(),
+        QueryMsg::Referrers { start_after, limit } => {
+            query_referrers(ctx, start_after, limit)?.to_json_value()
+        },
+        QueryMsg::ReferralFees { referrer } => query_referral_fees(ctx, referrer)?.to_json_value(),
+        QueryMsg::AllReferralFees { start_after, limit } => {
+            query_all_referral_fees(ctx, start_after, limit)?.to_json_value()
+        },
+        QueryMsg::GrantDeposit { granter } => query_grant_deposit(ctx, granter)?.to_json_value(),
+        QueryMsg::FeeGrant { granter, grantee } => {
+            query_fee_grant(ctx, granter, grantee)?.to_json_value()
+        },
+        QueryMsg::FeeGrants {
+            granter,
+            start_after,
+            limit,
+        } => query_fee_grants(ctx, granter, start_after, limit)?.to_json_value(),
     }
 }
 
 fn query_config(ctx: ImmutableCtx) -> StdResult<Config> {
     CONFIG.load(ctx.storage)
 }
+
+fn query_base_fee(ctx: ImmutableCtx) -> StdResult<Udec128> {
+    BASE_FEE.load(ctx.storage)
+}
+
+fn query_referrers(
+    ctx: ImmutableCtx,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeSet<Addr>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    REFERRERS
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn query_referral_fees(ctx: ImmutableCtx, referrer: Addr) -> StdResult<Coins> {
+    REFERRAL_FEES
+        .may_load(ctx.storage, referrer)
+        .map(Option::unwrap_or_default)
+}
+
+fn query_all_referral_fees(
+    ctx: ImmutableCtx,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Addr, Coins>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    REFERRAL_FEES
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn query_grant_deposit(ctx: ImmutableCtx, granter: Addr) -> StdResult<Coins> {
+    GRANT_DEPOSITS
+        .may_load(ctx.storage, granter)
+        .map(Option::unwrap_or_default)
+}
+
+fn query_fee_grant(ctx: ImmutableCtx, granter: Addr, grantee: Addr) -> StdResult<Option<FeeGrant>> {
+    FEE_GRANTS.may_load(ctx.storage, (granter, grantee))
+}
+
+fn query_fee_grants(
+    ctx: ImmutableCtx,
+    granter: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Addr, FeeGrant>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    FEE_GRANTS
+        .prefix(granter)
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
@@ -1,8 +1,47 @@
 use {
-    dango_types::taxman::Config,
-    grug::{Item, Uint128},
+    dango_types::taxman::{Config, FeeGrant},
+    grug::{Addr, Coins, Denom, Item, Map, Set, Udec128, Uint128},
 };
 
+/// Fee withheld from a transaction in `withhold_fee`, to be settled in
+/// `finalize_fee`.
+#[grug::derive(Borsh)]
+pub struct WithheldFee {
+    pub config: Config,
+    /// The base fee per gas unit at the time the fee was withheld.
+    pub base_fee: Udec128,
+    /// The fee per gas unit charged, i.e. the base fee plus the tip, capped at
+    /// the sender's maximum.
+    pub fee_rate: Udec128,
+    /// The denom the fee is paid in.
+    pub denom: Denom,
+    /// The amount withheld, in `denom`.
+    pub amount: Uint128,
+    /// The equivalent of `amount` in the config's fee denom.
+    pub base_amount: Uint128,
+    /// The account paying the fee on behalf of the sender, if any.
+    pub granter: Option<Addr>,
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
 
-pub const WITHHELD_FEE: Item<(Config, Uint128)> = Item::new("withheld_fee");
+pub const WITHHELD_FEE: Item<WithheldFee> = Item::new("withheld_fee");
+
+/// The current base fee per gas unit.
+pub const BASE_FEE: Item<Udec128> = Item::new("base_fee");
+
+/// Total gas used by transactions in the current block.
+pub const BLOCK_GAS_USED: Item<u64> = Item::new("block_gas_used");
+
+/// Base fees collected in the current block, to be burned or sent to the
+/// treasury at the end of the block.
+pub const BASE_FEES_COLLECTED: Item<Coins> = Item::new("base_fees_collected");
+
+pub const REFERRERS: Set<Addr> = Set::new("referrer");
+
+pub const REFERRAL_FEES: Map<Addr, Coins> = Map::new("referral_fee");
+
+pub const GRANT_DEPOSITS: Map<Addr, Coins> = Map::new("grant_deposit");
+
+/// Fee grants, indexed by (granter, grantee).
+pub const FEE_GRANTS: Map<(Addr, Addr), FeeGrant> = Map::new("fee_grant");
@@ -163,6 +163,11 @@ where
             chain_id: chain_id.to_string(),
             expiry,
             nonce,
+            referrer: None,
+            fee_denom: None,
+            granter: None,
+            max_fee_rate: None,
+            tip_rate: None,
         }
     }
 
@@ -2,7 +2,7 @@ use {
     crate::generate_random_key,
     dango_types::{
         config::{AppAddresses, AppConfig},
-        warp::{self, QueryRouteRequest, Route, TokenMessage},
+        warp::{self, QueryRouteRequest, QueuedTransferId, RateLimits, Route, TokenMessage},
     },
     grug::{
         Addr, Coin, Coins, Denom, Hash256, HashExt, HexBinary, HexByteArray, Inner, NumberConst,
@@ -235,6 +235,9 @@ where
                 destination_domain: domain,
                 recipient: to,
                 metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
             },
             coin,
         )
@@ -268,6 +271,98 @@ where
         )
     }
 
+    pub fn set_alloy(
+        &mut self,
+        underlying_denom: Denom,
+        alloyed_denom: Denom,
+        cap: Option<Uint128>,
+    ) -> TxOutcome {
+        let warp_addr = self.addresses().warp;
+
+        let shared_owner = self.suite.owner.clone();
+        let mut owner = shared_owner.write_access();
+
+        self.suite.execute(
+            owner.deref_mut(),
+            warp_addr,
+            &warp::ExecuteMsg::SetAlloy {
+                underlying_denom,
+                alloyed_denom,
+                cap,
+            },
+            Coins::new(),
+        )
+    }
+
+    pub fn set_rate_limits(
+        &mut self,
+        denom: Denom,
+        domain: Domain,
+        limits: RateLimits,
+    ) -> TxOutcome {
+        let warp_addr = self.addresses().warp;
+
+        let shared_owner = self.suite.owner.clone();
+        let mut owner = shared_owner.write_access();
+
+        self.suite.execute(
+            owner.deref_mut(),
+            warp_addr,
+            &warp::ExecuteMsg::SetRateLimits {
+                denom,
+                domain,
+                limits,
+            },
+            Coins::new(),
+        )
+    }
+
+    pub fn set_route_paused(&mut self, denom: Denom, domain: Domain, paused: bool) -> TxOutcome {
+        let warp_addr = self.addresses().warp;
+
+        let shared_owner = self.suite.owner.clone();
+        let mut owner = shared_owner.write_access();
+
+        self.suite.execute(
+            owner.deref_mut(),
+            warp_addr,
+            &warp::ExecuteMsg::SetRoutePaused {
+                denom,
+                domain,
+                paused,
+            },
+            Coins::new(),
+        )
+    }
+
+    pub fn set_ism(&mut self, ism: Option<Addr>) -> TxOutcome {
+        let warp_addr = self.addresses().warp;
+
+        let shared_owner = self.suite.owner.clone();
+        let mut owner = shared_owner.write_access();
+
+        self.suite.execute(
+            owner.deref_mut(),
+            warp_addr,
+            &warp::ExecuteMsg::SetIsm { ism },
+            Coins::new(),
+        )
+    }
+
+    pub fn cancel_queued_transfer(&mut self, id: QueuedTransferId) -> TxOutcome {
+        let warp_addr = self.addresses().warp;
+
+        let shared_owner = self.suite.owner.clone();
+        let mut owner = shared_owner.write_access();
+
+        self.suite.execute(
+            owner.deref_mut(),
+            warp_addr,
+            &warp::ExecuteMsg::CancelQueuedTransfer { id },
+            Coins::new(),
+        )
+    }
+
     fn create_msg(
         &mut self,
         origin_domain: Domain,
@@ -26,7 +26,7 @@ use {
     grug_vm_wasm::WasmVm,
     hex_literal::hex,
     indexer_sql::{non_blocking_indexer::NonBlockingIndexer, Context},
-    std::path::PathBuf,
+    std::{collections::BTreeSet, path::PathBuf},
 };
 
 pub const MOCK_CHAIN_ID: &str = "mock-1";
@@ -308,9 +308,14 @@ where
         fee_cfg: taxman::Config {
             fee_denom: USDC_DENOM.clone(),
             fee_rate: Udec128::ZERO,
+            alt_fee_denoms: BTreeSet::new(),
+            alt_fee_premium: Udec128::ZERO,
+            referral_fee_rate: Udec128::ZERO,
+            dynamic_fee: None,
         },
         max_orphan_age: Duration::from_seconds(7 * 24 * 60 * 60),
         metadatas: btree_map! {},
+        orphaned_transfer_expiry: Duration::from_days(30),
         pairs: vec![
             PairUpdate {
                 base_denom: DANGO_DENOM.clone(),
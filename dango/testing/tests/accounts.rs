@@ -0,0 +1,315 @@
+use {
+    dango_testing::{setup_test_naive, TestAccount},
+    dango_types::{
+        account::single,
+        account_factory::{self, AccountParams, AccountType, Username},
+        constants::USDC_DENOM,
+    },
+    grug::{btree_map, Addressable, Coins, HashExt, QuerierExt, ResultExt, Uint128},
+    std::str::FromStr,
+};
+
+#[test]
+fn changing_account_type() {
+    let (mut suite, mut accounts, codes, contracts) = setup_test_naive();
+
+    // Owner registers a new spot account.
+    let mut spot = accounts
+        .owner
+        .register_new_account(
+            &mut suite,
+            contracts.account_factory,
+            AccountParams::Spot(single::Params::new(accounts.owner.username.clone())),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .unwrap();
+
+    // Can't change into the same type.
+    suite
+        .execute(
+            &mut spot,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::ChangeAccountType {
+                account_type: AccountType::Spot,
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("account is already of type `spot`");
+
+    // Can't change into a Safe.
+    suite
+        .execute(
+            &mut spot,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::ChangeAccountType {
+                account_type: AccountType::Safe,
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("can't change a single-signature account into a Safe");
+
+    // Change the account into a margin account.
+    suite
+        .execute(
+            &mut spot,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::ChangeAccountType {
+                account_type: AccountType::Margin,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The account's params should have been updated.
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryAccountRequest {
+                address: spot.address(),
+            },
+        )
+        .should_succeed_and(|account| account.params.clone().is_margin());
+
+    // The account should have been migrated to the margin account code.
+    suite
+        .query_contract(spot.address())
+        .should_succeed_and(|info| info.code_hash == codes.account_margin.to_bytes().hash256());
+
+    // The balance is unaffected.
+    suite
+        .query_balance(&spot, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(100));
+
+    // The account can still send transactions.
+    suite
+        .transfer(
+            &mut spot,
+            accounts.user1.address(),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_succeed();
+}
+
+#[test]
+fn deleting_account() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let mut account = accounts
+        .owner
+        .register_new_account(
+            &mut suite,
+            contracts.account_factory,
+            AccountParams::Spot(single::Params::new(accounts.owner.username.clone())),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .unwrap();
+
+    // Can't delete an account that holds a balance.
+    suite
+        .execute(
+            &mut account,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::DeleteAccount {},
+            Coins::new(),
+        )
+        .should_fail_with_error("account must be empty before it can be deleted");
+
+    // Empty the account, then delete it.
+    suite
+        .transfer(
+            &mut account,
+            accounts.owner.address(),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut account,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::DeleteAccount {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The account should no longer be associated with the user.
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryAccountsByUserRequest {
+                username: accounts.owner.username.clone(),
+            },
+        )
+        .should_succeed_and(|accounts| !accounts.contains_key(&account.address()));
+
+    // The deleted account rejects incoming transfers, since nobody could
+    // retrieve the funds from it.
+    suite
+        .transfer(
+            &mut accounts.owner,
+            account.address(),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error(format!(
+            "account {} has been deleted and can't receive funds",
+            account.address()
+        ));
+
+    // The deleted account can no longer send transactions.
+    suite
+        .execute(
+            &mut account,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::DeleteAccount {},
+            Coins::new(),
+        )
+        .should_fail_with_error("isn't associated with user");
+}
+
+#[test]
+fn transferring_username() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    // The buyer generates a new key, which will control user1's username.
+    let buyer = TestAccount::new_random("user1");
+    let (key, key_hash) = (buyer.key(), buyer.key_hash());
+    let mut buyer = buyer.set_address(&btree_map! {
+        accounts.user1.username.clone() => accounts.user1.address(),
+    });
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::TransferUsername { key_hash, key },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Only the new key should be associated with the username.
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryKeysByUserRequest {
+                username: accounts.user1.username.clone(),
+            },
+        )
+        .should_succeed_and_equal(btree_map! { key_hash => key });
+
+    // The old key can no longer sign transactions.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail();
+
+    // The new key can.
+    buyer.nonce = accounts.user1.nonce;
+
+    suite
+        .transfer(
+            &mut buyer,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_succeed();
+}
+
+#[test]
+fn setting_alias() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let alias = Username::from_str("acme").unwrap();
+
+    // Can't use an existing username as alias.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::SetAlias {
+                alias: Some(accounts.user2.username.clone()),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("is already taken by a username or an alias");
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::SetAlias {
+                alias: Some(alias.clone()),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryAliasRequest {
+                username: accounts.user1.username.clone(),
+            },
+        )
+        .should_succeed_and_equal(Some(alias.clone()));
+
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryUserByAliasRequest {
+                alias: alias.clone(),
+            },
+        )
+        .should_succeed_and_equal(accounts.user1.username.clone());
+
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryUsernameByAddressRequest {
+                address: accounts.user1.address(),
+            },
+        )
+        .should_succeed_and_equal(Some(accounts.user1.username.clone()));
+
+    // Another user can't take the same alias.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::SetAlias {
+                alias: Some(alias.clone()),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("is already taken by a username or an alias");
+
+    // User1 unsets the alias; then user2 can take it.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::SetAlias { alias: None },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::SetAlias {
+                alias: Some(alias.clone()),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryUserByAliasRequest { alias },
+        )
+        .should_succeed_and_equal(accounts.user2.username.clone());
+}
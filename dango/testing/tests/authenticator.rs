@@ -0,0 +1,296 @@
+use {
+    dango_testing::setup_test_naive,
+    dango_types::{
+        account::spot::{ExecuteMsg, QueryAuthenticatorsRequest},
+        constants::USDC_DENOM,
+    },
+    grug::{Addressable, Coins, Empty, Message, NonEmpty, QuerierExt, ResultExt},
+    grug_vm_rust::ContractBuilder,
+    std::collections::BTreeSet,
+};
+
+/// An authenticator that only allows the account to transfer tokens to a
+/// fixed allow-list of recipients.
+mod allowlist_authenticator {
+    use {
+        anyhow::ensure,
+        dango_types::auth::AuthenticatorQueryMsg,
+        grug::{Addr, Empty, ImmutableCtx, Item, Json, JsonSerExt, Message, MutableCtx, Response},
+        std::collections::BTreeSet,
+    };
+
+    const ALLOWED_RECIPIENTS: Item<BTreeSet<Addr>> = Item::new("allowed_recipients");
+
+    pub fn instantiate(ctx: MutableCtx, allowed: BTreeSet<Addr>) -> anyhow::Result<Response> {
+        ALLOWED_RECIPIENTS.save(ctx.storage, &allowed)?;
+
+        Ok(Response::new())
+    }
+
+    pub fn query(ctx: ImmutableCtx, msg: AuthenticatorQueryMsg) -> anyhow::Result<Json> {
+        match msg {
+            AuthenticatorQueryMsg::Authenticate { tx, .. } => {
+                let allowed = ALLOWED_RECIPIENTS.load(ctx.storage)?;
+
+                for msg in tx.msgs.iter() {
+                    if let Message::Transfer(transfer) = msg {
+                        ensure!(
+                            allowed.contains(&transfer.to),
+                            "recipient {} isn't allowed",
+                            transfer.to
+                        );
+                    }
+                }
+
+                Ok(Empty {}.to_json_value()?)
+            },
+            AuthenticatorQueryMsg::Backrun { .. } => Ok(Empty {}.to_json_value()?),
+        }
+    }
+}
+
+/// An authenticator that approves every transaction up front, but vetoes all
+/// of them once they have been executed.
+mod backrun_veto_authenticator {
+    use {
+        anyhow::bail,
+        dango_types::auth::AuthenticatorQueryMsg,
+        grug::{Empty, ImmutableCtx, Json, JsonSerExt, MutableCtx, Response, StdResult},
+    };
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query(_ctx: ImmutableCtx, msg: AuthenticatorQueryMsg) -> anyhow::Result<Json> {
+        match msg {
+            AuthenticatorQueryMsg::Authenticate { .. } => Ok(Empty {}.to_json_value()?),
+            AuthenticatorQueryMsg::Backrun { .. } => bail!("vetoed in backrun"),
+        }
+    }
+}
+
+/// An authenticator that rejects every transaction, e.g. because it's faulty.
+mod reject_all_authenticator {
+    use {
+        anyhow::bail,
+        dango_types::auth::AuthenticatorQueryMsg,
+        grug::{Empty, ImmutableCtx, Json, MutableCtx, Response, StdResult},
+    };
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query(_ctx: ImmutableCtx, _msg: AuthenticatorQueryMsg) -> anyhow::Result<Json> {
+        bail!("rejected");
+    }
+}
+
+#[test]
+fn authenticator_can_veto_transactions() {
+    let (mut suite, mut accounts, ..) = setup_test_naive();
+
+    let code = ContractBuilder::new(Box::new(allowlist_authenticator::instantiate))
+        .with_query(Box::new(allowlist_authenticator::query))
+        .build();
+
+    let authenticator = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            code,
+            &BTreeSet::from([accounts.user2.address()]),
+            "allowlist_authenticator",
+            Some("allowlist_authenticator"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // Only the account itself can register an authenticator.
+    suite
+        .execute(
+            &mut accounts.user2,
+            accounts.user1.address(),
+            &ExecuteMsg::RegisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the account itself can register authenticators");
+
+    // User1 registers the authenticator.
+    suite
+        .execute(
+            &mut accounts.user1,
+            accounts.user1.address(),
+            &ExecuteMsg::RegisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(accounts.user1.address(), QueryAuthenticatorsRequest {})
+        .should_succeed_and_equal(BTreeSet::from([authenticator]));
+
+    // Transferring to an allowed recipient works.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+        )
+        .should_succeed();
+
+    // Transferring to anyone else is vetoed by the authenticator.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user3.address(),
+            Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+        )
+        .should_fail_with_error(format!(
+            "recipient {} isn't allowed",
+            accounts.user3.address()
+        ));
+
+    // Deregister the authenticator. This is allowed, since the message is not
+    // a transfer.
+    suite
+        .execute(
+            &mut accounts.user1,
+            accounts.user1.address(),
+            &ExecuteMsg::DeregisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(accounts.user1.address(), QueryAuthenticatorsRequest {})
+        .should_succeed_and(|authenticators| authenticators.is_empty());
+
+    // Transfer to the previously disallowed recipient now works.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user3.address(),
+            Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+        )
+        .should_succeed();
+}
+
+#[test]
+fn deregistering_authenticator_is_backrun_by_it() {
+    let (mut suite, mut accounts, ..) = setup_test_naive();
+
+    let authenticator = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(backrun_veto_authenticator::instantiate))
+                .with_query(Box::new(backrun_veto_authenticator::query))
+                .build(),
+            &Empty {},
+            "backrun_veto_authenticator",
+            Some("backrun_veto_authenticator"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            accounts.user1.address(),
+            &ExecuteMsg::RegisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // A transaction that deregisters the authenticator while also doing
+    // something else is still backrun by it, which vetoes it.
+    suite
+        .send_messages(
+            &mut accounts.user1,
+            NonEmpty::new_unchecked(vec![
+                Message::execute(
+                    accounts.user1.address(),
+                    &ExecuteMsg::DeregisterAuthenticator { authenticator },
+                    Coins::new(),
+                )
+                .unwrap(),
+                Message::transfer(
+                    accounts.user2.address(),
+                    Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+                )
+                .unwrap(),
+            ]),
+        )
+        .should_fail_with_error("vetoed in backrun");
+
+    suite
+        .query_wasm_smart(accounts.user1.address(), QueryAuthenticatorsRequest {})
+        .should_succeed_and_equal(BTreeSet::from([authenticator]));
+}
+
+#[test]
+fn faulty_authenticator_can_be_deregistered() {
+    let (mut suite, mut accounts, ..) = setup_test_naive();
+
+    let authenticator = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(reject_all_authenticator::instantiate))
+                .with_query(Box::new(reject_all_authenticator::query))
+                .build(),
+            &Empty {},
+            "reject_all_authenticator",
+            Some("reject_all_authenticator"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            accounts.user1.address(),
+            &ExecuteMsg::RegisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Every transaction is now rejected by the authenticator.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+        )
+        .should_fail_with_error(format!(
+            "authenticator {authenticator} rejected the transaction"
+        ));
+
+    // Except for a transaction that only deregisters it.
+    suite
+        .execute(
+            &mut accounts.user1,
+            accounts.user1.address(),
+            &ExecuteMsg::DeregisterAuthenticator { authenticator },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(accounts.user1.address(), QueryAuthenticatorsRequest {})
+        .should_succeed_and(|authenticators| authenticators.is_empty());
+
+    // The account works again.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 123).unwrap(),
+        )
+        .should_succeed();
+}
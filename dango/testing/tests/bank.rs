@@ -0,0 +1,904 @@
+use {
+    dango_bank::HOOK_GAS_LIMIT,
+    dango_testing::setup_test_naive,
+    dango_types::{
+        account::single,
+        account_factory::{self, AccountParams},
+        bank::{
+            self, Allowance, QueryAllowanceRequest, QueryBalanceAtRequest, QueryHookRequest,
+            QueryNamespaceOwnersRequest, QueryNamespaceRequest, QueryNamespacesByOwnerRequest,
+            QueryOrphanedTransferRequest, QueryOrphanedTransfersByRecipientRequest,
+            QueryOrphanedTransfersBySenderRequest, TransferTagged,
+        },
+        constants::USDC_DENOM,
+    },
+    grug::{
+        Addr, Addressable, Coins, ContractEvent, Denom, Duration, Empty, JsonDeExt, Part,
+        QuerierExt, ResultExt, SearchEvent, Uint128,
+    },
+    grug_vm_rust::ContractBuilder,
+    std::{collections::BTreeSet, str::FromStr},
+};
+
+/// A hook that rejects transfers to a blocked address, and tags transfers
+/// above a certain amount.
+mod compliance_hook {
+    use {
+        anyhow::ensure,
+        dango_types::bank::{BeforeSendResponse, HookQueryMsg},
+        grug::{Addr, ImmutableCtx, Item, Json, JsonSerExt, MutableCtx, Response},
+    };
+
+    const BLOCKED: Item<Addr> = Item::new("blocked");
+
+    pub fn instantiate(ctx: MutableCtx, blocked: Addr) -> anyhow::Result<Response> {
+        BLOCKED.save(ctx.storage, &blocked)?;
+
+        Ok(Response::new())
+    }
+
+    pub fn query(ctx: ImmutableCtx, msg: HookQueryMsg) -> anyhow::Result<Json> {
+        match msg {
+            HookQueryMsg::BeforeSend { to, coins, .. } => {
+                ensure!(to != BLOCKED.load(ctx.storage)?, "recipient is blocked");
+
+                let tag = coins
+                    .into_iter()
+                    .any(|coin| coin.amount.into_inner() > 100)
+                    .then(|| "large".to_string());
+
+                Ok(BeforeSendResponse { tag }.to_json_value()?)
+            },
+        }
+    }
+}
+
+/// A hook that consumes gas until it runs out, by repeatedly querying its own
+/// contract info.
+mod gas_guzzling_hook {
+    use {
+        dango_types::bank::HookQueryMsg,
+        grug::{Empty, ImmutableCtx, Json, MutableCtx, QuerierExt, Response, StdResult},
+    };
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query(ctx: ImmutableCtx, _msg: HookQueryMsg) -> anyhow::Result<Json> {
+        loop {
+            ctx.querier.query_contract(ctx.contract)?;
+        }
+    }
+}
+
+#[test]
+fn namespace_hooks() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let namespace = Part::from_str("rwa").unwrap();
+    let denom = Denom::from_str("rwa/bond").unwrap();
+
+    // Chain owner grants the namespace to themself, and mints some tokens.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::GrantNamespace {
+                namespace: namespace.clone(),
+                owner: accounts.owner.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::Mint {
+                to: accounts.user1.address(),
+                denom: denom.clone(),
+                amount: 1_000.into(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let code = ContractBuilder::new(Box::new(compliance_hook::instantiate))
+        .with_query(Box::new(compliance_hook::query))
+        .build();
+
+    let hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            code,
+            &accounts.user3.address(),
+            "compliance_hook",
+            Some("compliance_hook"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // Only the namespace owner can set the hook.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::SetHook {
+                namespace: namespace.clone(),
+                hook: Some(hook),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("sender does not own the namespace `rwa`");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::SetHook {
+                namespace: namespace.clone(),
+                hook: Some(hook),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryHookRequest {
+            namespace: namespace.clone(),
+        })
+        .should_succeed_and_equal(Some(hook));
+
+    // A small transfer goes through without being tagged.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(denom.clone(), 50).unwrap(),
+        )
+        .should_succeed_and(|outcome| {
+            outcome
+                .events
+                .clone()
+                .search_event::<ContractEvent>()
+                .with_predicate(|e| e.ty == "transfer_tagged")
+                .take()
+                .all()
+                .is_empty()
+        });
+
+    // A large transfer is tagged.
+    let tagged = suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(denom.clone(), 200).unwrap(),
+        )
+        .should_succeed()
+        .events
+        .search_event::<ContractEvent>()
+        .with_predicate(|e| e.ty == "transfer_tagged")
+        .take()
+        .one()
+        .event
+        .data
+        .deserialize_json::<TransferTagged>()
+        .unwrap();
+
+    assert_eq!(tagged.namespace, namespace);
+    assert_eq!(tagged.to, accounts.user2.address());
+    assert_eq!(tagged.tag, "large");
+
+    // A transfer to the blocked address is vetoed.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user3.address(),
+            Coins::one(denom.clone(), 50).unwrap(),
+        )
+        .should_fail_with_error("recipient is blocked");
+
+    // Transfers of other denoms aren't affected.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user3.address(),
+            Coins::one(USDC_DENOM.clone(), 50).unwrap(),
+        )
+        .should_succeed();
+
+    // Unset the hook. The transfer now goes through.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::SetHook {
+                namespace,
+                hook: None,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user3.address(),
+            Coins::one(denom, 50).unwrap(),
+        )
+        .should_succeed();
+}
+
+#[test]
+fn namespace_hook_out_of_gas() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let namespace = Part::from_str("rwa").unwrap();
+    let denom = Denom::from_str("rwa/bond").unwrap();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::GrantNamespace {
+                namespace: namespace.clone(),
+                owner: accounts.owner.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::Mint {
+                to: accounts.user1.address(),
+                denom: denom.clone(),
+                amount: 1_000.into(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(gas_guzzling_hook::instantiate))
+                .with_query(Box::new(gas_guzzling_hook::query))
+                .build(),
+            &Empty {},
+            "gas_guzzling_hook",
+            Some("gas_guzzling_hook"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::SetHook {
+                namespace,
+                hook: Some(hook),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The hook runs out of its own gas limit, which vetoes the transfer. The
+    // transaction doesn't run out of gas, despite having plenty more.
+    let gas_limit = HOOK_GAS_LIMIT * 10;
+
+    let outcome = suite.transfer_with_gas(
+        &mut accounts.user1,
+        gas_limit,
+        accounts.user2.address(),
+        Coins::one(denom.clone(), 50).unwrap(),
+    );
+
+    assert!(outcome.gas_used < gas_limit);
+
+    outcome.should_fail_with_error(format!("out of gas! limit: {HOOK_GAS_LIMIT}"));
+
+    // Transfers of denoms outside the namespace aren't affected.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(USDC_DENOM.clone(), 50).unwrap(),
+        )
+        .should_succeed();
+}
+
+#[test]
+fn namespace_ownership() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let namespace = Part::from_str("rwa").unwrap();
+
+    // Chain owner grants the namespace to two owners.
+    for owner in [accounts.user1.address(), accounts.user2.address()] {
+        suite
+            .execute(
+                &mut accounts.owner,
+                contracts.bank,
+                &bank::ExecuteMsg::GrantNamespace {
+                    namespace: namespace.clone(),
+                    owner,
+                },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    // Can't grant to the same owner twice.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::GrantNamespace {
+                namespace: namespace.clone(),
+                owner: accounts.user1.address(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("already granted to");
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespaceOwnersRequest {
+            namespace: namespace.clone(),
+        })
+        .should_succeed_and_equal(BTreeSet::from([
+            accounts.user1.address(),
+            accounts.user2.address(),
+        ]));
+
+    // The single-owner query doesn't work for a namespace with two owners.
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespaceRequest {
+            namespace: namespace.clone(),
+        })
+        .should_fail_with_error("namespace `rwa` has more than one owner");
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespacesByOwnerRequest {
+            owner: accounts.user1.address(),
+        })
+        .should_succeed_and_equal(BTreeSet::from([namespace.clone()]));
+
+    // User1 transfers its ownership to user3.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferNamespace {
+                namespace: namespace.clone(),
+                to: accounts.user3.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // User1 can no longer transfer it.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferNamespace {
+                namespace: namespace.clone(),
+                to: accounts.user4.address(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("sender does not own the namespace `rwa`");
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespacesByOwnerRequest {
+            owner: accounts.user1.address(),
+        })
+        .should_succeed_and(|namespaces| namespaces.is_empty());
+
+    // User2 renounces.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::RenounceNamespace {
+                namespace: namespace.clone(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // User3 is now the only owner.
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespaceRequest {
+            namespace: namespace.clone(),
+        })
+        .should_succeed_and_equal(accounts.user3.address());
+
+    // Only the chain owner can revoke.
+    suite
+        .execute(
+            &mut accounts.user3,
+            contracts.bank,
+            &bank::ExecuteMsg::RevokeNamespace {
+                namespace: namespace.clone(),
+                owner: accounts.user3.address(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("you don't have the right");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::RevokeNamespace {
+                namespace: namespace.clone(),
+                owner: accounts.user3.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespaceOwnersRequest {
+            namespace: namespace.clone(),
+        })
+        .should_succeed_and(|owners| owners.is_empty());
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryNamespaceRequest { namespace })
+        .should_fail_with_error("namespace `rwa` has no owner");
+}
+
+#[test]
+fn orphaned_transfers_expire() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let balance_before = suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .unwrap();
+
+    // User1 sends funds to two addresses that don't exist.
+    let recipient1 = Addr::mock(123);
+    let recipient2 = Addr::mock(234);
+
+    for recipient in [recipient1, recipient2] {
+        suite
+            .transfer(
+                &mut accounts.user1,
+                recipient,
+                Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+            )
+            .should_succeed();
+    }
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransfersBySenderRequest {
+            sender: accounts.user1.address(),
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers| {
+            transfers.len() == 2
+                && transfers
+                    .values()
+                    .all(|transfer| transfer.coins == Coins::one(USDC_DENOM.clone(), 100).unwrap())
+        });
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransfersByRecipientRequest {
+            recipient: recipient1,
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers| transfers.keys().eq([accounts.user1.address()].iter()));
+
+    // The sender recovers one of the transfers manually.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::RecoverTransfer {
+                sender: accounts.user1.address(),
+                recipient: recipient1,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransfersByRecipientRequest {
+            recipient: recipient1,
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers| transfers.is_empty());
+
+    // Before the expiry, the other transfer remains orphaned.
+    suite.make_empty_block();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransferRequest {
+            sender: accounts.user1.address(),
+            recipient: recipient2,
+        })
+        .should_succeed();
+
+    // Once expired, the cronjob returns the funds to the sender.
+    suite.block_time = Duration::from_days(31);
+    suite.make_empty_block();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransfersBySenderRequest {
+            sender: accounts.user1.address(),
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers| transfers.is_empty());
+
+    // Fee rate is zero in tests, so the sender gets back exactly what it sent.
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(balance_before);
+
+    suite
+        .query_balance(&recipient2, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+}
+
+#[test]
+fn allowances() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    // User1 allows user2 to spend up to 100 USDC, for one day.
+    let expires_at = suite.block.timestamp + Duration::from_days(1);
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::Approve {
+                spender: accounts.user2.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(100),
+                expires_at: Some(expires_at),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // User3 has no allowance.
+    suite
+        .execute(
+            &mut accounts.user3,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: accounts.user3.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(10),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("no allowance of");
+
+    // User2 can't exceed the allowance.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: accounts.user3.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(101),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("insufficient allowance");
+
+    let user3_balance_before = suite
+        .query_balance(&accounts.user3, USDC_DENOM.clone())
+        .unwrap();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: accounts.user3.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(60),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user3, USDC_DENOM.clone())
+        .should_succeed_and_equal(user3_balance_before + Uint128::new(60));
+
+    // The allowance is reduced.
+    suite
+        .query_wasm_smart(contracts.bank, QueryAllowanceRequest {
+            owner: accounts.user1.address(),
+            spender: accounts.user2.address(),
+            denom: USDC_DENOM.clone(),
+        })
+        .should_succeed_and_equal(Some(Allowance {
+            amount: Uint128::new(40),
+            expires_at: Some(expires_at),
+        }));
+
+    // After the expiry, the allowance can't be used.
+    suite.block_time = Duration::from_days(2);
+    suite.make_empty_block();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: accounts.user3.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(10),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("has expired");
+
+    // User1 revokes the allowance.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::RevokeApproval {
+                spender: accounts.user2.address(),
+                denom: USDC_DENOM.clone(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryAllowanceRequest {
+            owner: accounts.user1.address(),
+            spender: accounts.user2.address(),
+            denom: USDC_DENOM.clone(),
+        })
+        .should_succeed_and_equal(None);
+}
+
+#[test]
+fn transfer_from_recipients() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::Approve {
+                spender: accounts.user2.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(100),
+                expires_at: None,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Transferring to a non-existent recipient creates an orphaned transfer,
+    // recoverable by the owner rather than the spender.
+    let recipient = Addr::mock(123);
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: recipient,
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(10),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryOrphanedTransferRequest {
+            sender: accounts.user1.address(),
+            recipient,
+        })
+        .should_succeed_and(|transfer| {
+            transfer.coins == Coins::one(USDC_DENOM.clone(), 10).unwrap()
+        });
+
+    suite
+        .query_balance(&recipient, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    // Create an account and delete it.
+    let mut deleted = accounts
+        .owner
+        .register_new_account(
+            &mut suite,
+            contracts.account_factory,
+            AccountParams::Spot(single::Params::new(accounts.owner.username.clone())),
+            Coins::new(),
+        )
+        .unwrap();
+
+    suite
+        .execute(
+            &mut deleted,
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::DeleteAccount {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The deleted account's `receive` rejects the funds, same as a regular
+    // transfer.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: accounts.user1.address(),
+                to: deleted.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(10),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error(format!(
+            "account {} has been deleted and can't receive funds",
+            deleted.address()
+        ));
+
+    // The failed transfer didn't consume the allowance.
+    suite
+        .query_wasm_smart(contracts.bank, QueryAllowanceRequest {
+            owner: accounts.user1.address(),
+            spender: accounts.user2.address(),
+            denom: USDC_DENOM.clone(),
+        })
+        .should_succeed_and(|allowance| {
+            allowance.as_ref().map(|a| a.amount) == Some(Uint128::new(90))
+        });
+}
+
+#[test]
+fn balance_snapshots() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let denom = Denom::from_str("gov/vote").unwrap();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::GrantNamespace {
+                namespace: Part::from_str("gov").unwrap(),
+                owner: accounts.owner.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::Mint {
+                to: accounts.user1.address(),
+                denom: denom.clone(),
+                amount: Uint128::new(100),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Only the namespace owner can enable snapshots.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.bank,
+            &bank::ExecuteMsg::EnableSnapshots {
+                denom: denom.clone(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("sender does not own the namespace `gov`");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.bank,
+            &bank::ExecuteMsg::EnableSnapshots {
+                denom: denom.clone(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let enabled_at = suite.block.height;
+
+    suite.make_empty_block();
+
+    // User1 transfers to user2 twice, at different heights.
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(denom.clone(), 30).unwrap(),
+        )
+        .should_succeed();
+
+    let first_transfer_at = suite.block.height;
+
+    suite.make_empty_block();
+
+    suite
+        .transfer(
+            &mut accounts.user1,
+            accounts.user2.address(),
+            Coins::one(denom.clone(), 20).unwrap(),
+        )
+        .should_succeed();
+
+    let second_transfer_at = suite.block.height;
+
+    suite.make_empty_block();
+
+    for (address, height, balance) in [
+        (accounts.user1.address(), enabled_at, 100),
+        (accounts.user1.address(), first_transfer_at - 1, 100),
+        (accounts.user1.address(), first_transfer_at, 70),
+        (accounts.user1.address(), second_transfer_at - 1, 70),
+        (accounts.user1.address(), second_transfer_at, 50),
+        (accounts.user1.address(), suite.block.height, 50),
+        (accounts.user2.address(), enabled_at, 0),
+        (accounts.user2.address(), first_transfer_at, 30),
+        (accounts.user2.address(), second_transfer_at, 50),
+        (accounts.user3.address(), second_transfer_at, 0),
+    ] {
+        suite
+            .query_wasm_smart(contracts.bank, QueryBalanceAtRequest {
+                address,
+                denom: denom.clone(),
+                height,
+            })
+            .should_succeed_and_equal(Uint128::new(balance));
+    }
+
+    // Heights before the enablement or in the future can't be queried.
+    suite
+        .query_wasm_smart(contracts.bank, QueryBalanceAtRequest {
+            address: accounts.user1.address(),
+            denom: denom.clone(),
+            height: enabled_at - 1,
+        })
+        .should_fail_with_error("were enabled at height");
+
+    suite
+        .query_wasm_smart(contracts.bank, QueryBalanceAtRequest {
+            address: accounts.user1.address(),
+            denom,
+            height: suite.block.height + 1,
+        })
+        .should_fail_with_error("is greater than the current height");
+}
@@ -0,0 +1,208 @@
+use {
+    dango_testing::{setup_test, MOCK_LOCAL_DOMAIN, MOCK_REMOTE_DOMAIN},
+    dango_types::{
+        constants::{DANGO_DENOM, ETH_DENOM},
+        warp::{self, Route},
+    },
+    grug::{
+        btree_map, coins, Addressable, Coins, HexBinary, NumberConst, QuerierExt, ResultExt,
+        Uint128,
+    },
+    hyperlane_types::{
+        addr32,
+        hooks::{fee, HookQuery, HookQueryResponse},
+        mailbox::{Message, MAILBOX_VERSION},
+        Addr32,
+    },
+};
+
+const MOCK_ROUTE: Route = Route {
+    address: addr32!("0000000000000000000000000000000000000000000000000000000000000000"),
+    fee: Uint128::new(25),
+};
+
+const MOCK_RECIPIENT: Addr32 =
+    addr32!("0000000000000000000000000000000000000000000000000000000000000001");
+
+#[test]
+fn fee_hook() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    // Set the routes for two tokens.
+    for denom in [DANGO_DENOM.clone(), ETH_DENOM.clone()] {
+        suite
+            .execute(
+                &mut accounts.owner,
+                contracts.warp,
+                &warp::ExecuteMsg::SetRoute {
+                    denom,
+                    destination_domain: MOCK_REMOTE_DOMAIN,
+                    route: MOCK_ROUTE,
+                },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    // Only the chain owner can set fees.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::SetFee {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                fee: Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_fee`");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::SetFee {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                fee: Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.hyperlane.fee, fee::QueryFeesRequest {
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and_equal(btree_map! {
+            MOCK_REMOTE_DOMAIN => Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+        });
+
+    // The quote depends on the message's destination domain.
+    for (destination_domain, quote) in [
+        (
+            MOCK_REMOTE_DOMAIN,
+            Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+        ),
+        (MOCK_REMOTE_DOMAIN + 1, Coins::new()),
+    ] {
+        let raw_message = Message {
+            version: MAILBOX_VERSION,
+            nonce: 0,
+            origin_domain: MOCK_LOCAL_DOMAIN,
+            sender: contracts.warp.into(),
+            destination_domain,
+            recipient: MOCK_ROUTE.address,
+            body: HexBinary::default(),
+        }
+        .encode();
+
+        suite
+            .query_wasm_smart(
+                contracts.hyperlane.fee,
+                fee::QueryHookRequest(HookQuery::QuoteDispatch {
+                    raw_message,
+                    raw_metadata: HexBinary::default(),
+                }),
+            )
+            .should_succeed_and_equal(HookQueryResponse::QuoteDispatch(quote));
+    }
+
+    // Sending a token without attaching the hook's fee fails, even though the
+    // withdrawal fee would cover it.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
+            },
+            Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error("insufficient hook fee");
+
+    // Sending a token with the hook's fee attached succeeds.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: Some(Coins::one(DANGO_DENOM.clone(), 25).unwrap()),
+            },
+            coins! {
+                DANGO_DENOM.clone() => 25,
+                ETH_DENOM.clone() => 100,
+            },
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&contracts.hyperlane.fee, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(25));
+
+    // Only the chain owner can withdraw the collected fees.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::Withdraw {
+                recipient: accounts.user1.address(),
+                coins: Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `withdraw`");
+
+    let balance_before = suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .unwrap();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::Withdraw {
+                recipient: accounts.user2.address(),
+                coins: Coins::one(DANGO_DENOM.clone(), 25).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&contracts.hyperlane.fee, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .should_succeed_and_equal(balance_before + Uint128::new(25));
+
+    // Removing the fee makes dispatching free again.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::SetFee {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                fee: Coins::new(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.hyperlane.fee, fee::QueryFeeRequest {
+            destination_domain: MOCK_REMOTE_DOMAIN,
+        })
+        .should_succeed_and(|fee| fee.is_empty());
+}
@@ -0,0 +1,483 @@
+use {
+    dango_testing::{setup_test, MOCK_LOCAL_DOMAIN, MOCK_REMOTE_DOMAIN},
+    dango_types::{
+        constants::{DANGO_DENOM, USDC_DENOM},
+        warp::{self, Route, TokenMessage},
+    },
+    grug::{
+        btree_map, coins, Addr, Addressable, Coins, HashExt, HexBinary, NumberConst, QuerierExt,
+        ResultExt, Udec128, Uint128,
+    },
+    grug_vm_rust::ContractBuilder,
+    hyperlane_types::{
+        addr32,
+        hooks::{
+            aggregation, fee,
+            igp::{self, GasOracle},
+            merkle, pausable, HookQuery, HookQueryResponse, QueryHookRequest, StandardHookMetadata,
+            STANDARD_HOOK_METADATA_VARIANT,
+        },
+        mailbox::{self, Message, MAILBOX_VERSION},
+        Addr32, IncrementalMerkleTree,
+    },
+};
+
+const MOCK_RECIPIENT: Addr32 =
+    addr32!("0000000000000000000000000000000000000000000000000000000000000001");
+
+const MOCK_ROUTE: Route = Route {
+    address: addr32!("0000000000000000000000000000000000000000000000000000000000000002"),
+    fee: Uint128::new(25),
+};
+
+#[test]
+fn aggregation_and_pausable_hooks() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    let aggregation_hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(hyperlane_aggregation_hook::instantiate))
+                .with_execute(Box::new(hyperlane_aggregation_hook::execute))
+                .with_query(Box::new(hyperlane_aggregation_hook::query))
+                .build(),
+            &aggregation::InstantiateMsg {
+                mailbox: contracts.hyperlane.mailbox,
+                hooks: vec![],
+            },
+            "hyperlane/hook/aggregation",
+            Some("hyperlane/hook/aggregation"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // The child hooks are called by the aggregation hook, not the mailbox.
+    let igp_hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(hyperlane_igp::instantiate))
+                .with_execute(Box::new(hyperlane_igp::execute))
+                .with_query(Box::new(hyperlane_igp::query))
+                .build(),
+            &igp::InstantiateMsg {
+                mailbox: aggregation_hook,
+                config: igp::Config {
+                    gas_denom: DANGO_DENOM.clone(),
+                    default_gas_limit: Uint128::new(100),
+                },
+                gas_oracles: btree_map! {
+                    MOCK_REMOTE_DOMAIN => GasOracle {
+                        gas_price: Uint128::new(1),
+                        token_exchange_rate: Udec128::new(1),
+                        gas_overhead: Uint128::ZERO,
+                    },
+                },
+            },
+            "hyperlane/hook/igp",
+            Some("hyperlane/hook/igp"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    let pausable_hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(hyperlane_pausable::instantiate))
+                .with_execute(Box::new(hyperlane_pausable::execute))
+                .with_query(Box::new(hyperlane_pausable::query))
+                .build(),
+            &pausable::InstantiateMsg {
+                mailbox: aggregation_hook,
+                paused: false,
+            },
+            "hyperlane/hook/pausable",
+            Some("hyperlane/hook/pausable"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // Only the chain owner can set the hooks.
+    suite
+        .execute(
+            &mut accounts.user1,
+            aggregation_hook,
+            &aggregation::ExecuteMsg::SetHooks {
+                hooks: vec![igp_hook, pausable_hook],
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_hooks`");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            aggregation_hook,
+            &aggregation::ExecuteMsg::SetHooks {
+                hooks: vec![igp_hook, pausable_hook],
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(aggregation_hook, aggregation::QueryHooksRequest {})
+        .should_succeed_and_equal(vec![igp_hook, pausable_hook]);
+
+    // The quote is the sum of the hooks' quotes.
+    suite
+        .query_wasm_smart(
+            aggregation_hook,
+            QueryHookRequest(HookQuery::QuoteDispatch {
+                raw_message: Message {
+                    version: MAILBOX_VERSION,
+                    nonce: 0,
+                    origin_domain: MOCK_LOCAL_DOMAIN,
+                    sender: accounts.user1.address().into(),
+                    destination_domain: MOCK_REMOTE_DOMAIN,
+                    recipient: MOCK_RECIPIENT,
+                    body: HexBinary::default(),
+                }
+                .encode(),
+                raw_metadata: HexBinary::default(),
+            }),
+        )
+        .should_succeed_and_equal(HookQueryResponse::QuoteDispatch(
+            Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
+        ));
+
+    let dispatch_msg = mailbox::ExecuteMsg::Dispatch {
+        destination_domain: MOCK_REMOTE_DOMAIN,
+        recipient: MOCK_RECIPIENT,
+        body: HexBinary::default(),
+        metadata: Some(
+            StandardHookMetadata {
+                variant: STANDARD_HOOK_METADATA_VARIANT,
+                value: Uint128::ZERO,
+                gas_limit: Uint128::new(100),
+                refund_address: accounts.user2.address(),
+                custom: HexBinary::default(),
+            }
+            .encode(),
+        ),
+        hook: Some(aggregation_hook),
+    };
+
+    // Dispatching fails if the funds don't cover the quotes.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 50).unwrap(),
+        )
+        .should_fail_with_error("insufficient fee for hook");
+
+    // Each hook is paid its quote, and the excess is refunded.
+    let user2_balance_before = suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .unwrap();
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 150).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&igp_hook, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(100));
+
+    suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .should_succeed_and_equal(user2_balance_before + Uint128::new(50));
+
+    // Only the chain owner can pause the hook.
+    suite
+        .execute(
+            &mut accounts.user1,
+            pausable_hook,
+            &pausable::ExecuteMsg::SetPaused { paused: true },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_paused`");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            pausable_hook,
+            &pausable::ExecuteMsg::SetPaused { paused: true },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(pausable_hook, pausable::QueryPausedRequest {})
+        .should_succeed_and_equal(true);
+
+    // While paused, dispatching fails.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error("hook is paused");
+
+    // Unpause, and dispatching works again.
+    suite
+        .execute(
+            &mut accounts.owner,
+            pausable_hook,
+            &pausable::ExecuteMsg::SetPaused { paused: false },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&igp_hook, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(200));
+}
+
+#[test]
+fn aggregation_hook_as_required_hook() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    // The mailbox's hooks can't be changed once it's instantiated, so deploy a
+    // new mailbox, and a Warp contract using it. The aggregation hook needs the
+    // mailbox's address, and vice versa, so predict the mailbox's address.
+    let [mailbox_code_hash, merkle_code_hash, fee_code_hash, warp_code_hash] = [
+        contracts.hyperlane.mailbox,
+        contracts.hyperlane.merkle,
+        contracts.hyperlane.fee,
+        contracts.warp,
+    ]
+    .map(|contract| suite.query_contract(contract).unwrap().code_hash);
+
+    let mailbox = Addr::derive(
+        accounts.owner.address(),
+        mailbox_code_hash,
+        b"hyperlane/mailbox/aggregated",
+    );
+
+    let aggregation_hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(hyperlane_aggregation_hook::instantiate))
+                .with_execute(Box::new(hyperlane_aggregation_hook::execute))
+                .with_query(Box::new(hyperlane_aggregation_hook::query))
+                .build(),
+            &aggregation::InstantiateMsg {
+                mailbox,
+                hooks: vec![],
+            },
+            "hyperlane/hook/aggregation",
+            Some("hyperlane/hook/aggregation"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // Redeploy the Merkle tree and fee hooks with the aggregation hook as their
+    // mailbox.
+    let merkle_hook = suite
+        .instantiate(
+            &mut accounts.owner,
+            merkle_code_hash,
+            &merkle::InstantiateMsg {
+                mailbox: aggregation_hook,
+            },
+            "hyperlane/hook/merkle/aggregated",
+            Some("hyperlane/hook/merkle/aggregated"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    let fee_hook = suite
+        .instantiate(
+            &mut accounts.owner,
+            fee_code_hash,
+            &fee::InstantiateMsg {
+                mailbox: aggregation_hook,
+                fees: btree_map! {
+                    MOCK_REMOTE_DOMAIN => Coins::one(USDC_DENOM.clone(), 10).unwrap(),
+                },
+            },
+            "hyperlane/hook/fee/aggregated",
+            Some("hyperlane/hook/fee/aggregated"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            aggregation_hook,
+            &aggregation::ExecuteMsg::SetHooks {
+                hooks: vec![merkle_hook, fee_hook],
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The default hook doesn't charge anything.
+    let default_hook = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            ContractBuilder::new(Box::new(hyperlane_pausable::instantiate))
+                .with_execute(Box::new(hyperlane_pausable::execute))
+                .with_query(Box::new(hyperlane_pausable::query))
+                .build(),
+            &pausable::InstantiateMsg {
+                mailbox,
+                paused: false,
+            },
+            "hyperlane/hook/pausable",
+            Some("hyperlane/hook/pausable"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    let mailbox_address = suite
+        .instantiate(
+            &mut accounts.owner,
+            mailbox_code_hash,
+            &mailbox::InstantiateMsg {
+                config: mailbox::Config {
+                    local_domain: MOCK_LOCAL_DOMAIN,
+                    default_ism: contracts.hyperlane.ism,
+                    default_hook,
+                    required_hook: aggregation_hook,
+                },
+            },
+            "hyperlane/mailbox/aggregated",
+            Some("hyperlane/mailbox/aggregated"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    assert_eq!(mailbox_address, mailbox);
+
+    let warp = suite
+        .instantiate(
+            &mut accounts.owner,
+            warp_code_hash,
+            &warp::InstantiateMsg { mailbox },
+            "dango/warp/aggregated",
+            Some("dango/warp/aggregated"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            warp,
+            &warp::ExecuteMsg::SetRoute {
+                denom: DANGO_DENOM.clone(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                route: MOCK_ROUTE,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let body = TokenMessage {
+        recipient: MOCK_RECIPIENT,
+        amount: Uint128::new(100) - MOCK_ROUTE.fee,
+        metadata: HexBinary::default(),
+    }
+    .encode();
+
+    // The mailbox quotes the sum of the child hooks' quotes.
+    suite
+        .query_wasm_smart(mailbox, mailbox::QueryQuoteDispatchRequest {
+            sender: warp,
+            destination_domain: MOCK_REMOTE_DOMAIN,
+            recipient: MOCK_ROUTE.address,
+            body: body.clone(),
+            metadata: None,
+            hook: None,
+        })
+        .should_succeed_and_equal(Coins::one(USDC_DENOM.clone(), 10).unwrap());
+
+    // Send with more hook fee than needed. The excess is refunded by Warp.
+    suite
+        .execute(
+            &mut accounts.user1,
+            warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: Some(Coins::one(USDC_DENOM.clone(), 15).unwrap()),
+            },
+            coins! {
+                DANGO_DENOM.clone() => 100,
+                USDC_DENOM.clone() => 15,
+            },
+        )
+        .should_succeed();
+
+    // The Merkle tree hook accepted the call from the aggregation hook, and
+    // inserted the message.
+    suite
+        .query_wasm_smart(merkle_hook, merkle::QueryTreeRequest {})
+        .should_succeed_and_equal({
+            let msg = Message {
+                version: MAILBOX_VERSION,
+                nonce: 0,
+                origin_domain: MOCK_LOCAL_DOMAIN,
+                sender: warp.into(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_ROUTE.address,
+                body,
+            };
+
+            let mut tree = IncrementalMerkleTree::default();
+            tree.insert(msg.encode().keccak256()).unwrap();
+            tree
+        });
+
+    // The fee hook was paid exactly its fee, and none of it was left behind.
+    suite
+        .query_balance(&fee_hook, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(10));
+
+    for contract in [warp, mailbox, aggregation_hook, merkle_hook, default_hook] {
+        suite
+            .query_balance(&contract, USDC_DENOM.clone())
+            .should_succeed_and_equal(Uint128::ZERO);
+    }
+}
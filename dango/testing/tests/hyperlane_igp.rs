@@ -0,0 +1,254 @@
+use {
+    dango_testing::{setup_test, MOCK_LOCAL_DOMAIN, MOCK_REMOTE_DOMAIN},
+    dango_types::constants::DANGO_DENOM,
+    grug::{
+        btree_map, Addressable, Coin, Coins, ContractEvent, HexBinary, JsonDeExt, QuerierExt,
+        ResultExt, SearchEvent, Udec128, Uint128,
+    },
+    grug_vm_rust::ContractBuilder,
+    hyperlane_types::{
+        addr32,
+        hooks::{
+            igp::{self, GasOracle, GasPayment},
+            HookQuery, HookQueryResponse, StandardHookMetadata, STANDARD_HOOK_METADATA_VARIANT,
+        },
+        mailbox::{self, DispatchId, Message, MAILBOX_VERSION},
+        Addr32,
+    },
+};
+
+const MOCK_RECIPIENT: Addr32 =
+    addr32!("0000000000000000000000000000000000000000000000000000000000000001");
+
+#[test]
+fn igp_hook() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    let code = ContractBuilder::new(Box::new(hyperlane_igp::instantiate))
+        .with_execute(Box::new(hyperlane_igp::execute))
+        .with_query(Box::new(hyperlane_igp::query))
+        .build();
+
+    // Deliveries to the remote domain cost 3 units of gas each, plus an overhead
+    // of 20 units of gas, and each unit is worth 1.5 of the gas token.
+    let igp = suite
+        .upload_and_instantiate(
+            &mut accounts.owner,
+            code,
+            &igp::InstantiateMsg {
+                mailbox: contracts.hyperlane.mailbox,
+                config: igp::Config {
+                    gas_denom: DANGO_DENOM.clone(),
+                    default_gas_limit: Uint128::new(100),
+                },
+                gas_oracles: btree_map! {
+                    MOCK_REMOTE_DOMAIN => GasOracle {
+                        gas_price: Uint128::new(3),
+                        token_exchange_rate: Udec128::new_percent(150),
+                        gas_overhead: Uint128::new(20),
+                    },
+                },
+            },
+            "hyperlane/hook/igp",
+            Some("hyperlane/hook/igp"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .query_wasm_smart(igp, igp::QueryQuoteGasPaymentRequest {
+            destination_domain: MOCK_REMOTE_DOMAIN,
+            gas_limit: Uint128::new(100),
+        })
+        .should_succeed_and_equal(Coin::new(DANGO_DENOM.clone(), 540).unwrap());
+
+    // Without metadata, the hook quotes for the default gas limit.
+    let raw_message = |destination_domain| {
+        Message {
+            version: MAILBOX_VERSION,
+            nonce: 0,
+            origin_domain: MOCK_LOCAL_DOMAIN,
+            sender: accounts.user1.address().into(),
+            destination_domain,
+            recipient: MOCK_RECIPIENT,
+            body: HexBinary::default(),
+        }
+        .encode()
+    };
+
+    suite
+        .query_wasm_smart(
+            igp,
+            igp::QueryHookRequest(HookQuery::QuoteDispatch {
+                raw_message: raw_message(MOCK_REMOTE_DOMAIN),
+                raw_metadata: HexBinary::default(),
+            }),
+        )
+        .should_succeed_and_equal(HookQueryResponse::QuoteDispatch(
+            Coins::one(DANGO_DENOM.clone(), 540).unwrap(),
+        ));
+
+    // Domains without a gas oracle can't be quoted.
+    suite
+        .query_wasm_smart(
+            igp,
+            igp::QueryHookRequest(HookQuery::QuoteDispatch {
+                raw_message: raw_message(MOCK_REMOTE_DOMAIN + 1),
+                raw_metadata: HexBinary::default(),
+            }),
+        )
+        .should_fail_with_error("no gas oracle for domain");
+
+    // Only the chain owner can set gas oracles.
+    suite
+        .execute(
+            &mut accounts.user1,
+            igp,
+            &igp::ExecuteMsg::SetGasOracle {
+                destination_domain: MOCK_REMOTE_DOMAIN + 1,
+                gas_oracle: GasOracle {
+                    gas_price: Uint128::new(1),
+                    token_exchange_rate: Udec128::new(1),
+                    gas_overhead: Uint128::ZERO,
+                },
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_gas_oracle`");
+
+    // Dispatch a message with a gas limit of 200, which costs 990, and ask for
+    // the excess to be refunded to user2.
+    let metadata = StandardHookMetadata {
+        variant: STANDARD_HOOK_METADATA_VARIANT,
+        value: Uint128::ZERO,
+        gas_limit: Uint128::new(200),
+        refund_address: accounts.user2.address(),
+        custom: HexBinary::default(),
+    }
+    .encode();
+
+    let dispatch_msg = mailbox::ExecuteMsg::Dispatch {
+        destination_domain: MOCK_REMOTE_DOMAIN,
+        recipient: MOCK_RECIPIENT,
+        body: HexBinary::default(),
+        metadata: Some(metadata),
+        hook: Some(igp),
+    };
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 800).unwrap(),
+        )
+        .should_fail_with_error("insufficient gas payment!");
+
+    let user2_balance_before = suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .unwrap();
+
+    let events = suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &dispatch_msg,
+            Coins::one(DANGO_DENOM.clone(), 1000).unwrap(),
+        )
+        .should_succeed()
+        .events;
+
+    let message_id = events
+        .clone()
+        .search_event::<ContractEvent>()
+        .with_predicate(|e| e.ty == "mailbox_dispatch_id")
+        .take()
+        .one()
+        .event
+        .data
+        .deserialize_json::<DispatchId>()
+        .unwrap()
+        .message_id;
+
+    events
+        .search_event::<ContractEvent>()
+        .with_predicate(|e| e.ty == "gas_payment")
+        .take()
+        .one()
+        .event
+        .data
+        .deserialize_json::<GasPayment>()
+        .should_succeed_and_equal(GasPayment {
+            message_id,
+            destination_domain: MOCK_REMOTE_DOMAIN,
+            gas_limit: Uint128::new(200),
+            payment: Uint128::new(990),
+        });
+
+    suite
+        .query_balance(&igp, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(990));
+
+    suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .should_succeed_and_equal(user2_balance_before + Uint128::new(10));
+
+    // Metadata that isn't standard hook metadata doesn't fail the dispatch. The
+    // hook quotes for the default gas limit, and keeps the excess, since there
+    // is no refund address.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &mailbox::ExecuteMsg::Dispatch {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                body: HexBinary::default(),
+                metadata: Some(HexBinary::from_inner(b"foo".to_vec())),
+                hook: Some(igp),
+            },
+            Coins::one(DANGO_DENOM.clone(), 600).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&igp, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(1590));
+
+    // Only the chain owner can claim the gas payments.
+    suite
+        .execute(
+            &mut accounts.user1,
+            igp,
+            &igp::ExecuteMsg::Claim {
+                recipient: accounts.user1.address(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `claim`");
+
+    let user3_balance_before = suite
+        .query_balance(&accounts.user3, DANGO_DENOM.clone())
+        .unwrap();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            igp,
+            &igp::ExecuteMsg::Claim {
+                recipient: accounts.user3.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&igp, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    suite
+        .query_balance(&accounts.user3, DANGO_DENOM.clone())
+        .should_succeed_and_equal(user3_balance_before + Uint128::new(1590));
+}
@@ -0,0 +1,305 @@
+use {
+    dango_testing::{setup_test, HyperlaneTestSuite, MOCK_LOCAL_DOMAIN, MOCK_REMOTE_DOMAIN},
+    dango_types::{
+        constants::SOL_DENOM,
+        warp::{self, Route},
+    },
+    grug::{btree_map, Addressable, Coin, Coins, Empty, HexBinary, QuerierExt, ResultExt, Uint128},
+    grug_vm_rust::ContractBuilder,
+    hyperlane_types::{
+        addr32,
+        isms::{
+            aggregation::{self, IsmSet},
+            routing, IsmQuery, IsmQueryResponse, QueryIsmRequest,
+        },
+        mailbox::{Domain, Message, MAILBOX_VERSION},
+        recipients::{QueryRecipientRequest, RecipientQuery, RecipientQueryResponse},
+    },
+};
+
+const MOCK_ROUTE: Route = Route {
+    address: addr32!("0000000000000000000000000000000000000000000000000000000000000000"),
+    fee: Uint128::new(25),
+};
+
+/// An ISM that accepts a message if and only if the metadata is `ok`.
+mod mock_ism {
+    use {
+        anyhow::ensure,
+        grug::{Empty, ImmutableCtx, Json, JsonSerExt, MutableCtx, Response, StdResult},
+        hyperlane_types::isms::{IsmQuery, IsmQueryResponse, QueryMsg},
+    };
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query(_ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
+        let QueryMsg::Ism(IsmQuery::Verify { raw_metadata, .. }) = msg;
+
+        ensure!(
+            raw_metadata.as_ref() == b"ok",
+            "mock ISM rejected the message"
+        );
+
+        Ok(IsmQueryResponse::Verify(()).to_json_value()?)
+    }
+}
+
+fn raw_message(origin_domain: Domain) -> HexBinary {
+    Message {
+        version: MAILBOX_VERSION,
+        nonce: 0,
+        origin_domain,
+        sender: MOCK_ROUTE.address,
+        destination_domain: MOCK_LOCAL_DOMAIN,
+        recipient: MOCK_ROUTE.address,
+        body: HexBinary::default(),
+    }
+    .encode()
+}
+
+#[test]
+fn aggregation_and_routing_isms() {
+    let (suite, mut accounts, _, contracts) = setup_test();
+    let (mut suite, ..) = HyperlaneTestSuite::new(suite, accounts.owner, 3, 2, MOCK_REMOTE_DOMAIN);
+
+    // Deploy two mock ISMs.
+    let mock_code_hash = suite
+        .upload(
+            &mut accounts.user1,
+            ContractBuilder::new(Box::new(mock_ism::instantiate))
+                .with_query(Box::new(mock_ism::query))
+                .build(),
+        )
+        .should_succeed()
+        .code_hash;
+
+    let [mock1, mock2] = ["mock_ism/1", "mock_ism/2"].map(|salt| {
+        suite
+            .instantiate(
+                &mut accounts.user1,
+                mock_code_hash,
+                &Empty {},
+                salt,
+                Some(salt),
+                None,
+                Coins::new(),
+            )
+            .should_succeed()
+            .address
+    });
+
+    // ------------------------------ aggregation ------------------------------
+
+    let aggregation_code_hash = suite
+        .upload(
+            &mut accounts.user1,
+            ContractBuilder::new(Box::new(hyperlane_aggregation_ism::instantiate))
+                .with_execute(Box::new(hyperlane_aggregation_ism::execute))
+                .with_query(Box::new(hyperlane_aggregation_ism::query))
+                .build(),
+        )
+        .should_succeed()
+        .code_hash;
+
+    let ism_set = IsmSet {
+        threshold: 2,
+        isms: vec![contracts.hyperlane.ism, mock1, mock2],
+    };
+
+    // The threshold can't exceed the number of ISMs.
+    suite
+        .instantiate(
+            &mut accounts.user1,
+            aggregation_code_hash,
+            &aggregation::InstantiateMsg {
+                threshold: 4,
+                isms: ism_set.isms.clone(),
+            },
+            "hyperlane/ism/aggregation",
+            Some("hyperlane/ism/aggregation"),
+            None,
+            Coins::new(),
+        )
+        .should_fail_with_error("not enough ISMs! threshold: 4, ISMs: 3");
+
+    let aggregation_ism = suite
+        .instantiate(
+            &mut accounts.user1,
+            aggregation_code_hash,
+            &aggregation::InstantiateMsg {
+                threshold: ism_set.threshold,
+                isms: ism_set.isms.clone(),
+            },
+            "hyperlane/ism/aggregation",
+            Some("hyperlane/ism/aggregation"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    suite
+        .query_wasm_smart(aggregation_ism, aggregation::QueryIsmSetRequest {})
+        .should_succeed_and_equal(ism_set);
+
+    // Only the chain owner can change the ISMs.
+    suite
+        .execute(
+            &mut accounts.user1,
+            aggregation_ism,
+            &aggregation::ExecuteMsg::SetIsms {
+                threshold: 1,
+                isms: vec![mock1],
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_isms`");
+
+    let ok = HexBinary::from(b"ok".to_vec());
+    let bad = HexBinary::from(b"bad".to_vec());
+
+    // Verification succeeds if the threshold is met and all ISMs that metadata
+    // is provided for verify the message.
+    for (metadata, error) in [
+        (vec![None, Some(ok.clone()), Some(ok.clone())], None),
+        (
+            vec![None, Some(ok.clone()), None],
+            Some("not enough ISMs verified the message! expecting at least 2, got 1"),
+        ),
+        (
+            vec![None, Some(ok.clone()), Some(bad)],
+            Some("mock ISM rejected the message"),
+        ),
+    ] {
+        let res = suite.query_wasm_smart(
+            aggregation_ism,
+            QueryIsmRequest(IsmQuery::Verify {
+                raw_message: raw_message(MOCK_REMOTE_DOMAIN),
+                raw_metadata: aggregation::Metadata(metadata).encode(),
+            }),
+        );
+
+        if let Some(error) = error {
+            res.should_fail_with_error(error);
+        } else {
+            res.should_succeed_and_equal(IsmQueryResponse::Verify(()));
+        }
+    }
+
+    // -------------------------------- routing --------------------------------
+
+    let routing_ism = suite
+        .upload_and_instantiate(
+            &mut accounts.user1,
+            ContractBuilder::new(Box::new(hyperlane_routing_ism::instantiate))
+                .with_execute(Box::new(hyperlane_routing_ism::execute))
+                .with_query(Box::new(hyperlane_routing_ism::query))
+                .build(),
+            &routing::InstantiateMsg {
+                modules: btree_map! {
+                    MOCK_REMOTE_DOMAIN => contracts.hyperlane.ism,
+                    MOCK_REMOTE_DOMAIN + 1 => aggregation_ism,
+                },
+            },
+            "hyperlane/ism/routing",
+            Some("hyperlane/ism/routing"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // Only the chain owner can change the routes.
+    suite
+        .execute(
+            &mut accounts.user1,
+            routing_ism,
+            &routing::ExecuteMsg::SetModule {
+                origin_domain: MOCK_REMOTE_DOMAIN,
+                ism: mock1,
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the chain owner can call `set_module`");
+
+    // Messages are verified by the ISM of their origin domain.
+    suite
+        .query_wasm_smart(
+            routing_ism,
+            QueryIsmRequest(IsmQuery::Verify {
+                raw_message: raw_message(MOCK_REMOTE_DOMAIN + 1),
+                raw_metadata: aggregation::Metadata(vec![None, Some(ok.clone()), Some(ok)])
+                    .encode(),
+            }),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(
+            routing_ism,
+            QueryIsmRequest(IsmQuery::Verify {
+                raw_message: raw_message(MOCK_REMOTE_DOMAIN + 2),
+                raw_metadata: HexBinary::default(),
+            }),
+        )
+        .should_fail_with_error(format!(
+            "no ISM for origin domain {}",
+            MOCK_REMOTE_DOMAIN + 2
+        ));
+
+    // ---------------------------------- warp ---------------------------------
+
+    // Only the chain owner can set Warp's ISM.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::SetIsm {
+                ism: Some(routing_ism),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("only chain owner can call `set_ism`");
+
+    suite
+        .hyperlane()
+        .set_ism(Some(routing_ism))
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(
+            contracts.warp,
+            QueryRecipientRequest(RecipientQuery::InterchainSecurityModule {}),
+        )
+        .should_succeed_and_equal(RecipientQueryResponse::InterchainSecurityModule(Some(
+            routing_ism,
+        )));
+
+    // Receive a transfer, which the mailbox verifies through the routing ISM.
+    suite
+        .hyperlane()
+        .set_route(SOL_DENOM.clone(), MOCK_REMOTE_DOMAIN, MOCK_ROUTE)
+        .should_succeed();
+
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(SOL_DENOM.clone(), 88).unwrap(),
+    );
+
+    suite
+        .query_balance(&accounts.user1, SOL_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(88));
+
+    // Unset Warp's ISM, deferring to the mailbox's default ISM.
+    suite.hyperlane().set_ism(None).should_succeed();
+
+    suite
+        .query_wasm_smart(
+            contracts.warp,
+            QueryRecipientRequest(RecipientQuery::InterchainSecurityModule {}),
+        )
+        .should_succeed_and_equal(RecipientQueryResponse::InterchainSecurityModule(None));
+}
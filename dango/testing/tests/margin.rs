@@ -9,6 +9,7 @@ use {
             single,
         },
         account_factory::AccountParams,
+        bank,
         config::AppConfig,
         constants::{USDC_DENOM, WBTC_DENOM},
         lending::{self, MarketUpdates, QueryDebtRequest},
@@ -591,6 +592,57 @@ fn liquidation_works_with_multiple_debt_denoms() {
     .unwrap();
 }
 
+#[test]
+fn cant_transfer_from_margin_account() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+    let mut margin_account = setup_margin_test_env(&mut suite, &mut accounts, &contracts);
+
+    // Borrow with the margin account, bringing it to the maximum utilization.
+    suite
+        .execute(
+            &mut margin_account,
+            contracts.lending,
+            &lending::ExecuteMsg::Borrow(Coins::one(USDC_DENOM.clone(), 100_000_000).unwrap()),
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The margin account allows user2 to spend the borrowed USDC.
+    suite
+        .execute(
+            &mut margin_account,
+            contracts.bank,
+            &bank::ExecuteMsg::Approve {
+                spender: accounts.user2.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(100_000_000),
+                expires_at: None,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Pulling the funds would skip the margin account's health check in the
+    // backrun, leaving it undercollateralized, so it's rejected.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.bank,
+            &bank::ExecuteMsg::TransferFrom {
+                owner: margin_account.address(),
+                to: accounts.user2.address(),
+                denom: USDC_DENOM.clone(),
+                amount: Uint128::new(100_000_000),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("can't transfer from margin account");
+
+    suite
+        .query_balance(&margin_account, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(100_000_000));
+}
+
 #[derive(Debug, Clone)]
 struct TestDenom {
     denom: Denom,
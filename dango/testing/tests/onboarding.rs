@@ -3,9 +3,10 @@ use {
     dango_testing::{setup_test_naive, Factory, HyperlaneTestSuite, TestAccount},
     dango_types::{
         account::single,
-        account_factory::{self, Account, AccountParams, NewUserSalt, Username},
+        account_factory::{self, Account, AccountParams, AccountType, NewUserSalt, Salt, Username},
         auth::Key,
         constants::USDC_DENOM,
+        warp,
     },
     grug::{
         btree_map, Addr, Addressable, ByteArray, Coin, Coins, Hash256, HashExt, Json, Message,
@@ -83,6 +84,132 @@ fn user_onboarding() {
         .should_succeed_and_equal(Uint128::new(10_000_000));
 }
 
+/// A user sends the initial deposit from another chain to the Ethereum address
+/// of their key, rather than to the predicted address of their account. Warp
+/// holds the funds, which are claimed when the user registers.
+#[test]
+fn onboarding_with_deposit_to_ethereum_address() {
+    let (suite, accounts, codes, contracts) = setup_test_naive();
+    let (mut suite, _) = HyperlaneTestSuite::new_mocked(suite, accounts.owner);
+
+    let user = TestAccount::new_random("user").predict_address(
+        contracts.account_factory,
+        codes.account_spot.to_bytes().hash256(),
+        true,
+    );
+
+    // Derive the Ethereum address of the user's key.
+    let eth_address = {
+        let (sk, _) = &user.keys()[&user.first_key_hash()];
+        let pk = sk.verifying_key().to_encoded_point(false).to_bytes();
+        let pk_hash = (&pk[1..]).keccak256();
+        Addr::from_inner(pk_hash[12..].try_into().unwrap())
+    };
+
+    // Make the initial deposit to the Ethereum address.
+    suite.hyperlane().recieve_transfer_mock(
+        eth_address,
+        Coin::new(USDC_DENOM.clone(), 10_000_000).unwrap(),
+    );
+
+    // Warp should be holding the funds for the Ethereum address.
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryDepositRequest {
+            owner: eth_address.into(),
+        })
+        .should_succeed_and_equal(Coins::one(USDC_DENOM.clone(), 10_000_000).unwrap());
+
+    suite
+        .execute(
+            &mut Factory::new(contracts.account_factory),
+            contracts.account_factory,
+            &account_factory::ExecuteMsg::RegisterUser {
+                username: user.username.clone(),
+                key: user.first_key(),
+                key_hash: user.first_key_hash(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The funds should have been claimed into the user's new account.
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryDepositRequest {
+            owner: eth_address.into(),
+        })
+        .should_succeed_and(|coins| coins.is_empty());
+
+    suite
+        .query_balance(&user, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(10_000_000));
+}
+
+/// Funds are sent from another chain to the predictable address of an account
+/// that hasn't been registered yet. Warp holds the funds, which are claimed when
+/// the account is registered.
+#[test]
+fn registering_account_claims_deposit() {
+    let (suite, mut accounts, _, contracts) = setup_test_naive();
+    let (mut suite, _) = HyperlaneTestSuite::new_mocked(suite, accounts.owner);
+
+    // Predict the address of the next account.
+    let index = suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryNextAccountIndexRequest {},
+        )
+        .unwrap();
+
+    let code_hash = suite
+        .query_wasm_smart(
+            contracts.account_factory,
+            account_factory::QueryCodeHashRequest {
+                account_type: AccountType::Spot,
+            },
+        )
+        .unwrap();
+
+    let address = Addr::derive(
+        contracts.account_factory,
+        code_hash,
+        &Salt { index }.into_bytes(),
+    );
+
+    suite
+        .hyperlane()
+        .recieve_transfer_mock(address, Coin::new(USDC_DENOM.clone(), 10_000_000).unwrap());
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryDepositRequest {
+            owner: address.into(),
+        })
+        .should_succeed_and_equal(Coins::one(USDC_DENOM.clone(), 10_000_000).unwrap());
+
+    let username = accounts.user1.username.clone();
+    let account = accounts
+        .user1
+        .register_new_account(
+            &mut suite,
+            contracts.account_factory,
+            AccountParams::Spot(single::Params::new(username)),
+            Coins::new(),
+        )
+        .unwrap();
+
+    assert_eq!(account.address(), address);
+
+    // The funds should have been claimed into the new account.
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryDepositRequest {
+            owner: address.into(),
+        })
+        .should_succeed_and(|coins| coins.is_empty());
+
+    suite
+        .query_balance(&account, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(10_000_000));
+}
+
 /// Attempt to register a username twice.
 /// The transaction should fail `CheckTx` and be rejected from entering mempool.
 #[test]
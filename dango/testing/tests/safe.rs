@@ -4,7 +4,10 @@ use {
     dango_testing::{setup_test_naive, Safe, TestAccount, TestAccounts, TestSuite},
     dango_types::{
         account::{
-            multi::{self, ParamUpdates, QueryProposalRequest, QueryVoteRequest, Status, Vote},
+            multi::{
+                self, ParamUpdates, QueryAllowanceRequest, QueryProposalRequest, QueryVoteRequest,
+                Status, Vote,
+            },
             single,
         },
         account_factory::{
@@ -40,6 +43,9 @@ fn setup_safe_test<'a>() -> (
         },
         voting_period: NonZero::new(Duration::from_seconds(30)).unwrap(),
         threshold: NonZero::new(2).unwrap(),
+        quorum: None,
+        veto_threshold: None,
+        policies: btree_map! {},
         // For the purpose of this test, the Safe doesn't have a timelock.
         timelock: None,
     };
@@ -129,6 +135,7 @@ fn proposal_passing_with_auto_execution() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "send 123 uusdc to owner".to_string(),
                 description: None,
                 messages: vec![Message::transfer(
@@ -202,6 +209,9 @@ fn proposal_passing_with_manual_execution() {
         ),
         voting_period: None,
         threshold: None,
+        quorum: None,
+        veto_threshold: None,
+        policies: ChangeSet::new_unchecked(btree_map! {}, btree_set! {}),
     };
 
     suite
@@ -209,6 +219,7 @@ fn proposal_passing_with_manual_execution() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "add user4 as member".to_string(),
                 description: None,
                 messages: vec![Message::execute(
@@ -299,6 +310,7 @@ fn proposal_failing() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -362,6 +374,7 @@ fn unauthorized_voting_via_impersonation_by_a_non_member() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -472,6 +485,7 @@ fn unauthorized_voting_via_impersonation_by_a_member() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -672,6 +686,7 @@ fn unauthorized_execute() {
             &mut accounts.user1,
             safe.address(),
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -717,6 +732,7 @@ fn vote_edge_cases() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -730,6 +746,7 @@ fn vote_edge_cases() {
             safe.with_signer(&accounts.user1),
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
                 title: "remove user3".to_string(),
                 description: None,
                 messages: vec![Message::execute(
@@ -746,6 +763,9 @@ fn vote_edge_cases() {
                             ),
                             threshold: None,
                             voting_period: None,
+                            quorum: None,
+                            veto_threshold: None,
+                            policies: ChangeSet::new_unchecked(btree_map! {}, btree_set! {}),
                         },
                     },
                     Coins::new(),
@@ -855,6 +875,7 @@ fn non_member_cannot_create_proposal() {
             safe.with_signer(&accounts.user4), // not a member
             safe_address,
             &multi::ExecuteMsg::Propose {
+                proposer: accounts.user4.username.clone(),
                 title: "nothing".to_string(),
                 description: None,
                 messages: vec![],
@@ -866,3 +887,285 @@ fn non_member_cannot_create_proposal() {
             safe_address, accounts.user4.username
         ));
 }
+
+#[test]
+fn proposal_cancelling() {
+    let (mut suite, accounts, _, mut safe, _) = setup_safe_test();
+    let safe_address = safe.address();
+
+    // Member 1 makes a proposal.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user1),
+            safe_address,
+            &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
+                title: "nothing".to_string(),
+                description: None,
+                messages: vec![],
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Member 2 attempts to cancel the proposal. Should fail, because only the
+    // proposer can cancel it.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user2),
+            safe_address,
+            &multi::ExecuteMsg::Cancel { proposal_id: 1 },
+            Coins::new(),
+        )
+        .should_fail_with_error("only the proposer can cancel a proposal");
+
+    // Member 1 cancels the proposal.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user1),
+            safe_address,
+            &multi::ExecuteMsg::Cancel { proposal_id: 1 },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The proposal should be in the "cancelled" state.
+    suite
+        .query_wasm_smart(safe.address(), QueryProposalRequest { proposal_id: 1 })
+        .should_succeed_and(|prop| prop.status == Status::Cancelled);
+
+    // Attempting to vote in the proposal should fail.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user2),
+            safe_address,
+            &multi::ExecuteMsg::Vote {
+                proposal_id: 1,
+                voter: accounts.user2.username.clone(),
+                vote: Vote::Yes,
+                execute: false,
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("proposal is not in voting period");
+}
+
+#[test]
+fn spending_allowance() {
+    let (mut suite, accounts, _, mut safe, _) = setup_safe_test();
+    let safe_address = safe.address();
+
+    // Member 1 attempts to grant themself an allowance without a proposal.
+    // Should fail.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user1),
+            safe_address,
+            &multi::ExecuteMsg::SetAllowance {
+                member: accounts.user1.username.clone(),
+                budget: Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+                period: NonZero::new(Duration::from_seconds(60)).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("this action can only be done through a proposal");
+
+    // Member 1 proposes to grant member 2 an allowance.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user1),
+            safe_address,
+            &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
+                title: "grant user2 an allowance".to_string(),
+                description: None,
+                messages: vec![Message::execute(
+                    safe_address,
+                    &multi::ExecuteMsg::SetAllowance {
+                        member: accounts.user2.username.clone(),
+                        budget: Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+                        period: NonZero::new(Duration::from_seconds(60)).unwrap(),
+                    },
+                    Coins::new(),
+                )
+                .unwrap()],
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Members 1 and 2 vote YES; the proposal passes and is executed.
+    for (member, execute) in [(&accounts.user1, false), (&accounts.user2, true)] {
+        suite
+            .execute(
+                safe.with_signer(member),
+                safe_address,
+                &multi::ExecuteMsg::Vote {
+                    proposal_id: 1,
+                    voter: member.username.clone(),
+                    vote: Vote::Yes,
+                    execute,
+                },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    // Member 3 attempts to spend from member 2's allowance. Should fail.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user3),
+            safe_address,
+            &multi::ExecuteMsg::Spend {
+                spender: accounts.user2.username.clone(),
+                recipient: accounts.user3.address(),
+                coins: Coins::one(USDC_DENOM.clone(), 50).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("can't spend with a different username");
+
+    // Member 2 spends from their allowance without a proposal.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user2),
+            safe_address,
+            &multi::ExecuteMsg::Spend {
+                spender: accounts.user2.username.clone(),
+                recipient: accounts.owner.address(),
+                coins: Coins::one(USDC_DENOM.clone(), 50).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Owner has 100_000_000_000 uusdc to start, and now has received 50.
+    suite
+        .query_balance(&accounts.owner, USDC_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(100_000_000_050));
+
+    // The remaining allowance should have been reduced.
+    suite
+        .query_wasm_smart(safe_address, QueryAllowanceRequest {
+            member: accounts.user2.username.clone(),
+        })
+        .should_succeed_and(|allowance| {
+            allowance.as_ref().unwrap().remaining == Coins::one(USDC_DENOM.clone(), 50).unwrap()
+        });
+}
+
+#[test]
+fn recurring_transfers_without_members() {
+    let (mut suite, mut accounts, _, mut safe, _) = setup_safe_test();
+    let safe_address = safe.address();
+
+    // Members propose and pass a recurring transfer of 100 uusdc to user5,
+    // once per minute.
+    suite
+        .execute(
+            safe.with_signer(&accounts.user1),
+            safe_address,
+            &multi::ExecuteMsg::Propose {
+                proposer: accounts.user1.username.clone(),
+                title: "pay user5 every minute".to_string(),
+                description: None,
+                messages: vec![Message::execute(
+                    safe_address,
+                    &multi::ExecuteMsg::CreateRecurringTransfer {
+                        recipient: accounts.user5.address(),
+                        coins: Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+                        interval: NonZero::new(Duration::from_seconds(60)).unwrap(),
+                    },
+                    Coins::new(),
+                )
+                .unwrap()],
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    for (member, execute) in [(&accounts.user1, false), (&accounts.user2, true)] {
+        suite
+            .execute(
+                safe.with_signer(member),
+                safe_address,
+                &multi::ExecuteMsg::Vote {
+                    proposal_id: 1,
+                    voter: member.username.clone(),
+                    vote: Vote::Yes,
+                    execute,
+                },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    let user5_balance_before = suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .unwrap();
+
+    // A non-member triggers the transfers before they're due. Nothing is paid.
+    suite
+        .execute(
+            &mut accounts.user4,
+            safe_address,
+            &multi::ExecuteMsg::ExecuteRecurringTransfers {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .should_succeed_and_equal(user5_balance_before);
+
+    // A minute later, the non-member triggers the transfers again. The due
+    // transfer is paid without any involvement of the members.
+    suite.block_time = Duration::from_seconds(60);
+
+    suite
+        .execute(
+            &mut accounts.user4,
+            safe_address,
+            &multi::ExecuteMsg::ExecuteRecurringTransfers {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .should_succeed_and_equal(user5_balance_before + Uint128::new(100));
+
+    // Another minute later, the account factory's cronjob makes the transfer,
+    // without anyone triggering it.
+    suite.make_empty_block();
+
+    suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .should_succeed_and_equal(user5_balance_before + Uint128::new(200));
+
+    // Five minutes later, the transfer is made only once, rather than once for
+    // each missed minute.
+    suite.block_time = Duration::from_minutes(5);
+    suite.make_empty_block();
+
+    suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .should_succeed_and_equal(user5_balance_before + Uint128::new(300));
+
+    // The next transfer is due after the current time, so triggering it again
+    // in the same block does nothing.
+    suite.block_time = Duration::ZERO;
+
+    suite
+        .execute(
+            &mut accounts.user4,
+            safe_address,
+            &multi::ExecuteMsg::ExecuteRecurringTransfers {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user5, USDC_DENOM.clone())
+        .should_succeed_and_equal(user5_balance_before + Uint128::new(300));
+}
@@ -150,6 +150,11 @@ mod session_account {
                 chain_id: chain_id.to_string(),
                 nonce: self.nonce,
                 expiry: None,
+                referrer: None,
+                fee_denom: None,
+                granter: None,
+                max_fee_rate: None,
+                tip_rate: None,
             };
 
             let sign_doc = SignDoc {
@@ -1,11 +1,49 @@
 use {
-    dango_testing::setup_test_naive,
-    dango_types::{constants::USDC_DENOM, taxman},
-    grug::{Addressable, Coins, MultiplyFraction, Number, ResultExt, Udec128, Uint128},
+    dango_testing::{setup_test_naive, TestAccount},
+    dango_types::{
+        auth::{Credential, Metadata, SignDoc},
+        constants::{ETH_DENOM, USDC_DENOM, WBTC_DENOM},
+        oracle::{self, PriceSource},
+        taxman::{
+            self, QueryBaseFeeRequest, QueryFeeGrantRequest, QueryGrantDepositRequest,
+            QueryReferralFeesRequest,
+        },
+    },
+    grug::{
+        btree_map, btree_set, Addressable, Coins, JsonSerExt, Message, MultiplyFraction,
+        MultiplyRatio, NonEmpty, Number, NumberConst, QuerierExt, ResultExt, Tx, Udec128, Uint128,
+    },
+    std::collections::BTreeSet,
 };
 
 const OLD_FEE_RATE: Udec128 = Udec128::new_percent(1); // 0.01 uusdc per gas unit
 const NEW_FEE_RATE: Udec128 = Udec128::new_percent(2); // 0.02 uusdc per gas unit
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// Sign a transaction with the given metadata, which may have the optional
+/// fields such as the referrer and the fee denom set.
+fn sign_tx_with_metadata(account: &mut TestAccount, msg: Message, data: Metadata) -> Tx {
+    let msgs = NonEmpty::new_unchecked(vec![msg]);
+
+    let sign_doc = SignDoc {
+        sender: account.address(),
+        gas_limit: GAS_LIMIT,
+        messages: msgs.clone(),
+        data: data.clone(),
+    };
+
+    let credential = account.create_standard_credential(&sign_doc.to_json_vec().unwrap());
+
+    account.nonce += 1;
+
+    Tx {
+        sender: account.address(),
+        gas_limit: GAS_LIMIT,
+        msgs,
+        data: data.to_json_value().unwrap(),
+        credential: Credential::Standard(credential).to_json_value().unwrap(),
+    }
+}
 
 #[test]
 fn fee_rate_update_works() {
@@ -27,6 +65,10 @@ fn fee_rate_update_works() {
                 new_cfg: taxman::Config {
                     fee_denom: USDC_DENOM.clone(),
                     fee_rate: OLD_FEE_RATE,
+                    alt_fee_denoms: BTreeSet::new(),
+                    alt_fee_premium: Udec128::ZERO,
+                    referral_fee_rate: Udec128::ZERO,
+                    dynamic_fee: None,
                 },
             },
             Coins::new(),
@@ -50,6 +92,10 @@ fn fee_rate_update_works() {
                 new_cfg: taxman::Config {
                     fee_denom: USDC_DENOM.clone(),
                     fee_rate: NEW_FEE_RATE,
+                    alt_fee_denoms: BTreeSet::new(),
+                    alt_fee_premium: Udec128::ZERO,
+                    referral_fee_rate: Udec128::ZERO,
+                    dynamic_fee: None,
                 },
             },
             Coins::new(),
@@ -79,3 +125,484 @@ fn fee_rate_update_works() {
         .query_balance(&accounts.user1, USDC_DENOM.clone())
         .should_succeed_and_equal(user_usdc_balance.checked_sub(fee).unwrap());
 }
+
+#[test]
+fn referral_fees_work() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.taxman,
+            &taxman::ExecuteMsg::Configure {
+                new_cfg: taxman::Config {
+                    fee_denom: USDC_DENOM.clone(),
+                    fee_rate: OLD_FEE_RATE,
+                    alt_fee_denoms: BTreeSet::new(),
+                    alt_fee_premium: Udec128::ZERO,
+                    referral_fee_rate: Udec128::new_percent(50),
+                    dynamic_fee: None,
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Only the owner can register referrers.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.taxman,
+            &taxman::ExecuteMsg::RegisterReferrer {
+                referrer: accounts.user2.address(),
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("you don't have the right");
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.taxman,
+            &taxman::ExecuteMsg::RegisterReferrer {
+                referrer: accounts.user2.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // User1 sends a transaction naming user2 as the referrer.
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.referrer = Some(accounts.user2.address());
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(
+            accounts.user3.address(),
+            Coins::one(USDC_DENOM.clone(), 1).unwrap(),
+        )
+        .unwrap(),
+        data,
+    );
+
+    let outcome = suite.send_transaction(tx).should_succeed();
+
+    // Half of the fee is credited to the referrer.
+    let referral_amount = Uint128::new(outcome.gas_used as u128)
+        .checked_mul_dec_ceil(OLD_FEE_RATE)
+        .unwrap()
+        .checked_mul_dec_floor(Udec128::new_percent(50))
+        .unwrap();
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryReferralFeesRequest {
+            referrer: accounts.user2.address(),
+        })
+        .should_succeed_and_equal(Coins::one(USDC_DENOM.clone(), referral_amount).unwrap());
+
+    // User2 claims the referral fees.
+    let user2_balance = suite
+        .query_balance(&accounts.user2, USDC_DENOM.clone())
+        .unwrap();
+
+    let outcome = suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::ClaimReferralFees {},
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let fee = Uint128::new(outcome.gas_used as u128)
+        .checked_mul_dec_ceil(OLD_FEE_RATE)
+        .unwrap();
+
+    suite
+        .query_balance(&accounts.user2, USDC_DENOM.clone())
+        .should_succeed_and_equal(user2_balance + referral_amount - fee);
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryReferralFeesRequest {
+            referrer: accounts.user2.address(),
+        })
+        .should_succeed_and(|fees| fees.is_empty());
+}
+
+#[test]
+fn paying_fee_in_alt_denom_works() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    // Use fixed prices for USDC and ETH, so that the conversion is predictable.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.oracle,
+            &oracle::ExecuteMsg::RegisterPriceSources(btree_map! {
+                USDC_DENOM.clone() => PriceSource::Fixed {
+                    humanized_price: Udec128::ONE,
+                    precision: 6,
+                    timestamp: 0,
+                },
+                ETH_DENOM.clone() => PriceSource::Fixed {
+                    humanized_price: Udec128::new(2_000),
+                    precision: 18,
+                    timestamp: 0,
+                },
+            }),
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.taxman,
+            &taxman::ExecuteMsg::Configure {
+                new_cfg: taxman::Config {
+                    fee_denom: USDC_DENOM.clone(),
+                    fee_rate: OLD_FEE_RATE,
+                    alt_fee_denoms: btree_set! { ETH_DENOM.clone() },
+                    alt_fee_premium: Udec128::new_percent(10),
+                    referral_fee_rate: Udec128::ZERO,
+                    dynamic_fee: None,
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let usdc_balance = suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .unwrap();
+    let eth_balance = suite
+        .query_balance(&accounts.user1, ETH_DENOM.clone())
+        .unwrap();
+
+    // User1 sends a transaction, paying the fee in ETH.
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.fee_denom = Some(ETH_DENOM.clone());
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user2.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    let outcome = suite.send_transaction(tx).should_succeed();
+
+    // The withheld amount is 1,000,000 gas * 0.01 uusdc = 10,000 uusdc = 0.01 USD,
+    // plus 10% premium, i.e. 0.011 USD, or 0.0000055 ETH.
+    // The actual charge is converted at the same rate.
+    let fee = Uint128::new(outcome.gas_used as u128)
+        .checked_mul_dec_ceil(OLD_FEE_RATE)
+        .unwrap()
+        .checked_multiply_ratio_ceil(Uint128::new(5_500_000_000_000), Uint128::new(10_000))
+        .unwrap();
+
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(usdc_balance);
+
+    suite
+        .query_balance(&accounts.user1, ETH_DENOM.clone())
+        .should_succeed_and_equal(eth_balance - fee);
+
+    // Paying in a denom that isn't whitelisted fails.
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.fee_denom = Some(WBTC_DENOM.clone());
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user2.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    suite
+        .send_transaction(tx)
+        .should_fail_with_error("is not accepted");
+}
+
+#[test]
+fn fee_grants_work() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.taxman,
+            &taxman::ExecuteMsg::Configure {
+                new_cfg: taxman::Config {
+                    fee_denom: USDC_DENOM.clone(),
+                    fee_rate: OLD_FEE_RATE,
+                    alt_fee_denoms: BTreeSet::new(),
+                    alt_fee_premium: Udec128::ZERO,
+                    referral_fee_rate: Udec128::ZERO,
+                    dynamic_fee: None,
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // User2 deposits funds and grants user1 a fee allowance.
+    let deposit_amount = Uint128::new(1_000_000);
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::DepositGrantFunds {},
+            Coins::one(USDC_DENOM.clone(), deposit_amount).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::GrantFeeAllowance {
+                grantee: accounts.user1.address(),
+                allowance: taxman::FeeAllowance {
+                    total_limit: Some(Uint128::new(500_000)),
+                    period_limit: None,
+                    allowed_contracts: None,
+                    expires_at: None,
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let user1_balance = suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .unwrap();
+
+    // User1 sends a transaction naming user2 as the granter.
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.granter = Some(accounts.user2.address());
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user3.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    let outcome = suite.send_transaction(tx).should_succeed();
+
+    let fee = Uint128::new(outcome.gas_used as u128)
+        .checked_mul_dec_ceil(OLD_FEE_RATE)
+        .unwrap();
+
+    // The fee is paid from user2's deposit instead of user1's balance.
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(user1_balance);
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryGrantDepositRequest {
+            granter: accounts.user2.address(),
+        })
+        .should_succeed_and_equal(Coins::one(USDC_DENOM.clone(), deposit_amount - fee).unwrap());
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryFeeGrantRequest {
+            granter: accounts.user2.address(),
+            grantee: accounts.user1.address(),
+        })
+        .should_succeed_and(|grant| grant.as_ref().unwrap().spent == fee);
+
+    // User2 restricts the allowance to executing the taxman contract. A plain
+    // transfer is no longer covered.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::GrantFeeAllowance {
+                grantee: accounts.user1.address(),
+                allowance: taxman::FeeAllowance {
+                    total_limit: None,
+                    period_limit: None,
+                    allowed_contracts: Some(btree_set! { contracts.taxman }),
+                    expires_at: None,
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.granter = Some(accounts.user2.address());
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user3.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    suite
+        .send_transaction(tx)
+        .should_fail_with_error("fee allowance doesn't cover this transaction");
+
+    // User2 revokes the allowance and withdraws the remaining deposit.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::RevokeFeeAllowance {
+                grantee: accounts.user1.address(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.taxman,
+            &taxman::ExecuteMsg::WithdrawGrantFunds {
+                coins: Coins::one(USDC_DENOM.clone(), deposit_amount - fee).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryFeeGrantRequest {
+            granter: accounts.user2.address(),
+            grantee: accounts.user1.address(),
+        })
+        .should_succeed_and_equal(None);
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryGrantDepositRequest {
+            granter: accounts.user2.address(),
+        })
+        .should_succeed_and(|deposit| deposit.is_empty());
+}
+
+#[test]
+fn dynamic_base_fee_works() {
+    let (mut suite, mut accounts, _, contracts) = setup_test_naive();
+
+    let max_change_rate = Udec128::new_permille(125);
+
+    // Enable dynamic fees, with a target so low that any block containing a
+    // transaction raises the base fee by the maximum rate.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.taxman,
+            &taxman::ExecuteMsg::Configure {
+                new_cfg: taxman::Config {
+                    fee_denom: USDC_DENOM.clone(),
+                    fee_rate: OLD_FEE_RATE,
+                    alt_fee_denoms: BTreeSet::new(),
+                    alt_fee_premium: Udec128::ZERO,
+                    referral_fee_rate: Udec128::ZERO,
+                    dynamic_fee: Some(taxman::DynamicFeeParams {
+                        target_gas_per_block: 1,
+                        max_change_rate,
+                        base_fee_destination: taxman::BaseFeeDestination::Treasury(
+                            accounts.user4.address(),
+                        ),
+                    }),
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    let base_fee = OLD_FEE_RATE
+        .checked_mul(Udec128::ONE + max_change_rate)
+        .unwrap();
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryBaseFeeRequest {})
+        .should_succeed_and_equal(base_fee);
+
+    let user1_balance = suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .unwrap();
+    let treasury_balance = suite
+        .query_balance(&accounts.user4, USDC_DENOM.clone())
+        .unwrap();
+
+    // User1 sends a transaction with a tip.
+    let tip_rate = Udec128::new_percent(1);
+
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.tip_rate = Some(tip_rate);
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user2.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    let outcome = suite.send_transaction(tx).should_succeed();
+
+    // User1 pays the base fee plus the tip. The base fee portion goes to the
+    // treasury.
+    let fee_rate = base_fee + tip_rate;
+    let fee = Uint128::new(outcome.gas_used as u128)
+        .checked_mul_dec_ceil(fee_rate)
+        .unwrap();
+    let base_fee_amount = fee
+        .checked_mul_dec_floor(base_fee.checked_div(fee_rate).unwrap())
+        .unwrap();
+
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(user1_balance - fee);
+
+    suite
+        .query_balance(&accounts.user4, USDC_DENOM.clone())
+        .should_succeed_and_equal(treasury_balance + base_fee_amount);
+
+    // The base fee rises again.
+    let base_fee = base_fee
+        .checked_mul(Udec128::ONE + max_change_rate)
+        .unwrap();
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryBaseFeeRequest {})
+        .should_succeed_and_equal(base_fee);
+
+    // A transaction whose max fee rate is below the base fee is rejected.
+    let mut data = accounts
+        .user1
+        .metadata(&suite.chain_id, accounts.user1.nonce, None);
+    data.max_fee_rate = Some(OLD_FEE_RATE);
+
+    let tx = sign_tx_with_metadata(
+        &mut accounts.user1,
+        Message::transfer(accounts.user2.address(), Coins::new()).unwrap(),
+        data,
+    );
+
+    suite
+        .send_transaction(tx)
+        .should_fail_with_error("is below the base fee");
+
+    // Empty blocks lower the base fee, but not below the minimum.
+    for _ in 0..3 {
+        suite.make_empty_block();
+    }
+
+    suite
+        .query_wasm_smart(contracts.taxman, QueryBaseFeeRequest {})
+        .should_succeed_and_equal(OLD_FEE_RATE);
+}
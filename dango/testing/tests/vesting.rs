@@ -2,11 +2,14 @@ use {
     dango_testing::{setup_test_naive, TestAccounts, TestSuite},
     dango_types::{
         constants::{DANGO_DENOM, USDC_DENOM},
-        vesting::{self, QueryPositionRequest, Schedule, VestingStatus},
+        vesting::{
+            self, QueryPositionRequest, QueryUserSummaryRequest, QueryVotingPowerRequest, Schedule,
+            Step, VestingSchedule, VestingStatus,
+        },
     },
     grug::{
         Addr, Addressable, Coin, Coins, Duration, Inner, MultiplyFraction, QuerierExt, ResultExt,
-        StdError, Timestamp, Udec128, Uint128,
+        Timestamp, Udec128, Uint128,
     },
     grug_app::NaiveProposalPreparer,
     std::sync::LazyLock,
@@ -33,11 +36,11 @@ fn missing_funds() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: Duration::from_seconds(0),
                     cliff: Duration::from_seconds(0),
                     period: Duration::from_seconds(0),
-                },
+                }),
             },
             Coins::default(),
         )
@@ -54,11 +57,11 @@ fn non_owner_creating_position() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.owner.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: Duration::from_seconds(0),
                     cliff: Duration::from_seconds(0),
                     period: Duration::from_seconds(0),
-                },
+                }),
             },
             Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
         )
@@ -66,27 +69,145 @@ fn non_owner_creating_position() {
 }
 
 #[test]
-fn not_dango_token() {
+fn multiple_positions_of_any_denom() {
     let (mut suite, mut accounts, vesting_addr) = setup_test();
 
+    let start_time = suite.block.timestamp;
+
+    // Step portions must sum up to one.
     suite
         .execute(
             &mut accounts.owner,
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
-                    start_time: Duration::from_seconds(0),
-                    cliff: Duration::from_seconds(0),
-                    period: Duration::from_seconds(0),
+                schedule: VestingSchedule::Steps(vec![Step {
+                    time: start_time + ONE_MONTH,
+                    portion: Udec128::new_percent(50),
+                }]),
+            },
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error("step portions must sum up to one");
+
+    // Position 1: 25% vests at the end of each month, for 4 months.
+    suite
+        .execute(
+            &mut accounts.owner,
+            vesting_addr,
+            &vesting::ExecuteMsg::Create {
+                user: accounts.user1.address(),
+                schedule: VestingSchedule::Periodic {
+                    start_time,
+                    cliff: Duration::ZERO,
+                    interval: ONE_MONTH,
+                    num_periods: 4,
                 },
             },
             Coins::one(USDC_DENOM.clone(), 100).unwrap(),
         )
-        .should_fail_with_error(StdError::invalid_payment(
-            DANGO_DENOM.clone(),
-            USDC_DENOM.clone(),
-        ));
+        .should_succeed();
+
+    // Position 2: half vests after 2 months, the other half after 3 months.
+    suite
+        .execute(
+            &mut accounts.owner,
+            vesting_addr,
+            &vesting::ExecuteMsg::Create {
+                user: accounts.user1.address(),
+                schedule: VestingSchedule::Steps(vec![
+                    Step {
+                        time: start_time + ONE_MONTH * 2,
+                        portion: Udec128::new_percent(50),
+                    },
+                    Step {
+                        time: start_time + ONE_MONTH * 3,
+                        portion: Udec128::new_percent(50),
+                    },
+                ]),
+            },
+            Coins::one(USDC_DENOM.clone(), 100).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(vesting_addr, vesting::QueryPositionsRequest {
+            user: accounts.user1.address(),
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|positions| positions.keys().eq([1, 2].iter()));
+
+    let initial_balance = suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed();
+
+    // After one month, only the first period of position 1 has vested.
+    // Tokens other than Dango aren't subject to the unlocking schedule.
+    suite.block_time = ONE_MONTH;
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Claim {},
+            Coins::default(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(initial_balance + Uint128::new(25));
+
+    suite
+        .query_wasm_smart(vesting_addr, QueryUserSummaryRequest {
+            user: accounts.user1.address(),
+        })
+        .should_succeed_and(|summary| {
+            summary.total == Coins::one(USDC_DENOM.clone(), 200).unwrap()
+                && summary.claimed == Coins::one(USDC_DENOM.clone(), 25).unwrap()
+                && summary.claimable.is_empty()
+        });
+
+    // After two months, the second period of position 1 and the first step of
+    // position 2 have vested.
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Claim {},
+            Coins::default(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(initial_balance + Uint128::new(100));
+
+    // After four months, everything has vested.
+    suite.block_time = ONE_MONTH * 2;
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Claim {},
+            Coins::default(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, USDC_DENOM.clone())
+        .should_succeed_and_equal(initial_balance + Uint128::new(200));
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Claim {},
+            Coins::default(),
+        )
+        .should_fail_with_error("nothing to claim");
 }
 
 #[test]
@@ -99,11 +220,11 @@ fn before_unlocking_starting_time() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: suite.block.timestamp - ONE_MONTH,
                     cliff: ONE_MONTH * 9,
                     period: ONE_MONTH * 27,
-                },
+                }),
             },
             TEST_AMOUNT.clone(),
         )
@@ -196,6 +317,7 @@ fn before_unlocking_starting_time() {
         suite
             .query_wasm_smart(vesting_addr, vesting::QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| res.position.claimed == res.position.total);
     }
@@ -211,11 +333,11 @@ fn after_unlocking_starting_time() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: suite.block.timestamp + ONE_MONTH,
                     cliff: ONE_MONTH * 9,
                     period: ONE_MONTH * 27,
-                },
+                }),
             },
             TEST_AMOUNT.clone(),
         )
@@ -323,6 +445,7 @@ fn after_unlocking_starting_time() {
         suite
             .query_wasm_smart(vesting_addr, vesting::QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| res.position.claimed == res.position.total);
     }
@@ -338,11 +461,11 @@ fn terminate_before_unlocking_starting_time_never_claimed() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: suite.block.timestamp - ONE_MONTH,
                     cliff: ONE_MONTH * 9,
                     period: ONE_MONTH * 27,
-                },
+                }),
             },
             TEST_AMOUNT.clone(),
         )
@@ -369,6 +492,7 @@ fn terminate_before_unlocking_starting_time_never_claimed() {
                 vesting_addr,
                 &vesting::ExecuteMsg::Terminate {
                     user: accounts.user1.address(),
+                    position_id: 1,
                 },
                 Coins::default(),
             )
@@ -378,6 +502,7 @@ fn terminate_before_unlocking_starting_time_never_claimed() {
         suite
             .query_wasm_smart(vesting_addr, QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(40))
@@ -418,6 +543,7 @@ fn terminate_before_unlocking_starting_time_never_claimed() {
         suite
             .query_wasm_smart(vesting_addr, vesting::QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(40))
@@ -441,11 +567,11 @@ fn terminate_before_unlocking_starting_time_with_claimed() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: suite.block.timestamp - ONE_MONTH,
                     cliff: ONE_MONTH * 9,
                     period: ONE_MONTH * 27,
-                },
+                }),
             },
             TEST_AMOUNT.clone(),
         )
@@ -497,6 +623,7 @@ fn terminate_before_unlocking_starting_time_with_claimed() {
                 vesting_addr,
                 &vesting::ExecuteMsg::Terminate {
                     user: accounts.user1.address(),
+                    position_id: 1,
                 },
                 Coins::default(),
             )
@@ -506,6 +633,7 @@ fn terminate_before_unlocking_starting_time_with_claimed() {
         suite
             .query_wasm_smart(vesting_addr, QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(44))
@@ -531,6 +659,7 @@ fn terminate_before_unlocking_starting_time_with_claimed() {
         suite
             .query_wasm_smart(vesting_addr, vesting::QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(44))
@@ -554,11 +683,11 @@ fn terminate_after_unlocking_starting_time() {
             vesting_addr,
             &vesting::ExecuteMsg::Create {
                 user: accounts.user1.address(),
-                schedule: Schedule {
+                schedule: VestingSchedule::Linear(Schedule {
                     start_time: suite.block.timestamp + ONE_MONTH,
                     cliff: ONE_MONTH * 9,
                     period: ONE_MONTH * 27,
-                },
+                }),
             },
             TEST_AMOUNT.clone(),
         )
@@ -583,6 +712,7 @@ fn terminate_after_unlocking_starting_time() {
                 vesting_addr,
                 &vesting::ExecuteMsg::Terminate {
                     user: accounts.user1.address(),
+                    position_id: 1,
                 },
                 Coins::default(),
             )
@@ -592,6 +722,7 @@ fn terminate_after_unlocking_starting_time() {
         suite
             .query_wasm_smart(vesting_addr, QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(37))
@@ -614,6 +745,7 @@ fn terminate_after_unlocking_starting_time() {
         suite
             .query_wasm_smart(vesting_addr, vesting::QueryPositionRequest {
                 user: accounts.user1.address(),
+                position_id: 1,
             })
             .should_succeed_and(|res| {
                 res.position.vesting_status == VestingStatus::Terminated(Uint128::new(37))
@@ -627,6 +759,158 @@ fn terminate_after_unlocking_starting_time() {
     }
 }
 
+#[test]
+fn delegating_voting_power() {
+    let (mut suite, mut accounts, vesting_addr) = setup_test();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            vesting_addr,
+            &vesting::ExecuteMsg::Create {
+                user: accounts.user1.address(),
+                schedule: VestingSchedule::Linear(Schedule {
+                    start_time: suite.block.timestamp - ONE_MONTH,
+                    cliff: ONE_MONTH * 9,
+                    period: ONE_MONTH * 27,
+                }),
+            },
+            TEST_AMOUNT.clone(),
+        )
+        .should_succeed();
+
+    let voting_power = |suite: &TestSuite<NaiveProposalPreparer>, address: Addr| {
+        suite
+            .query_wasm_smart(vesting_addr, QueryVotingPowerRequest {
+                address,
+                denom: DANGO_DENOM.clone(),
+            })
+            .unwrap()
+    };
+
+    // Unclaimed tokens count as voting power.
+    assert_eq!(
+        voting_power(&suite, accounts.user1.address()),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::ZERO
+    );
+
+    // User3 has no vesting position, so it can't delegate.
+    suite
+        .execute(
+            &mut accounts.user3,
+            vesting_addr,
+            &vesting::ExecuteMsg::Delegate {
+                delegatee: Some(accounts.user2.address()),
+            },
+            Coins::default(),
+        )
+        .should_fail_with_error("can't delegate without a vesting position");
+
+    // User1 delegates to user2.
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Delegate {
+                delegatee: Some(accounts.user2.address()),
+            },
+            Coins::default(),
+        )
+        .should_succeed();
+
+    assert_eq!(
+        voting_power(&suite, accounts.user1.address()),
+        Uint128::ZERO
+    );
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::new(100)
+    );
+
+    // Claimed tokens no longer count.
+    suite.block_time = ONE_MONTH * 10;
+
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Claim {},
+            Coins::default(),
+        )
+        .should_succeed();
+
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::new(63)
+    );
+
+    // A new position of user1 is delegated too.
+    suite.block_time = Duration::ZERO;
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            vesting_addr,
+            &vesting::ExecuteMsg::Create {
+                user: accounts.user1.address(),
+                schedule: VestingSchedule::Linear(Schedule {
+                    start_time: suite.block.timestamp,
+                    cliff: ONE_MONTH * 9,
+                    period: ONE_MONTH * 27,
+                }),
+            },
+            TEST_AMOUNT.clone(),
+        )
+        .should_succeed();
+
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::new(163)
+    );
+
+    // Nothing of the new position has vested, so terminating it claws back
+    // all of its voting power.
+    suite
+        .execute(
+            &mut accounts.owner,
+            vesting_addr,
+            &vesting::ExecuteMsg::Terminate {
+                user: accounts.user1.address(),
+                position_id: 2,
+            },
+            Coins::default(),
+        )
+        .should_succeed();
+
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::new(63)
+    );
+
+    // User1 undelegates.
+    suite
+        .execute(
+            &mut accounts.user1,
+            vesting_addr,
+            &vesting::ExecuteMsg::Delegate { delegatee: None },
+            Coins::default(),
+        )
+        .should_succeed();
+
+    assert_eq!(
+        voting_power(&suite, accounts.user1.address()),
+        Uint128::new(63)
+    );
+    assert_eq!(
+        voting_power(&suite, accounts.user2.address()),
+        Uint128::ZERO
+    );
+}
+
 // Duration for unlock 1 token
 fn epoch(total_duration: Duration, vesting_amount: Uint128) -> Duration {
     Duration::from_nanos(total_duration.into_nanos() / vesting_amount.into_inner())
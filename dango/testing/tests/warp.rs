@@ -5,21 +5,25 @@ use {
         MOCK_REMOTE_DOMAIN,
     },
     dango_types::{
-        constants::{DANGO_DENOM, ETH_DENOM, SOL_DENOM},
-        warp::{self, Route, TokenMessage},
+        constants::{DANGO_DENOM, ETH_DENOM, SOL_DENOM, USDC_DENOM},
+        warp::{
+            self, AlloyComponent, QueuedTransfer, RateLimit, RateLimitUsage, RateLimits, Route,
+            TokenMessage,
+        },
     },
     dango_warp::ROUTES,
     grug::{
-        Addressable, Coin, Coins, HashExt, HexBinary, NumberConst, QuerierExt, ResultExt, StdError,
-        Uint128,
+        btree_map, coins, Addressable, Coin, Coins, Denom, Duration, HashExt, HexBinary,
+        NumberConst, QuerierExt, ResultExt, StdError, Uint128,
     },
     hyperlane_types::{
         addr32,
-        hooks::merkle,
+        hooks::{fee, merkle},
         mailbox::{self, Message, MAILBOX_VERSION},
         Addr32, IncrementalMerkleTree,
     },
     sea_orm::EntityTrait,
+    std::{collections::BTreeMap, str::FromStr},
 };
 
 const MOCK_ROUTE: Route = Route {
@@ -46,6 +50,9 @@ fn send_escrowing_collateral() {
                 destination_domain: MOCK_REMOTE_DOMAIN,
                 recipient: MOCK_RECIPIENT,
                 metadata: Some(metadata.clone()),
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
             },
             Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
         )
@@ -86,6 +93,9 @@ fn send_escrowing_collateral() {
                 destination_domain: MOCK_REMOTE_DOMAIN,
                 recipient: MOCK_RECIPIENT,
                 metadata: Some(metadata.clone()),
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
             },
             Coins::one(DANGO_DENOM.clone(), 100).unwrap(),
         )
@@ -115,9 +125,9 @@ fn send_escrowing_collateral() {
             tree
         });
 
-    // The fee hook should have received the fee.
+    // The taxman should have received the withdrawal fee.
     suite
-        .query_balance(&contracts.hyperlane.fee, DANGO_DENOM.clone())
+        .query_balance(&contracts.taxman, DANGO_DENOM.clone())
         .should_succeed_and_equal(MOCK_ROUTE.fee);
 
     // Force the runtime to wait for the async indexer task to finish
@@ -137,13 +147,13 @@ fn send_escrowing_collateral() {
             .await
             .expect("Can't fetch transfers");
 
-        assert_that!(transfers).has_length(3);
+        assert_that!(transfers).has_length(2);
 
         assert_that!(transfers
             .iter()
             .map(|t| t.amount.as_str())
             .collect::<Vec<_>>())
-        .is_equal_to(vec!["100", "25", "25"]);
+        .is_equal_to(vec!["100", "25"]);
     });
 }
 
@@ -176,6 +186,9 @@ fn send_burning_synth() {
                 destination_domain: MOCK_REMOTE_DOMAIN,
                 recipient: MOCK_RECIPIENT,
                 metadata: Some(metadata.clone()),
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
             },
             Coins::one(ETH_DENOM.clone(), 12345).unwrap(),
         )
@@ -215,10 +228,214 @@ fn send_burning_synth() {
         .query_balance(&contracts.warp, ETH_DENOM.clone())
         .should_succeed_and_equal(Uint128::ZERO);
 
-    // Fee hook should have received the fee.
+    // Taxman should have received the withdrawal fee.
     suite
-        .query_balance(&contracts.hyperlane.fee, ETH_DENOM.clone())
+        .query_balance(&contracts.taxman, ETH_DENOM.clone())
+        .should_succeed_and_equal(MOCK_ROUTE.fee);
+}
+
+#[test]
+fn send_multiple_tokens_with_custom_hook() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    // Set the routes for a collateral and a synth token.
+    for denom in [DANGO_DENOM.clone(), ETH_DENOM.clone()] {
+        suite
+            .execute(
+                &mut accounts.owner,
+                contracts.warp,
+                &warp::ExecuteMsg::SetRoute {
+                    denom,
+                    destination_domain: MOCK_REMOTE_DOMAIN,
+                    route: MOCK_ROUTE,
+                },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    // Send both tokens at once, using the Merkle tree hook as the custom hook.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: Some(contracts.hyperlane.merkle),
+                hook_metadata: None,
+                hook_fee: None,
+            },
+            coins! {
+                DANGO_DENOM.clone() => 100,
+                ETH_DENOM.clone() => 200,
+            },
+        )
+        .should_succeed();
+
+    // One message should have been dispatched per token. The Merkle tree hook
+    // is called both as the required hook and as the custom hook, so each
+    // message is inserted twice.
+    suite
+        .query_wasm_smart(contracts.hyperlane.merkle, merkle::QueryTreeRequest {})
+        .should_succeed_and(|tree| tree.count == 4);
+
+    // The collateral should have been escrowed, and the synth burned.
+    suite
+        .query_balance(&contracts.warp, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(100) - MOCK_ROUTE.fee);
+
+    suite
+        .query_balance(&contracts.warp, ETH_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    // The taxman should have received the withdrawal fees, rather than the
+    // custom hook.
+    for denom in [DANGO_DENOM.clone(), ETH_DENOM.clone()] {
+        suite
+            .query_balance(&contracts.taxman, denom.clone())
+            .should_succeed_and_equal(MOCK_ROUTE.fee);
+
+        suite
+            .query_balance(&contracts.hyperlane.merkle, denom)
+            .should_succeed_and_equal(Uint128::ZERO);
+    }
+}
+
+#[test]
+fn send_with_fee_schedule() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    // Set the route for the synth token.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.warp,
+            &warp::ExecuteMsg::SetRoute {
+                denom: ETH_DENOM.clone(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                route: MOCK_ROUTE,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // Charge a fee, in a different denom than the token being sent, for
+    // dispatching messages to the remote domain.
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::SetFee {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                fee: Coins::one(DANGO_DENOM.clone(), 10).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The mailbox should quote the fee schedule, not the withdrawal fee.
+    suite
+        .query_wasm_smart(
+            contracts.hyperlane.mailbox,
+            mailbox::QueryQuoteDispatchRequest {
+                sender: contracts.warp,
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_ROUTE.address,
+                body: TokenMessage {
+                    recipient: MOCK_RECIPIENT,
+                    amount: Uint128::new(100) - MOCK_ROUTE.fee,
+                    metadata: HexBinary::default(),
+                }
+                .encode(),
+                metadata: None,
+                hook: None,
+            },
+        )
+        .should_succeed_and_equal(Coins::one(DANGO_DENOM.clone(), 10).unwrap());
+
+    // Sending without the hook fee fails.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
+            },
+            Coins::one(ETH_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error("insufficient hook fee");
+
+    // Specifying a hook fee without attaching it fails.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: Some(Coins::one(DANGO_DENOM.clone(), 10).unwrap()),
+            },
+            Coins::one(ETH_DENOM.clone(), 100).unwrap(),
+        )
+        .should_fail_with_error("attached funds don't cover the hook fee");
+
+    let dango_balance_before = suite
+        .query_balance(&accounts.user1, DANGO_DENOM.clone())
+        .unwrap();
+
+    // Send with more hook fee than needed.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: Some(Coins::one(DANGO_DENOM.clone(), 15).unwrap()),
+            },
+            coins! {
+                DANGO_DENOM.clone() => 15,
+                ETH_DENOM.clone() => 100,
+            },
+        )
+        .should_succeed();
+
+    // The fee hook should have received exactly the fee schedule, and the
+    // excess should have been refunded.
+    suite
+        .query_balance(&contracts.hyperlane.fee, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(10));
+
+    suite
+        .query_balance(&accounts.user1, DANGO_DENOM.clone())
+        .should_succeed_and_equal(dango_balance_before - Uint128::new(10));
+
+    // The withdrawal fee should have gone to the taxman, not the fee hook.
+    suite
+        .query_balance(&contracts.taxman, ETH_DENOM.clone())
         .should_succeed_and_equal(MOCK_ROUTE.fee);
+
+    suite
+        .query_balance(&contracts.hyperlane.fee, ETH_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    // The Warp contract shouldn't hold any of the hook fee.
+    suite
+        .query_balance(&contracts.warp, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
 }
 
 #[test]
@@ -272,6 +489,111 @@ fn receive_release_collateral() {
         .should_succeed_and_equal(Uint128::new(100 - 88));
 }
 
+#[test]
+fn retry_failed_handle() {
+    let (suite, mut accounts, _, contracts) = setup_test();
+    let (mut suite, ..) = HyperlaneTestSuite::new(suite, accounts.owner, 3, 2, MOCK_REMOTE_DOMAIN);
+
+    suite
+        .hyperlane()
+        .set_route(DANGO_DENOM.clone(), MOCK_REMOTE_DOMAIN, MOCK_ROUTE)
+        .should_succeed();
+
+    let balance_before = suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .unwrap();
+
+    // Receive a message while Warp has no collateral to release. Handling it
+    // fails, but the message is still delivered, and recorded as failed.
+    let message_id = suite
+        .hyperlane()
+        .receive_transfer(
+            MOCK_REMOTE_DOMAIN,
+            accounts.user2.address(),
+            Coin::new(DANGO_DENOM.clone(), 88).unwrap(),
+        )
+        .message_id;
+
+    suite
+        .query_wasm_smart(
+            contracts.hyperlane.mailbox,
+            mailbox::QueryDeliveredRequest { message_id },
+        )
+        .should_succeed_and_equal(true);
+
+    suite
+        .query_wasm_smart(
+            contracts.hyperlane.mailbox,
+            mailbox::QueryFailedMessagesRequest {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .should_succeed_and(|failed| {
+            failed.len() == 1 && failed[&message_id].recipient == Addr32::from(contracts.warp)
+        });
+
+    // Retrying fails as long as the recipient can't handle the message. The
+    // message remains recorded as failed.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &mailbox::ExecuteMsg::RetryHandle { message_id },
+            Coins::new(),
+        )
+        .should_fail();
+
+    suite
+        .query_wasm_smart(
+            contracts.hyperlane.mailbox,
+            mailbox::QueryFailedMessageRequest { message_id },
+        )
+        .should_succeed_and(|failed| failed.is_some());
+
+    // Send some tokens so that Warp has collateral to release.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(DANGO_DENOM.clone(), 125).unwrap(),
+        )
+        .should_succeed();
+
+    // Now anyone can retry the message successfully.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &mailbox::ExecuteMsg::RetryHandle { message_id },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .should_succeed_and_equal(balance_before + Uint128::new(88));
+
+    suite
+        .query_wasm_smart(
+            contracts.hyperlane.mailbox,
+            mailbox::QueryFailedMessageRequest { message_id },
+        )
+        .should_succeed_and(|failed| failed.is_none());
+
+    // A message can't be retried once it's been handled.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.hyperlane.mailbox,
+            &mailbox::ExecuteMsg::RetryHandle { message_id },
+            Coins::new(),
+        )
+        .should_fail_with_error("no failed message with id");
+}
+
 #[test]
 fn receive_minting_synth() {
     let (suite, accounts, _, contracts) = setup_test();
@@ -306,3 +628,576 @@ fn receive_minting_synth() {
         .query_balance(&accounts.user1, SOL_DENOM.clone())
         .should_succeed_and_equal(Uint128::new(88));
 }
+
+#[test]
+fn alloyed_tokens() {
+    let (suite, mut accounts, _, contracts) = setup_test();
+    let (mut suite, ..) = HyperlaneTestSuite::new(suite, accounts.owner, 3, 2, MOCK_REMOTE_DOMAIN);
+
+    let eth_usdc = USDC_DENOM.clone();
+    let arb_usdc = Denom::from_str("hyp/arb/usdc").unwrap();
+    let alloy_usdc = Denom::from_str("alloy/usdc").unwrap();
+
+    let eth_usdc_route = Route {
+        address: addr32!("0000000000000000000000000000000000000000000000000000000000000002"),
+        fee: Uint128::new(25),
+    };
+    let arb_usdc_route = Route {
+        address: addr32!("0000000000000000000000000000000000000000000000000000000000000003"),
+        fee: Uint128::new(25),
+    };
+
+    suite
+        .hyperlane()
+        .set_route(eth_usdc.clone(), MOCK_REMOTE_DOMAIN, eth_usdc_route.clone())
+        .should_succeed();
+
+    suite
+        .hyperlane()
+        .set_route(arb_usdc.clone(), MOCK_REMOTE_DOMAIN, arb_usdc_route)
+        .should_succeed();
+
+    // Alloy both USDC variants. Cap the Arbitrum one.
+    for (underlying_denom, cap) in [(&eth_usdc, None), (&arb_usdc, Some(Uint128::new(60)))] {
+        suite
+            .hyperlane()
+            .set_alloy(underlying_denom.clone(), alloy_usdc.clone(), cap)
+            .should_succeed();
+    }
+
+    // Receive both variants. The alloyed token is minted instead.
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(eth_usdc.clone(), 100).unwrap(),
+    );
+
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(arb_usdc.clone(), 50).unwrap(),
+    );
+
+    suite
+        .query_balance(&accounts.user1, alloy_usdc.clone())
+        .should_succeed_and_equal(Uint128::new(150));
+
+    suite
+        .query_balance(&accounts.user1, arb_usdc.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    // Receiving more of the Arbitrum variant would exceed its cap, so the
+    // underlying token is minted instead.
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(arb_usdc.clone(), 20).unwrap(),
+    );
+
+    suite
+        .query_balance(&accounts.user1, arb_usdc.clone())
+        .should_succeed_and_equal(Uint128::new(20));
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryAlloyCompositionRequest {
+            alloyed_denom: alloy_usdc.clone(),
+        })
+        .should_succeed_and_equal(btree_map! {
+            arb_usdc.clone() => AlloyComponent {
+                cap: Some(Uint128::new(60)),
+                supply: Uint128::new(50),
+            },
+            eth_usdc.clone() => AlloyComponent {
+                cap: None,
+                supply: Uint128::new(100),
+            },
+        });
+
+    // Neither underlying token has enough liquidity for this withdrawal.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(alloy_usdc.clone(), 145).unwrap(),
+        )
+        .should_fail_with_error("has sufficient liquidity");
+
+    // This one can go through the Ethereum variant's route.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(alloy_usdc.clone(), 105).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, alloy_usdc.clone())
+        .should_succeed_and_equal(Uint128::new(45));
+
+    suite
+        .query_balance(&contracts.warp, alloy_usdc.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    suite
+        .query_balance(&contracts.taxman, alloy_usdc.clone())
+        .should_succeed_and_equal(eth_usdc_route.fee);
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryAlloyCompositionRequest {
+            alloyed_denom: alloy_usdc.clone(),
+        })
+        .should_succeed_and(|composition| {
+            composition[&eth_usdc].supply == Uint128::new(20)
+                && composition[&arb_usdc].supply == Uint128::new(50)
+        });
+
+    // Pause the Arbitrum variant's route. Although it comes first and has
+    // enough liquidity, the withdrawal goes through the Ethereum variant's
+    // route instead.
+    suite
+        .hyperlane()
+        .set_route_paused(arb_usdc.clone(), MOCK_REMOTE_DOMAIN, true)
+        .should_succeed();
+
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(alloy_usdc.clone(), 40).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryAlloyCompositionRequest {
+            alloyed_denom: alloy_usdc,
+        })
+        .should_succeed_and(|composition| {
+            composition[&eth_usdc].supply == Uint128::new(5)
+                && composition[&arb_usdc].supply == Uint128::new(50)
+        });
+}
+
+#[test]
+fn rate_limits_and_pausing() {
+    let (suite, mut accounts, _, contracts) = setup_test();
+    let (mut suite, ..) = HyperlaneTestSuite::new(suite, accounts.owner, 3, 2, MOCK_REMOTE_DOMAIN);
+
+    let denom = Denom::from_str("hyp/eth/usdc").unwrap();
+
+    suite
+        .hyperlane()
+        .set_route(denom.clone(), MOCK_REMOTE_DOMAIN, MOCK_ROUTE)
+        .should_succeed();
+
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(denom.clone(), 500).unwrap(),
+    );
+
+    // Cap outbound transfers at 100 tokens per day.
+    suite
+        .hyperlane()
+        .set_rate_limits(denom.clone(), MOCK_REMOTE_DOMAIN, RateLimits {
+            inbound: None,
+            outbound: Some(RateLimit {
+                window: Duration::from_days(1),
+                cap: Uint128::new(100),
+                release_delay: Duration::from_hours(1),
+            }),
+        })
+        .should_succeed();
+
+    // A transfer within the limit is dispatched right away.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(denom.clone(), 105).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryRateLimitUsageRequest {
+            denom: denom.clone(),
+            domain: MOCK_REMOTE_DOMAIN,
+        })
+        .should_succeed_and_equal(RateLimitUsage {
+            inbound: Uint128::ZERO,
+            outbound: Uint128::new(80),
+        });
+
+    // A transfer exceeding the limit is queued. Warp holds on to the tokens.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(denom.clone(), 75).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, denom.clone())
+        .should_succeed_and_equal(Uint128::new(320));
+
+    suite
+        .query_balance(&contracts.warp, denom.clone())
+        .should_succeed_and_equal(Uint128::new(75));
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryQueuedTransferRequest { id: 1 })
+        .should_succeed_and(|transfer: &QueuedTransfer| {
+            matches!(
+                transfer,
+                QueuedTransfer::Outbound { token, .. } if token.amount == Uint128::new(50)
+            )
+        });
+
+    // The queued transfer can't be released before the delay has elapsed.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.warp,
+            &warp::ExecuteMsg::ReleaseQueuedTransfer { id: 1 },
+            Coins::new(),
+        )
+        .should_fail_with_error("is not yet releasable");
+
+    // Pause the route. Outbound transfers are rejected, while inbound ones are
+    // queued.
+    suite
+        .hyperlane()
+        .set_route_paused(denom.clone(), MOCK_REMOTE_DOMAIN, true)
+        .should_succeed();
+
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(denom.clone(), 30).unwrap(),
+        )
+        .should_fail_with_error("is paused");
+
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(denom.clone(), 30).unwrap(),
+    );
+
+    suite
+        .query_balance(&accounts.user1, denom.clone())
+        .should_succeed_and_equal(Uint128::new(320));
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryQueuedTransferRequest { id: 2 })
+        .should_succeed_and(|transfer: &QueuedTransfer| {
+            matches!(
+                transfer,
+                QueuedTransfer::Inbound { amount, .. } if *amount == Uint128::new(30)
+            )
+        });
+
+    // Once the delay has elapsed, transfers still can't be released while the
+    // route is paused.
+    suite.block_time = Duration::from_hours(1);
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.warp,
+            &warp::ExecuteMsg::ReleaseQueuedTransfer { id: 1 },
+            Coins::new(),
+        )
+        .should_fail_with_error("is paused");
+
+    // Unpause the route. Anyone can now release the queued transfers.
+    suite
+        .hyperlane()
+        .set_route_paused(denom.clone(), MOCK_REMOTE_DOMAIN, false)
+        .should_succeed();
+
+    for id in [1, 2] {
+        suite
+            .execute(
+                &mut accounts.user2,
+                contracts.warp,
+                &warp::ExecuteMsg::ReleaseQueuedTransfer { id },
+                Coins::new(),
+            )
+            .should_succeed();
+    }
+
+    suite
+        .query_balance(&accounts.user1, denom.clone())
+        .should_succeed_and_equal(Uint128::new(350));
+
+    suite
+        .query_balance(&contracts.warp, denom.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryQueuedTransfersRequest {
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers: &BTreeMap<_, _>| transfers.is_empty());
+
+    // The released outbound transfer counts against the rate limit.
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryRateLimitUsageRequest {
+            denom: denom.clone(),
+            domain: MOCK_REMOTE_DOMAIN,
+        })
+        .should_succeed_and_equal(RateLimitUsage {
+            inbound: Uint128::ZERO,
+            outbound: Uint128::new(130),
+        });
+
+    // So the next transfer is queued, even though it's within the cap alone.
+    suite
+        .hyperlane()
+        .send_transfer(
+            &mut accounts.user1,
+            MOCK_REMOTE_DOMAIN,
+            MOCK_RECIPIENT,
+            Coin::new(denom.clone(), 45).unwrap(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&contracts.warp, denom.clone())
+        .should_succeed_and_equal(Uint128::new(45));
+
+    // Only the chain owner or the sender can cancel an outbound transfer.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.warp,
+            &warp::ExecuteMsg::CancelQueuedTransfer { id: 3 },
+            Coins::new(),
+        )
+        .should_fail_with_error("only chain owner or the sender can call `cancel_queued_transfer`");
+
+    // Cancelling an outbound transfer refunds the sender, including the fee.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::CancelQueuedTransfer { id: 3 },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, denom.clone())
+        .should_succeed_and_equal(Uint128::new(350));
+
+    suite
+        .query_balance(&contracts.warp, denom.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+
+    // Cancelling an inbound transfer drops it.
+    suite
+        .hyperlane()
+        .set_route_paused(denom.clone(), MOCK_REMOTE_DOMAIN, true)
+        .should_succeed();
+
+    suite.hyperlane().receive_transfer(
+        MOCK_REMOTE_DOMAIN,
+        accounts.user1.address(),
+        Coin::new(denom.clone(), 10).unwrap(),
+    );
+
+    // Only the chain owner can cancel an inbound transfer.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::CancelQueuedTransfer { id: 4 },
+            Coins::new(),
+        )
+        .should_fail_with_error("only chain owner or the sender can call `cancel_queued_transfer`");
+
+    suite.hyperlane().cancel_queued_transfer(4).should_succeed();
+
+    suite
+        .query_balance(&accounts.user1, denom.clone())
+        .should_succeed_and_equal(Uint128::new(350));
+
+    suite
+        .query_wasm_smart(contracts.warp, warp::QueryQueuedTransfersRequest {
+            start_after: None,
+            limit: None,
+        })
+        .should_succeed_and(|transfers: &BTreeMap<_, _>| transfers.is_empty());
+}
+
+#[test]
+fn release_with_current_route_and_quote() {
+    let (mut suite, mut accounts, _, contracts) = setup_test();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.warp,
+            &warp::ExecuteMsg::SetRoute {
+                denom: ETH_DENOM.clone(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                route: MOCK_ROUTE,
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.warp,
+            &warp::ExecuteMsg::SetRateLimits {
+                denom: ETH_DENOM.clone(),
+                domain: MOCK_REMOTE_DOMAIN,
+                limits: RateLimits {
+                    inbound: None,
+                    outbound: Some(RateLimit {
+                        window: Duration::from_days(1),
+                        cap: Uint128::new(100),
+                        release_delay: Duration::from_hours(1),
+                    }),
+                },
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    // The transfer exceeds the limit, so it's queued. No hook fee is charged
+    // at this point.
+    suite
+        .execute(
+            &mut accounts.user1,
+            contracts.warp,
+            &warp::ExecuteMsg::TransferRemote {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: MOCK_RECIPIENT,
+                metadata: None,
+                hook: None,
+                hook_metadata: None,
+                hook_fee: None,
+            },
+            Coins::one(ETH_DENOM.clone(), 150).unwrap(),
+        )
+        .should_succeed();
+
+    // While queued, the route is moved to a new address with a higher fee, and
+    // a fee is charged for dispatching messages.
+    let new_route = Route {
+        address: addr32!("0000000000000000000000000000000000000000000000000000000000000002"),
+        fee: Uint128::new(50),
+    };
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.warp,
+            &warp::ExecuteMsg::SetRoute {
+                denom: ETH_DENOM.clone(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                route: new_route.clone(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite
+        .execute(
+            &mut accounts.owner,
+            contracts.hyperlane.fee,
+            &fee::ExecuteMsg::SetFee {
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                fee: Coins::one(DANGO_DENOM.clone(), 10).unwrap(),
+            },
+            Coins::new(),
+        )
+        .should_succeed();
+
+    suite.block_time = Duration::from_hours(1);
+
+    // The reserved hook fee no longer covers the quote, so the releaser has to
+    // make up the difference.
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.warp,
+            &warp::ExecuteMsg::ReleaseQueuedTransfer { id: 1 },
+            Coins::new(),
+        )
+        .should_fail_with_error("attached funds don't cover the hook fee shortfall");
+
+    let dango_balance_before = suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .unwrap();
+
+    suite
+        .execute(
+            &mut accounts.user2,
+            contracts.warp,
+            &warp::ExecuteMsg::ReleaseQueuedTransfer { id: 1 },
+            Coins::one(DANGO_DENOM.clone(), 15).unwrap(),
+        )
+        .should_succeed();
+
+    // The fee hook received exactly the quote, and the excess was refunded to
+    // the releaser.
+    suite
+        .query_balance(&contracts.hyperlane.fee, DANGO_DENOM.clone())
+        .should_succeed_and_equal(Uint128::new(10));
+
+    suite
+        .query_balance(&accounts.user2, DANGO_DENOM.clone())
+        .should_succeed_and_equal(dango_balance_before - Uint128::new(10));
+
+    // The message was sent to the route's new address, while the withdrawal
+    // fee charged when the transfer was queued was kept.
+    suite
+        .query_wasm_smart(contracts.hyperlane.merkle, merkle::QueryTreeRequest {})
+        .should_succeed_and_equal({
+            let msg = Message {
+                version: MAILBOX_VERSION,
+                nonce: 0,
+                origin_domain: MOCK_LOCAL_DOMAIN,
+                sender: contracts.warp.into(),
+                destination_domain: MOCK_REMOTE_DOMAIN,
+                recipient: new_route.address,
+                body: TokenMessage {
+                    recipient: MOCK_RECIPIENT,
+                    amount: Uint128::new(150) - MOCK_ROUTE.fee,
+                    metadata: HexBinary::default(),
+                }
+                .encode(),
+            };
+
+            let mut tree = IncrementalMerkleTree::default();
+            tree.insert(msg.encode().keccak256()).unwrap();
+            tree
+        });
+
+    suite
+        .query_balance(&contracts.taxman, ETH_DENOM.clone())
+        .should_succeed_and_equal(MOCK_ROUTE.fee);
+
+    suite
+        .query_balance(&contracts.warp, ETH_DENOM.clone())
+        .should_succeed_and_equal(Uint128::ZERO);
+}
@@ -1,6 +1,6 @@
 use {
     crate::auth::Nonce,
-    grug::{Bounded, Coins, Denom, Udec128, Uint128, ZeroExclusiveOneInclusive},
+    grug::{Addr, Bounded, Coins, Denom, Udec128, Uint128, ZeroExclusiveOneInclusive},
     std::collections::BTreeSet,
 };
 
@@ -32,6 +32,15 @@ pub enum ExecuteMsg {
         /// The collateral denom to liquidate and be compensated with.
         collateral: Denom,
     },
+    /// Register an additional contract to be consulted when authenticating
+    /// transactions sent by this account.
+    RegisterAuthenticator { authenticator: Addr },
+    /// Deregister an authenticator contract.
+    ///
+    /// A transaction containing only this message isn't subject to the
+    /// authenticators' vetoes, so that a faulty authenticator can always be
+    /// removed with the owner's signature.
+    DeregisterAuthenticator { authenticator: Addr },
 }
 
 /// Query messages for the margin account
@@ -43,6 +52,9 @@ pub enum QueryMsg {
     /// Queries the health of the margin account.
     #[returns(HealthResponse)]
     Health {},
+    /// Query the additional authenticator contracts registered by the account.
+    #[returns(BTreeSet<Addr>)]
+    Authenticators {},
 }
 
 #[grug::derive(Serde)]
@@ -1,13 +1,16 @@
 use {
     crate::{account_factory::Username, auth::Nonce},
-    anyhow::anyhow,
-    grug::{ChangeSet, Duration, Inner, Message, NonZero, Timestamp},
+    anyhow::{anyhow, ensure},
+    grug::{Addr, ChangeSet, Coins, Duration, Inner, Message, NonZero, Op, Timestamp},
     std::collections::{BTreeMap, BTreeSet},
 };
 
 /// Identifier of a proposal.
 pub type ProposalId = u32;
 
+/// Identifier of a recurring transfer.
+pub type RecurringTransferId = u32;
+
 /// The number of votes a member has.
 ///
 /// E.g. if a user has a power of 2, then each vote this member casts is counted
@@ -23,7 +26,26 @@ pub struct Params {
     pub voting_period: NonZero<Duration>,
     /// The minimum number of YES votes a proposal must receive in order to pass.
     /// Must be between 1 and the total power across all members (inclusive).
+    ///
+    /// This is the default threshold, which applies to proposals that contain
+    /// messages not covered by any of the `policies` below.
     pub threshold: NonZero<Power>,
+    /// The minimum total power of votes (of any option) that must be casted
+    /// in order for a proposal to pass. `None` means there is no quorum.
+    ///
+    /// Same as `threshold`, this is the default that can be overridden by
+    /// `policies`.
+    pub quorum: Option<NonZero<Power>>,
+    /// If the total power of NO_WITH_VETO votes reaches this value, the
+    /// proposal fails, regardless of how many YES votes it has received.
+    /// `None` means a NO_WITH_VETO vote is counted the same way as a NO vote.
+    pub veto_threshold: Option<NonZero<Power>>,
+    /// Voting rules that apply to proposals containing specific kinds of
+    /// messages, overriding the default `threshold` and `quorum`.
+    ///
+    /// E.g. a Safe may require 1-of-N for transfers, but M-of-N for migrating
+    /// contracts.
+    pub policies: BTreeMap<MessageKind, Policy>,
     /// The minimum delay after a proposal is passed before it can be executed.
     pub timelock: Option<NonZero<Duration>>,
 }
@@ -44,6 +66,79 @@ impl Params {
         self.members.values().map(|power| power.into_inner()).sum()
     }
 
+    /// The voting rules that apply to messages not covered by any policy.
+    pub fn default_policy(&self) -> Policy {
+        Policy {
+            threshold: self.threshold,
+            quorum: self.quorum,
+        }
+    }
+
+    /// Find the voting rules applicable to a proposal containing the given
+    /// messages.
+    ///
+    /// Each message is subject to the policy of its kind, or the default policy
+    /// if there isn't one. If the proposal contains multiple messages, the most
+    /// restrictive threshold and quorum among them apply.
+    pub fn policy_for(&self, messages: &[Message]) -> Policy {
+        let default = self.default_policy();
+
+        messages
+            .iter()
+            .map(|msg| {
+                self.policies
+                    .get(&MessageKind::of(msg))
+                    .copied()
+                    .unwrap_or(default)
+            })
+            .reduce(|a, b| Policy {
+                threshold: a.threshold.max(b.threshold),
+                quorum: a.quorum.max(b.quorum),
+            })
+            .unwrap_or(default)
+    }
+
+    /// Ensure the parameters are sound. Specifically, none of the thresholds
+    /// or quorums may exceed the total voting power.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let total_power = self.total_power();
+
+        ensure!(
+            self.threshold.into_inner() <= total_power,
+            "threshold can't be greater than total power"
+        );
+
+        if let Some(quorum) = self.quorum {
+            ensure!(
+                quorum.into_inner() <= total_power,
+                "quorum can't be greater than total power"
+            );
+        }
+
+        if let Some(veto_threshold) = self.veto_threshold {
+            ensure!(
+                veto_threshold.into_inner() <= total_power,
+                "veto threshold can't be greater than total power"
+            );
+        }
+
+        for (kind, policy) in &self.policies {
+            ensure!(
+                policy.threshold.into_inner() <= total_power,
+                "threshold for `{kind:?}` messages can't be greater than total power"
+            );
+
+            if let Some(quorum) = policy.quorum {
+                ensure!(
+                    quorum.into_inner() <= total_power,
+                    "quorum for `{kind:?}` messages can't be greater than total power"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// Apply a set of updates to self.
     pub fn apply_updates(&mut self, updates: ParamUpdates) {
         for member in updates.members.remove() {
@@ -61,15 +156,71 @@ impl Params {
         if let Some(new) = updates.threshold {
             self.threshold = new;
         }
+
+        if let Some(new) = updates.quorum {
+            self.quorum = new.into_option();
+        }
+
+        if let Some(new) = updates.veto_threshold {
+            self.veto_threshold = new.into_option();
+        }
+
+        for kind in updates.policies.remove() {
+            self.policies.remove(kind);
+        }
+
+        for (kind, policy) in updates.policies.into_add() {
+            self.policies.insert(kind, policy);
+        }
+    }
+}
+
+/// Kinds of messages a proposal may contain. Used to assign different voting
+/// rules to different kinds of actions.
+#[grug::derive(Serde, Borsh)]
+#[derive(Copy, PartialOrd, Ord)]
+pub enum MessageKind {
+    Configure,
+    Transfer,
+    Upload,
+    Instantiate,
+    Execute,
+    Migrate,
+}
+
+impl MessageKind {
+    pub fn of(msg: &Message) -> Self {
+        match msg {
+            Message::Configure(_) => MessageKind::Configure,
+            Message::Transfer(_) => MessageKind::Transfer,
+            Message::Upload(_) => MessageKind::Upload,
+            Message::Instantiate(_) => MessageKind::Instantiate,
+            Message::Execute(_) => MessageKind::Execute,
+            Message::Migrate(_) => MessageKind::Migrate,
+        }
     }
 }
 
+/// Voting rules for a proposal.
+#[grug::derive(Serde, Borsh)]
+#[derive(Copy, PartialOrd, Ord)]
+pub struct Policy {
+    /// The minimum number of YES votes the proposal must receive in order to pass.
+    pub threshold: NonZero<Power>,
+    /// The minimum total power of votes (of any option) that must be casted
+    /// in order for the proposal to pass.
+    pub quorum: Option<NonZero<Power>>,
+}
+
 /// A set of updates to be applied to a Safe.
 #[grug::derive(Serde)]
 pub struct ParamUpdates {
     pub members: ChangeSet<Username, NonZero<Power>>,
     pub voting_period: Option<NonZero<Duration>>,
     pub threshold: Option<NonZero<Power>>,
+    pub quorum: Option<Op<NonZero<Power>>>,
+    pub veto_threshold: Option<Op<NonZero<Power>>>,
+    pub policies: ChangeSet<MessageKind, Policy>,
     // Note that we don't allow changing the timelock, which is an important
     // parameter in limiting admin power and minimizing trust in DeFi protocols.
 }
@@ -78,6 +229,8 @@ pub struct ParamUpdates {
 // which includes `serde_json::Value`, doesn't implement those traits.
 #[grug::derive(Serde)]
 pub struct Proposal {
+    /// The member who created this proposal.
+    pub proposer: Username,
     pub title: String,
     pub description: Option<String>,
     pub messages: Vec<Message>,
@@ -94,12 +247,19 @@ pub enum Status {
         /// These parameters can change at any time, so we save the parameters
         /// _at the time the proposal was created_ inside the proposal.
         params: Params,
+        /// The voting rules applicable to this proposal, determined by the
+        /// params and the kinds of messages the proposal contains.
+        policy: Policy,
         /// The time when voting period ends.
         until: Timestamp,
         /// Number of YES votes collected so far.
         yes: Power,
         /// Number of NO votes collected so far.
         no: Power,
+        /// Number of ABSTAIN votes collected so far.
+        abstain: Power,
+        /// Number of NO_WITH_VETO votes collected so far.
+        veto: Power,
     },
     /// The proposal has received equal or more YES votes than the Safe's
     /// threshold, and can be executed once the timelock (if any) is passed.
@@ -108,16 +268,15 @@ pub enum Status {
         execute_after: Timestamp,
     },
     /// The proposal has failed to receive a sufficient number of YES votes
-    /// during its voting period.
+    /// or reach the quorum during its voting period, or has been vetoed.
     Failed,
     /// The proposal has passed and been executed.
     Executed,
+    /// The proposal has been cancelled by its proposer before execution.
+    Cancelled,
 }
 
 /// A vote to a proposal.
-///
-/// We currently don't support "abstain" or "no with veto" votes. If you need
-/// them, please let us know.
 #[grug::derive(Serde, Borsh)]
 #[derive(Copy)]
 pub enum Vote {
@@ -125,12 +284,59 @@ pub enum Vote {
     Yes,
     /// The member voices opposition against this proposal.
     No,
+    /// The member neither supports nor opposes this proposal. The vote counts
+    /// towards the quorum, but not the threshold.
+    Abstain,
+    /// The member voices strong opposition against this proposal. Counted as
+    /// a NO vote, and additionally towards the Safe's veto threshold.
+    NoWithVeto,
+}
+
+/// An amount of coins a member can spend from the Safe per period, without
+/// going through a proposal.
+#[grug::derive(Serde, Borsh)]
+pub struct Allowance {
+    /// The maximum amount of coins that can be spent in each period.
+    pub budget: Coins,
+    /// The length of each period.
+    pub period: NonZero<Duration>,
+    /// The time when the current period started.
+    pub period_start: Timestamp,
+    /// The amount of coins that can still be spent in the current period.
+    pub remaining: Coins,
+}
+
+impl Allowance {
+    /// Start a new period if the current one has elapsed, which resets the
+    /// remaining amount to the full budget.
+    pub fn refresh(&mut self, now: Timestamp) {
+        if now >= self.period_start + self.period.into_inner() {
+            self.period_start = now;
+            self.remaining = self.budget.clone();
+        }
+    }
+}
+
+/// A transfer that is made from the Safe periodically.
+#[grug::derive(Serde, Borsh)]
+pub struct RecurringTransfer {
+    /// The address to receive the coins.
+    pub recipient: Addr,
+    /// The amount of coins to send in each transfer.
+    pub coins: Coins,
+    /// The time between two consecutive transfers.
+    pub interval: NonZero<Duration>,
+    /// The earliest time the next transfer can be made.
+    pub next_transfer: Timestamp,
 }
 
 #[grug::derive(Serde)]
 pub enum ExecuteMsg {
     /// Create a new proposal with the given title, descriptions, and messages.
     Propose {
+        /// Username of the member creating the proposal. Must match the
+        /// transaction signer's username.
+        proposer: Username,
         title: String,
         description: Option<String>,
         messages: Vec<Message>,
@@ -149,6 +355,54 @@ pub enum ExecuteMsg {
     /// Execute a proposal once it's passed and the timelock (if there is one)
     /// has elapsed.
     Execute { proposal_id: ProposalId },
+    /// Cancel a proposal that hasn't been executed yet.
+    ///
+    /// Can only be done by the proposal's proposer.
+    Cancel { proposal_id: ProposalId },
+    /// Grant a member an allowance to spend from the Safe without a proposal,
+    /// replacing the existing one if there is any.
+    ///
+    /// Can only be done through a proposal.
+    SetAllowance {
+        member: Username,
+        budget: Coins,
+        period: NonZero<Duration>,
+    },
+    /// Revoke a member's allowance.
+    ///
+    /// Can only be done through a proposal.
+    RevokeAllowance { member: Username },
+    /// Send coins from the Safe, deducted from the spender's allowance.
+    Spend {
+        /// Username of the member spending the allowance. Must match the
+        /// transaction signer's username.
+        spender: Username,
+        recipient: Addr,
+        coins: Coins,
+    },
+    /// Create a transfer that is to be made periodically. The first transfer
+    /// is made once one interval has elapsed.
+    ///
+    /// The transfers are made by the account factory's cronjob once due, or by
+    /// anyone calling `ExecuteRecurringTransfers`. If the Safe falls behind by
+    /// more than one interval, the missed transfers are skipped, so at most one
+    /// transfer is made per call.
+    ///
+    /// Can only be done through a proposal.
+    CreateRecurringTransfer {
+        recipient: Addr,
+        coins: Coins,
+        interval: NonZero<Duration>,
+    },
+    /// Remove a recurring transfer.
+    ///
+    /// Can only be done through a proposal.
+    RevokeRecurringTransfer { id: RecurringTransferId },
+    /// Make the recurring transfers that are due.
+    ///
+    /// Can be called by anyone. The account factory's cronjob calls this when
+    /// the next recurring transfer is due.
+    ExecuteRecurringTransfers {},
 }
 
 // Note: we don't provide a method for querying the Safe's config. Query the
@@ -176,4 +430,22 @@ pub enum QueryMsg {
     /// Enumerate all votes in a proposal.
     #[returns(BTreeMap<Username, Vote>)]
     Votes { proposal_id: ProposalId },
+    /// Query a member's spending allowance.
+    #[returns(Option<Allowance>)]
+    Allowance { member: Username },
+    /// Enumerate all spending allowances.
+    #[returns(BTreeMap<Username, Allowance>)]
+    Allowances {
+        start_after: Option<Username>,
+        limit: Option<u32>,
+    },
+    /// Query a recurring transfer by ID.
+    #[returns(RecurringTransfer)]
+    RecurringTransfer { id: RecurringTransferId },
+    /// Enumerate all recurring transfers.
+    #[returns(BTreeMap<RecurringTransferId, RecurringTransfer>)]
+    RecurringTransfers {
+        start_after: Option<RecurringTransferId>,
+        limit: Option<u32>,
+    },
 }
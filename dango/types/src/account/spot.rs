@@ -1,4 +1,8 @@
-use {crate::auth::Nonce, grug::Coins, std::collections::BTreeSet};
+use {
+    crate::auth::Nonce,
+    grug::{Addr, Coins},
+    std::collections::BTreeSet,
+};
 
 /// Query messages for the spot account
 #[grug::derive(Serde, QueryRequest)]
@@ -6,6 +10,22 @@ pub enum QueryMsg {
     /// Query the most recent transaction nonces that have been recorded.
     #[returns(BTreeSet<Nonce>)]
     SeenNonces {},
+    /// Query the additional authenticator contracts registered by the account.
+    #[returns(BTreeSet<Addr>)]
+    Authenticators {},
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Register an additional contract to be consulted when authenticating
+    /// transactions sent by this account.
+    RegisterAuthenticator { authenticator: Addr },
+    /// Deregister an authenticator contract.
+    ///
+    /// A transaction containing only this message isn't subject to the
+    /// authenticators' vetoes, so that a faulty authenticator can always be
+    /// removed with the owner's signature.
+    DeregisterAuthenticator { authenticator: Addr },
 }
 
 #[grug::derive(Serde)]
@@ -4,7 +4,7 @@ use {
         account_factory::{Account, AccountIndex, AccountParams, AccountType, Username},
         auth::Key,
     },
-    grug::{Addr, Coins, Hash256, Op},
+    grug::{Addr, Coins, Hash256, Op, Timestamp},
     std::collections::BTreeMap,
 };
 
@@ -15,6 +15,8 @@ pub struct User {
     pub keys: BTreeMap<Hash256, Key>,
     /// Accounts associated with this user, indexes by addresses.
     pub accounts: BTreeMap<Addr, Account>,
+    /// The user's display alias, if set.
+    pub alias: Option<Username>,
 }
 
 #[grug::derive(Serde)]
@@ -34,17 +36,52 @@ pub enum ExecuteMsg {
     /// Create a new user, following an initial deposit.
     ///
     /// This is the second of the two-step user onboarding process.
+    ///
+    /// Funds that Warp holds for the new account's address are claimed. If the
+    /// key is a Secp256k1 key, so are those held for its Ethereum address.
+    /// Funds held for an Ethereum address can't be claimed with a Secp256r1
+    /// (passkey) key.
     RegisterUser {
         username: Username,
         key: Key,
         key_hash: Hash256,
     },
     /// Register a new account for an existing user.
+    ///
+    /// Funds that Warp holds for the new account's address are claimed.
     RegisterAccount { params: AccountParams },
     /// Configure a key for a username.
     ConfigureKey { key_hash: Hash256, key: Op<Key> },
     /// Update a Safe account's parameters.
     ConfigureSafe { updates: ParamUpdates },
+    /// Change the sender account's type, by migrating it to the code hash
+    /// associated with the new type.
+    ///
+    /// Only conversions between spot and margin accounts are supported. A
+    /// margin account can only be converted if it has no outstanding debt.
+    ChangeAccountType { account_type: AccountType },
+    /// Deregister the sender account from its owner.
+    ///
+    /// The account must hold no balance and have no outstanding debt. The
+    /// owner must have at least one other account.
+    ///
+    /// Once deleted, the account rejects all incoming transfers.
+    DeleteAccount {},
+    /// Transfer the sender's username to a different key set.
+    ///
+    /// All existing keys associated with the username are removed and replaced
+    /// with the given key. The username's accounts are unaffected.
+    TransferUsername { key_hash: Hash256, key: Key },
+    /// Set or unset a display alias for the sender's username.
+    ///
+    /// An alias must not collide with any existing username or alias.
+    SetAlias { alias: Option<Username> },
+    /// Set the time the sender Safe's next recurring transfer is due, or `None`
+    /// if it has none.
+    ///
+    /// The factory's cronjob executes the Safe's recurring transfers once due.
+    /// Called by the Safe itself whenever its recurring transfers change.
+    ScheduleRecurringTransfers { next: Option<Timestamp> },
 }
 
 #[grug::derive(Serde, QueryRequest)]
@@ -89,6 +126,17 @@ pub enum QueryMsg {
     /// Query a single user by username.
     #[returns(User)]
     User { username: Username },
+    /// Query the display alias of a username.
+    #[returns(Option<Username>)]
+    Alias { username: Username },
+    /// Find the username that a display alias belongs to.
+    #[returns(Username)]
+    UserByAlias { alias: Username },
+    /// Find the username that owns a single-signature account.
+    ///
+    /// Returns `None` if the account is a Safe, or if it has been deleted.
+    #[returns(Option<Username>)]
+    UsernameByAddress { address: Addr },
 }
 
 #[grug::derive(Serde)]
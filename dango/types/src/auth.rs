@@ -1,6 +1,8 @@
 use {
     crate::account_factory::Username,
-    grug::{Addr, Binary, ByteArray, Hash256, Message, NonEmpty, Timestamp},
+    grug::{
+        Addr, Binary, ByteArray, Denom, Empty, Hash256, Message, NonEmpty, Timestamp, Tx, Udec128,
+    },
 };
 
 /// A number that included in each transaction's sign doc for the purpose of
@@ -85,6 +87,39 @@ pub struct Metadata {
     pub nonce: Nonce,
     /// The expiration time of this transaction.
     pub expiry: Option<Timestamp>,
+    /// A referrer registered with the taxman, who is to receive a share of the
+    /// transaction's fee.
+    ///
+    /// Omitted from serialization if not provided, so that transactions without
+    /// a referrer are signed the same way as before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<Addr>,
+    /// The denom in which to pay the transaction's fee. Must be either the
+    /// taxman's fee denom or one of its alternative fee denoms. If not provided,
+    /// the taxman's fee denom is used.
+    ///
+    /// Omitted from serialization if not provided, same as the referrer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_denom: Option<Denom>,
+    /// An account that has granted the sender a fee allowance in the taxman,
+    /// who is to pay the transaction's fee instead of the sender.
+    ///
+    /// Omitted from serialization if not provided, same as the referrer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub granter: Option<Addr>,
+    /// The maximum fee per gas unit, including the tip, the sender is willing
+    /// to pay. The transaction is rejected if the base fee exceeds it. If not
+    /// provided, there is no maximum.
+    ///
+    /// Omitted from serialization if not provided, same as the referrer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_rate: Option<Udec128>,
+    /// The fee per gas unit the sender pays on top of the base fee, to have the
+    /// transaction prioritized. If not provided, zero.
+    ///
+    /// Omitted from serialization if not provided, same as the referrer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tip_rate: Option<Udec128>,
 }
 
 /// An Secp256r1 signature generated by a Passkey via Webauthn, along with
@@ -117,3 +152,20 @@ pub struct ClientData {
     #[serde(rename = "crossOrigin")]
     pub cross_origin: bool,
 }
+
+/// Query messages that an authenticator contract must respond to.
+///
+/// Spot and margin accounts may register additional authenticator contracts,
+/// which are consulted on top of the account's own signature verification.
+/// An authenticator vetoes a transaction by returning an error.
+#[grug::derive(Serde, QueryRequest)]
+pub enum AuthenticatorQueryMsg {
+    /// Called during the account's `authenticate` entry point, before any
+    /// message in the transaction is executed.
+    #[returns(Empty)]
+    Authenticate { account: Addr, tx: Tx },
+    /// Called during the account's `backrun` entry point, after all messages
+    /// in the transaction have been executed.
+    #[returns(Empty)]
+    Backrun { account: Addr, tx: Tx },
+}
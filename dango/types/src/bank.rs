@@ -1,6 +1,6 @@
 use {
-    grug::{Addr, Coins, Denom, LengthBounded, Part, Uint128},
-    std::collections::BTreeMap,
+    grug::{Addr, Coins, Denom, Duration, LengthBounded, Part, Timestamp, Uint128},
+    std::collections::{BTreeMap, BTreeSet},
 };
 
 #[grug::derive(Serde)]
@@ -11,6 +11,17 @@ pub struct InstantiateMsg {
     pub namespaces: BTreeMap<Part, Addr>,
     /// Initial denom metadatas.
     pub metadatas: BTreeMap<Denom, Metadata>,
+    /// Time after which an orphaned transfer is returned to its sender.
+    pub orphaned_transfer_expiry: Duration,
+}
+
+#[grug::derive(Serde)]
+pub struct MigrateMsg {
+    /// Time after which an orphaned transfer is returned to its sender.
+    ///
+    /// Orphaned transfers made before the migration expire this long after the
+    /// migration.
+    pub orphaned_transfer_expiry: Duration,
 }
 
 #[grug::derive(Serde)]
@@ -18,20 +29,40 @@ pub enum ExecuteMsg {
     /// Grant the control of a namespace to an account.
     /// Can only be called by the chain owner.
     ///
-    /// Currently, we don't support:
-    ///
-    /// 1. granting the top-level namespace;
-    /// 2. chain owner canceling a grant;
-    /// 3. namespace owner renouncing or transferring a grant;
-    /// 4. a namespace to have more than one owner;
-    /// 5. before-send hooks.
+    /// A namespace may have more than one owner. Granting a namespace that
+    /// already has owners adds the account as an additional owner.
     ///
-    /// We may implement some of these in the future.
+    /// Currently, we don't support granting the top-level namespace.
     GrantNamespace { namespace: Part, owner: Addr },
+    /// Revoke an account's control of a namespace.
+    /// Can only be called by the chain owner.
+    RevokeNamespace { namespace: Part, owner: Addr },
+    /// Transfer the sender's control of a namespace to another account.
+    /// Can only be called by an owner of the namespace.
+    TransferNamespace { namespace: Part, to: Addr },
+    /// Give up the sender's control of a namespace.
+    /// Can only be called by an owner of the namespace.
+    ///
+    /// If the last owner renounces, denoms under the namespace can no longer
+    /// be minted or burned, unless the chain owner grants it again.
+    RenounceNamespace { namespace: Part },
+    /// Set or unset the hook contract of a namespace.
+    /// Can only be called by the namespace owner.
+    ///
+    /// The hook is consulted on every transfer of denoms under the namespace,
+    /// and may veto the transfer or tag it. See [`HookQueryMsg`].
+    SetHook { namespace: Part, hook: Option<Addr> },
     /// Set metadata of a denom.
     /// Can only be called by the namespace owner, or the chain owner in case of
     /// top-level denoms.
     SetMetadata { denom: Denom, metadata: Metadata },
+    /// Start recording historical balances of a denom, such that they can be
+    /// queried with [`QueryMsg::BalanceAt`].
+    /// Can only be called by the namespace owner, or the chain owner in case of
+    /// top-level denoms.
+    ///
+    /// Once enabled, snapshots can't be disabled.
+    EnableSnapshots { denom: Denom },
     /// Mint tokens of the specified amount to a recipient.
     /// Can only be called by the namespace owner.
     Mint {
@@ -40,7 +71,8 @@ pub enum ExecuteMsg {
         amount: Uint128,
     },
     /// Burn tokens of the specified amount from an account.
-    /// Can only be called by the namespace owner.
+    /// Can only be called by the namespace owner, or by the taxman to burn
+    /// from its own balance.
     Burn {
         from: Addr,
         denom: Denom,
@@ -59,21 +91,61 @@ pub enum ExecuteMsg {
     },
     /// Transfer coins to multiple recipients at once.
     BatchTransfer(BTreeMap<Addr, Coins>),
+    /// Allow a spender to transfer up to the given amount of a denom from the
+    /// sender's account, optionally until an expiry time.
+    ///
+    /// Overwrites any existing allowance of the same spender and denom.
+    Approve {
+        spender: Addr,
+        denom: Denom,
+        amount: Uint128,
+        expires_at: Option<Timestamp>,
+    },
+    /// Remove a spender's allowance of a denom from the sender's account.
+    RevokeApproval { spender: Addr, denom: Denom },
+    /// Transfer coins from an account that has granted the sender an allowance.
+    /// The allowance is reduced by the amount transferred.
+    ///
+    /// Margin accounts, and accounts that have registered authenticators, can't
+    /// be transferred from, because their backrun checks would be skipped.
+    ///
+    /// The recipient is credited the same way as in a regular transfer: if it
+    /// doesn't exist, an orphaned transfer is recorded from the owner;
+    /// otherwise, the coins are relayed through the bank, so the recipient's
+    /// `receive` entry point is called with the bank as the sender.
+    TransferFrom {
+        owner: Addr,
+        to: Addr,
+        denom: Denom,
+        amount: Uint128,
+    },
     /// Retrieve funds sent to a non-existing recipient.
+    ///
+    /// If not recovered, the funds are returned to the sender automatically
+    /// once the orphaned transfer expires.
     RecoverTransfer { sender: Addr, recipient: Addr },
 }
 
 #[grug::derive(Serde, QueryRequest)]
 pub enum QueryMsg {
     /// Query the owner of a namespace.
+    ///
+    /// Errors if the namespace has more than one owner. Use `NamespaceOwners`
+    /// instead for such namespaces.
     #[returns(Addr)]
     Namespace { namespace: Part },
+    /// Query the owners of a namespace.
+    #[returns(BTreeSet<Addr>)]
+    NamespaceOwners { namespace: Part },
     /// Enumerate owners of all namespaces.
-    #[returns(BTreeMap<Part, Addr>)]
+    #[returns(BTreeMap<Part, BTreeSet<Addr>>)]
     Namespaces {
         start_after: Option<Part>,
         limit: Option<u32>,
     },
+    /// Find all namespaces that an account controls.
+    #[returns(BTreeSet<Part>)]
+    NamespacesByOwner { owner: Addr },
     /// Query the metadata of a denom.
     #[returns(Metadata)]
     Metadata { denom: Denom },
@@ -83,7 +155,115 @@ pub enum QueryMsg {
         start_after: Option<Denom>,
         limit: Option<u32>,
     },
-    // TODO: add query methods for orphaned transfers
+    /// Query the hook contract of a namespace.
+    #[returns(Option<Addr>)]
+    Hook { namespace: Part },
+    /// Enumerate hook contracts of all namespaces.
+    #[returns(BTreeMap<Part, Addr>)]
+    Hooks {
+        start_after: Option<Part>,
+        limit: Option<u32>,
+    },
+    /// Query an account's balance of a denom at the end of a past block.
+    /// The denom must have snapshots enabled since or before that block.
+    #[returns(Uint128)]
+    BalanceAt {
+        address: Addr,
+        denom: Denom,
+        height: u64,
+    },
+    /// Enumerate denoms that have snapshots enabled, and the block heights at
+    /// which they were enabled.
+    #[returns(BTreeMap<Denom, u64>)]
+    SnapshottedDenoms {
+        start_after: Option<Denom>,
+        limit: Option<u32>,
+    },
+    /// Query a spender's allowance of a denom from an owner's account.
+    #[returns(Option<Allowance>)]
+    Allowance {
+        owner: Addr,
+        spender: Addr,
+        denom: Denom,
+    },
+    /// Enumerate a spender's allowances of all denoms from an owner's account.
+    #[returns(BTreeMap<Denom, Allowance>)]
+    Allowances {
+        owner: Addr,
+        spender: Addr,
+        start_after: Option<Denom>,
+        limit: Option<u32>,
+    },
+    /// Query an orphaned transfer by sender and recipient.
+    #[returns(OrphanedTransfer)]
+    OrphanedTransfer { sender: Addr, recipient: Addr },
+    /// Enumerate orphaned transfers made by a sender, indexed by recipients.
+    #[returns(BTreeMap<Addr, OrphanedTransfer>)]
+    OrphanedTransfersBySender {
+        sender: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Enumerate orphaned transfers made to a recipient, indexed by senders.
+    #[returns(BTreeMap<Addr, OrphanedTransfer>)]
+    OrphanedTransfersByRecipient {
+        recipient: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+}
+
+/// The amount of a denom that a spender may transfer from an owner's account.
+#[grug::derive(Serde, Borsh)]
+pub struct Allowance {
+    pub amount: Uint128,
+    /// Time after which the allowance can no longer be used.
+    pub expires_at: Option<Timestamp>,
+}
+
+/// Funds sent to a recipient that didn't exist at the time of the transfer.
+#[grug::derive(Serde, Borsh)]
+pub struct OrphanedTransfer {
+    pub coins: Coins,
+    /// Time after which the funds are returned to the sender.
+    ///
+    /// Sending more funds to the same recipient resets the expiry.
+    pub expires_at: Timestamp,
+}
+
+/// Query messages that a namespace's hook contract must respond to.
+///
+/// The bank calls the hook with a bounded amount of gas, such that a faulty
+/// hook can't consume the gas of the entire transaction. A hook that errors or
+/// runs out of gas vetoes the transfer.
+///
+/// There is no after-receive hook. Hooks are queries, so that their gas can be
+/// bounded; Grug can't bound the gas of an executed submessage. A query made
+/// after the transfer would learn nothing a before-send hook can't already
+/// compute from the sender, recipient and coins, such as the recipient's
+/// balance after the transfer.
+#[grug::derive(Serde, QueryRequest)]
+pub enum HookQueryMsg {
+    /// Called before coins under the hook's namespace are transferred.
+    #[returns(BeforeSendResponse)]
+    BeforeSend { from: Addr, to: Addr, coins: Coins },
+}
+
+#[grug::derive(Serde)]
+pub struct BeforeSendResponse {
+    /// If provided, the bank emits a `transfer_tagged` event with this tag.
+    pub tag: Option<String>,
+}
+
+/// An event indicating a transfer has been tagged by a namespace's hook.
+#[grug::derive(Serde)]
+#[grug::event("transfer_tagged")]
+pub struct TransferTagged {
+    pub namespace: Part,
+    pub from: Addr,
+    pub to: Addr,
+    pub coins: Coins,
+    pub tag: String,
 }
 
 #[grug::derive(Serde, Borsh)]
@@ -94,3 +274,68 @@ pub struct Metadata {
     pub description: Option<LengthBounded<String, 1, 140>>,
     pub decimals: u8,
 }
+
+/// An event indicating a namespace has been granted to an account.
+#[grug::derive(Serde)]
+#[grug::event("namespace_granted")]
+pub struct NamespaceGranted {
+    pub namespace: Part,
+    pub owner: Addr,
+}
+
+/// An event indicating an account's control of a namespace has been revoked
+/// by the chain owner.
+#[grug::derive(Serde)]
+#[grug::event("namespace_revoked")]
+pub struct NamespaceRevoked {
+    pub namespace: Part,
+    pub owner: Addr,
+}
+
+/// An event indicating an account has transferred its control of a namespace
+/// to another account.
+#[grug::derive(Serde)]
+#[grug::event("namespace_transferred")]
+pub struct NamespaceTransferred {
+    pub namespace: Part,
+    pub from: Addr,
+    pub to: Addr,
+}
+
+/// An event indicating an account has given up its control of a namespace.
+#[grug::derive(Serde)]
+#[grug::event("namespace_renounced")]
+pub struct NamespaceRenounced {
+    pub namespace: Part,
+    pub owner: Addr,
+}
+
+/// An event indicating an expired orphaned transfer has been returned to its
+/// sender.
+#[grug::derive(Serde)]
+#[grug::event("orphaned_transfer_returned")]
+pub struct OrphanedTransferReturned {
+    pub sender: Addr,
+    pub recipient: Addr,
+    pub coins: Coins,
+}
+
+/// An event indicating an account has granted a spender an allowance.
+#[grug::derive(Serde)]
+#[grug::event("approved")]
+pub struct Approved {
+    pub owner: Addr,
+    pub spender: Addr,
+    pub denom: Denom,
+    pub amount: Uint128,
+    pub expires_at: Option<Timestamp>,
+}
+
+/// An event indicating an account has revoked a spender's allowance.
+#[grug::derive(Serde)]
+#[grug::event("approval_revoked")]
+pub struct ApprovalRevoked {
+    pub owner: Addr,
+    pub spender: Addr,
+    pub denom: Denom,
+}
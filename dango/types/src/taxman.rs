@@ -1,9 +1,82 @@
-use grug::{Addr, Denom, Udec128};
+use {
+    grug::{Addr, Coins, Denom, Duration, Timestamp, Udec128, Uint128},
+    std::collections::{BTreeMap, BTreeSet},
+};
 
 #[grug::derive(Serde, Borsh)]
 pub struct Config {
     pub fee_denom: Denom,
+    /// The fee per gas unit. If dynamic fees are enabled, this is the minimum
+    /// base fee.
     pub fee_rate: Udec128,
+    /// Denoms other than `fee_denom` that may be used to pay fees, if named in
+    /// the transaction's metadata. Fees paid in these denoms are converted from
+    /// `fee_denom` using prices from the oracle.
+    pub alt_fee_denoms: BTreeSet<Denom>,
+    /// Extra portion charged on top of fees paid in an alternative denom, to
+    /// account for price movements and inaccuracies of the oracle.
+    pub alt_fee_premium: Udec128,
+    /// Portion of a transaction's fee that goes to the referrer named in the
+    /// transaction's metadata, if any. The rest goes to the protocol.
+    pub referral_fee_rate: Udec128,
+    /// Parameters for adjusting the base fee according to the gas used in
+    /// recent blocks. If `None`, the base fee is fixed at `fee_rate`.
+    pub dynamic_fee: Option<DynamicFeeParams>,
+}
+
+#[grug::derive(Serde, Borsh)]
+pub struct DynamicFeeParams {
+    /// The amount of gas used per block, at which the base fee stays the same.
+    /// The base fee rises if a block uses more gas, and falls if less.
+    pub target_gas_per_block: u64,
+    /// The maximum portion by which the base fee changes from one block to the
+    /// next. Reached if a block uses zero gas, or twice the target or more.
+    pub max_change_rate: Udec128,
+    /// Where the base fee portion of the collected fees goes. Tips remain with
+    /// the taxman.
+    pub base_fee_destination: BaseFeeDestination,
+}
+
+#[grug::derive(Serde, Borsh)]
+pub enum BaseFeeDestination {
+    Burn,
+    Treasury(Addr),
+}
+
+/// Conditions under which a granter pays fees on behalf of a grantee.
+///
+/// Amounts are denominated in the taxman's fee denom.
+#[grug::derive(Serde, Borsh)]
+pub struct FeeAllowance {
+    /// The maximum amount of fees the granter pays over the grant's lifetime.
+    /// `None` means unlimited.
+    pub total_limit: Option<Uint128>,
+    /// The maximum amount of fees the granter pays within each period.
+    /// `None` means unlimited.
+    pub period_limit: Option<PeriodLimit>,
+    /// If provided, the grant only covers transactions that exclusively consist
+    /// of executing these contracts.
+    pub allowed_contracts: Option<BTreeSet<Addr>>,
+    /// Time after which the grant can no longer be used.
+    pub expires_at: Option<Timestamp>,
+}
+
+#[grug::derive(Serde, Borsh)]
+pub struct PeriodLimit {
+    pub period: Duration,
+    pub limit: Uint128,
+}
+
+/// A fee allowance, along with how much of it has been used.
+#[grug::derive(Serde, Borsh)]
+pub struct FeeGrant {
+    pub allowance: FeeAllowance,
+    /// Total amount of fees the granter has paid under this grant.
+    pub spent: Uint128,
+    /// Start time of the current period.
+    pub period_start: Timestamp,
+    /// Amount of fees the granter has paid in the current period.
+    pub period_spent: Uint128,
 }
 
 #[grug::derive(Serde)]
@@ -18,6 +91,30 @@ pub enum ExecuteMsg {
     Configure { new_cfg: Config },
     /// Forward protocol fee to the taxman.
     Pay { payer: Addr },
+    /// Allow an address to be named as referrer in transactions.
+    /// Can only be called by the chain's owner.
+    RegisterReferrer { referrer: Addr },
+    /// Disallow an address to be named as referrer in transactions.
+    /// Can only be called by the chain's owner.
+    ///
+    /// Fees already accrued to the referrer remain claimable.
+    DeregisterReferrer { referrer: Addr },
+    /// Withdraw the referral fees accrued to the sender.
+    ClaimReferralFees {},
+    /// Deposit the attached funds, from which the sender pays the fees of the
+    /// accounts it has granted fee allowances to.
+    DepositGrantFunds {},
+    /// Withdraw funds from the sender's grant deposit.
+    WithdrawGrantFunds { coins: Coins },
+    /// Allow an account to have its transaction fees paid from the sender's
+    /// grant deposit, by naming the sender as granter in the transaction's
+    /// metadata. Replaces the existing grant, if any.
+    GrantFeeAllowance {
+        grantee: Addr,
+        allowance: FeeAllowance,
+    },
+    /// Remove the fee allowance the sender has granted to an account.
+    RevokeFeeAllowance { grantee: Addr },
 }
 
 #[grug::derive(Serde, QueryRequest)]
@@ -25,4 +122,35 @@ pub enum QueryMsg {
     /// Query the fee configurations.
     #[returns(Config)]
     Config {},
+    /// Query the current base fee per gas unit.
+    #[returns(Udec128)]
+    BaseFee {},
+    /// Enumerate all registered referrers.
+    #[returns(BTreeSet<Addr>)]
+    Referrers {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Query the unclaimed referral fees accrued to a referrer.
+    #[returns(Coins)]
+    ReferralFees { referrer: Addr },
+    /// Enumerate the unclaimed referral fees accrued to all referrers.
+    #[returns(BTreeMap<Addr, Coins>)]
+    AllReferralFees {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Query the funds a granter has deposited for paying fees.
+    #[returns(Coins)]
+    GrantDeposit { granter: Addr },
+    /// Query the fee grant from a granter to a grantee.
+    #[returns(Option<FeeGrant>)]
+    FeeGrant { granter: Addr, grantee: Addr },
+    /// Enumerate the fee grants made by a granter.
+    #[returns(BTreeMap<Addr, FeeGrant>)]
+    FeeGrants {
+        granter: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
 }
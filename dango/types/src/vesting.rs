@@ -1,11 +1,16 @@
 use {
     grug::{
-        Addr, Duration, MathResult, MultiplyFraction, Number, NumberConst, Timestamp, Udec128,
-        Uint128,
+        Addr, Coins, Denom, Duration, MathResult, MultiplyFraction, MultiplyRatio, Number,
+        NumberConst, Timestamp, Udec128, Uint128,
+    },
+    std::{
+        cmp::min,
+        collections::{BTreeMap, BTreeSet},
     },
-    std::{cmp::min, collections::BTreeMap},
 };
 
+pub type PositionId = u32;
+
 #[grug::derive(Serde, Borsh)]
 pub struct Schedule {
     pub start_time: Timestamp,
@@ -34,10 +39,72 @@ impl Schedule {
     }
 }
 
+/// A step in a [`VestingSchedule::Steps`] schedule.
+#[grug::derive(Serde, Borsh)]
+pub struct Step {
+    /// Time at which the portion vests.
+    pub time: Timestamp,
+    /// Portion of the total amount that vests at this time.
+    pub portion: Udec128,
+}
+
+#[grug::derive(Serde, Borsh)]
+pub enum VestingSchedule {
+    /// After the cliff, tokens vest linearly through time.
+    Linear(Schedule),
+    /// After the cliff, an equal portion of tokens vests at the end of each
+    /// interval, until all periods have elapsed.
+    Periodic {
+        start_time: Timestamp,
+        cliff: Duration,
+        interval: Duration,
+        num_periods: u32,
+    },
+    /// Portions of tokens vest at specific times.
+    /// Times must be strictly increasing, and portions must sum up to one.
+    Steps(Vec<Step>),
+}
+
+impl VestingSchedule {
+    pub fn compute_claimable(&self, now: Timestamp, total: Uint128) -> MathResult<Uint128> {
+        match self {
+            VestingSchedule::Linear(schedule) => schedule.compute_claimable(now, total),
+            VestingSchedule::Periodic {
+                start_time,
+                cliff,
+                interval,
+                num_periods,
+            } => {
+                if now < *start_time + *cliff {
+                    return Ok(Uint128::ZERO);
+                }
+
+                let elapsed_periods = min(
+                    (now - *start_time).into_nanos() / interval.into_nanos(),
+                    *num_periods as u128,
+                );
+
+                total.checked_multiply_ratio_floor(
+                    Uint128::new(elapsed_periods),
+                    Uint128::new(*num_periods as u128),
+                )
+            },
+            VestingSchedule::Steps(steps) => {
+                let claim_percent = steps
+                    .iter()
+                    .take_while(|step| step.time <= now)
+                    .try_fold(Udec128::ZERO, |acc, step| acc.checked_add(step.portion))?;
+
+                total.checked_mul_dec_floor(claim_percent)
+            },
+        }
+    }
+}
+
 #[grug::derive(Serde, Borsh)]
 pub enum VestingStatus {
     /// Position is actively being vested.
-    Active(Schedule),
+    Active(VestingSchedule),
     /// Position has been terminated.
     ///
     /// The amount of tokens that have been vested at the time of termination is
@@ -56,26 +123,49 @@ impl VestingStatus {
 
 #[grug::derive(Serde, Borsh)]
 pub struct Position {
+    pub denom: Denom,
     pub vesting_status: VestingStatus,
     pub total: Uint128,
     pub claimed: Uint128,
 }
 
 impl Position {
+    /// Compute the amount of tokens that can be claimed now.
+    ///
+    /// The unlocking schedule only applies to the Dango token, so it should be
+    /// `None` for positions of other denoms.
     pub fn compute_claimable(
         &self,
         now: Timestamp,
-        unlocking_schedule: &Schedule,
+        unlocking_schedule: Option<&Schedule>,
     ) -> MathResult<Uint128> {
+        let mut claimable = self.vesting_status.compute_claimable(now, self.total)?;
+
         // The claimable amount is the minimum between the claimable amount
         // from the vesting status and the unlocking schedule
-        let claimable = min(
-            self.vesting_status.compute_claimable(now, self.total)?,
-            unlocking_schedule.compute_claimable(now, self.total)?,
-        );
+        if let Some(unlocking_schedule) = unlocking_schedule {
+            claimable = min(
+                claimable,
+                unlocking_schedule.compute_claimable(now, self.total)?,
+            );
+        }
 
         Ok(claimable.checked_sub(self.claimed).unwrap_or_default())
     }
+
+    /// Compute the amount of tokens still held in the position, i.e. not yet
+    /// claimed by the user or clawed back by the chain owner.
+    ///
+    /// This includes tokens that have vested but not been claimed yet. It is
+    /// the position's voting power.
+    pub fn compute_unclaimed(&self) -> MathResult<Uint128> {
+        let total = match &self.vesting_status {
+            VestingStatus::Active(_) => self.total,
+            VestingStatus::Terminated(vested) => *vested,
+        };
+
+        total.checked_sub(self.claimed)
+    }
 }
 
 #[grug::derive(Serde)]
@@ -84,6 +174,14 @@ pub struct PositionResponse {
     pub claimable: Uint128,
 }
 
+/// Vesting positions of a user, aggregated across positions.
+#[grug::derive(Serde)]
+pub struct UserSummary {
+    pub total: Coins,
+    pub claimed: Coins,
+    pub claimable: Coins,
+}
+
 #[grug::derive(Serde)]
 pub struct InstantiateMsg {
     pub unlocking_cliff: Duration,
@@ -94,27 +192,63 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Create a vesting position for a user with the given schedule.
     ///
-    /// Sender must be the chain owner, and attach a non-zero amount of Dango
-    /// token and nothing else.
-    Create { user: Addr, schedule: Schedule },
+    /// Sender must be the chain owner, and attach a non-zero amount of exactly
+    /// one denom. A user may have any number of positions.
+    Create {
+        user: Addr,
+        schedule: VestingSchedule,
+    },
     /// Terminate a user's vesting position.
     ///
     /// Sender must be the chain owner.
-    Terminate { user: Addr },
-    /// Claim the withdrawable amount from the vesting position.
+    Terminate { user: Addr, position_id: PositionId },
+    /// Claim the withdrawable amounts from all of the sender's vesting
+    /// positions.
     ///
     /// Sender must have a non-zero amount of claimable tokens.
     Claim {},
+    /// Delegate the voting power of the sender's vesting positions to another
+    /// address, or undelegate if `None`.
+    ///
+    /// Delegation isn't transitive: voting power delegated to an address isn't
+    /// further delegated when that address delegates its own.
+    ///
+    /// Sender must have at least one vesting position to delegate.
+    Delegate { delegatee: Option<Addr> },
 }
 
 #[grug::derive(Serde, QueryRequest)]
 pub enum QueryMsg {
-    /// Query a single vesting position by user address.
+    /// Query a single vesting position by user address and position ID.
     #[returns(PositionResponse)]
-    Position { user: Addr },
-    /// Enumerate all vesting positions.
-    #[returns(BTreeMap<Addr, PositionResponse>)]
+    Position { user: Addr, position_id: PositionId },
+    /// Enumerate all vesting positions of a user.
+    #[returns(BTreeMap<PositionId, PositionResponse>)]
     Positions {
+        user: Addr,
+        start_after: Option<PositionId>,
+        limit: Option<u32>,
+    },
+    /// Query the total, claimed, and claimable amounts of a user, aggregated
+    /// across all of the user's vesting positions.
+    #[returns(UserSummary)]
+    UserSummary { user: Addr },
+    /// Query the voting power of an address in a denom, which is the amount of
+    /// unclaimed tokens, vested or not, in its vesting positions, unless
+    /// delegated elsewhere, plus those delegated to it.
+    ///
+    /// This is the voting power at the current block; it isn't snapshotted.
+    /// Since re-delegating moves it immediately, it must not be used to tally
+    /// votes cast over multiple blocks, or the same tokens can vote twice.
+    #[returns(Uint128)]
+    VotingPower { address: Addr, denom: Denom },
+    /// Query the address a user has delegated its voting power to.
+    #[returns(Option<Addr>)]
+    Delegation { delegator: Addr },
+    /// Enumerate the users who have delegated their voting power to an address.
+    #[returns(BTreeSet<Addr>)]
+    Delegators {
+        delegatee: Addr,
         start_after: Option<Addr>,
         limit: Option<u32>,
     },
@@ -1,14 +1,15 @@
 use {
     anyhow::ensure,
     grug::{
-        Addr, Bytable, Denom, HexBinary, Inner, NextNumber, Part, PrevNumber, Uint128, Uint256,
+        Addr, Bytable, Coin, Coins, Denom, Duration, HexBinary, Inner, NextNumber, Part,
+        PrevNumber, Timestamp, Uint128, Uint256,
     },
     hyperlane_types::{
         mailbox::Domain,
         recipients::{RecipientMsg, RecipientQuery, RecipientQueryResponse},
         Addr32,
     },
-    std::sync::LazyLock,
+    std::{collections::BTreeMap, sync::LazyLock},
 };
 
 /// The namespace that synthetic tokens will be minted under. The bank contract
@@ -85,6 +86,91 @@ pub struct Route {
     pub fee: Uint128,
 }
 
+/// An underlying token of an alloyed token.
+#[grug::derive(Serde, Borsh)]
+pub struct AlloyComponent {
+    /// The maximum amount of the alloyed token that can be backed by this
+    /// underlying token. `None` means unlimited.
+    pub cap: Option<Uint128>,
+    /// The amount of the alloyed token currently backed by this underlying
+    /// token. Withdrawals through this underlying token's routes are limited
+    /// to this amount.
+    pub supply: Uint128,
+}
+
+/// A limit on the amount of tokens transferred through a route in one direction.
+#[grug::derive(Serde, Borsh)]
+pub struct RateLimit {
+    /// Length of the rolling window.
+    pub window: Duration,
+    /// The maximum amount that can be transferred within the rolling window.
+    pub cap: Uint128,
+    /// Transfers that would exceed the cap are queued, and can be released
+    /// after this delay, unless the route is paused in the meantime.
+    pub release_delay: Duration,
+}
+
+#[grug::derive(Serde, Borsh)]
+#[derive(Default)]
+pub struct RateLimits {
+    /// Limit on tokens received from the remote domain. `None` means unlimited.
+    pub inbound: Option<RateLimit>,
+    /// Limit on tokens sent to the remote domain. `None` means unlimited.
+    pub outbound: Option<RateLimit>,
+}
+
+/// Amounts transferred through a route within the current rolling windows.
+#[grug::derive(Serde)]
+pub struct RateLimitUsage {
+    pub inbound: Uint128,
+    pub outbound: Uint128,
+}
+
+pub type QueuedTransferId = u64;
+
+/// A transfer that exceeded its route's rate limit, or arrived while the route
+/// was paused, pending release.
+#[grug::derive(Serde, Borsh)]
+pub enum QueuedTransfer {
+    Inbound {
+        origin_domain: Domain,
+        /// The denom of the route the tokens were received through.
+        denom: Denom,
+        recipient: Addr32,
+        amount: Uint128,
+        release_at: Timestamp,
+    },
+    Outbound {
+        sender: Addr,
+        destination_domain: Domain,
+        /// The denom of the route the tokens are to be sent through.
+        route_denom: Denom,
+        /// The route at the time the transfer was queued. Only its fee is used;
+        /// the transfer is sent to the route's address at the time of release.
+        route: Route,
+        recipient: Addr32,
+        /// The token to be sent, after deducting the withdrawal fee. Held by
+        /// the Warp contract until released.
+        token: Coin,
+        metadata: HexBinary,
+        hook: Option<Addr>,
+        hook_metadata: Option<HexBinary>,
+        /// The mailbox's quote for dispatching the transfer at the time it was
+        /// queued, reserved from the hook fee attached by the sender.
+        hook_fee: Coins,
+        release_at: Timestamp,
+    },
+}
+
+impl QueuedTransfer {
+    pub fn release_at(&self) -> Timestamp {
+        match self {
+            QueuedTransfer::Inbound { release_at, .. }
+            | QueuedTransfer::Outbound { release_at, .. } => *release_at,
+        }
+    }
+}
+
 // --------------------------------- messages ----------------------------------
 
 #[grug::derive(Serde)]
@@ -97,18 +183,16 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Sends tokens to a remote domain.
     ///
-    /// Sender must attach exactly one token that is greater than the withdrawal
-    /// fee.
-    ///
-    /// ## Notes:
-    ///
-    /// We currently don't support:
+    /// Sender must attach one or more tokens, each greater than the withdrawal
+    /// fee of its route. One message is dispatched per token.
     ///
-    /// - sending more than one tokens at a time;
-    /// - specifying a custom hook and hook metadata.
+    /// The withdrawal fees are paid to the taxman. The hooks are paid from the
+    /// `hook_fee`, which must also be attached, according to the mailbox's
+    /// quote for each dispatch. The part of it that isn't needed is refunded.
     ///
-    /// These should be trivial to implement, but we just don't see a use for
-    /// them for now.
+    /// Specifying a `hook` replaces the mailbox's default hook, which is the
+    /// fee hook. In that case, the fee hook's fee schedule isn't charged; only
+    /// the required hook and the specified hook are paid.
     TransferRemote {
         destination_domain: Domain,
         // Note: This means the account the tokens are being sent to, NOT the
@@ -118,6 +202,14 @@ pub enum ExecuteMsg {
         // Note: This is the metadata be to included in the [`TokenMessage`](crate::warp::TokenMessage),
         // NOT the metadata for the hooks.
         metadata: Option<HexBinary>,
+        // The hook to be called by the mailbox after dispatching, instead of
+        // the default hook. Note that this skips the fee hook's fee schedule.
+        hook: Option<Addr>,
+        // The metadata for the hooks.
+        hook_metadata: Option<HexBinary>,
+        // The part of the attached funds to pay the hooks with, rather than to
+        // be sent. Defaults to none.
+        hook_fee: Option<Coins>,
     },
     /// Define the recipient contract and withdrawal fee rate for a token on a
     /// destination domain.
@@ -126,6 +218,61 @@ pub enum ExecuteMsg {
         destination_domain: Domain,
         route: Route,
     },
+    /// Set the rate limits of the route of a token to or from a remote domain.
+    SetRateLimits {
+        denom: Denom,
+        domain: Domain,
+        limits: RateLimits,
+    },
+    /// Pause or unpause the route of a token to or from a remote domain.
+    ///
+    /// While paused, sending through the route fails, tokens received through
+    /// the route are queued, and queued transfers can't be released.
+    SetRoutePaused {
+        denom: Denom,
+        domain: Domain,
+        paused: bool,
+    },
+    /// Set the ISM for verifying incoming messages, such as an aggregation or
+    /// routing ISM. `None` defers to the mailbox's default ISM.
+    SetIsm { ism: Option<Addr> },
+    /// Release a queued transfer whose release time has passed.
+    /// Can be called by anyone.
+    ///
+    /// The released transfer counts against the route's rate limit, even if it
+    /// exceeds it, delaying the transfers that follow.
+    ///
+    /// Outbound transfers are sent to the route's current address, and the
+    /// mailbox is quoted again. If the hook fee reserved when the transfer was
+    /// queued falls short of the quote, the caller must attach the difference.
+    /// The excess reserved hook fee is refunded to the sender, and the excess
+    /// attached funds to the caller.
+    ReleaseQueuedTransfer { id: QueuedTransferId },
+    /// Cancel a queued transfer.
+    ///
+    /// Inbound transfers are dropped. The tokens of outbound transfers,
+    /// including the withdrawal fee and the reserved hook fee, are refunded to
+    /// the sender.
+    ///
+    /// Can be called by the chain owner, or by the sender of an outbound
+    /// transfer.
+    CancelQueuedTransfer { id: QueuedTransferId },
+    /// Make a synthetic token an underlying token of an alloyed token, or
+    /// update its cap.
+    ///
+    /// Once set, receiving the underlying token mints the alloyed token 1:1,
+    /// as long as the cap isn't exceeded. Sending the alloyed token remotely
+    /// uses the route of any underlying token with sufficient supply.
+    SetAlloy {
+        underlying_denom: Denom,
+        alloyed_denom: Denom,
+        cap: Option<Uint128>,
+    },
+    /// Send the funds held for a recipient that didn't exist when they were
+    /// received to the given address.
+    ///
+    /// Can only be called by the account factory, when onboarding a new user.
+    ClaimDeposit { owner: Addr32, recipient: Addr },
     /// Required Hyperlane recipient interface.
     Recipient(RecipientMsg),
 }
@@ -147,6 +294,48 @@ pub enum QueryMsg {
         start_after: Option<QueryRoutesPageParam>,
         limit: Option<u32>,
     },
+    /// Query the rate limits of the route of a token to or from a remote domain.
+    #[returns(RateLimits)]
+    RateLimits { denom: Denom, domain: Domain },
+    /// Query the amounts transferred through a route within the current
+    /// rolling windows.
+    #[returns(RateLimitUsage)]
+    RateLimitUsage { denom: Denom, domain: Domain },
+    /// Query whether the route of a token to or from a remote domain is paused.
+    #[returns(bool)]
+    RoutePaused { denom: Denom, domain: Domain },
+    /// Query a queued transfer.
+    #[returns(QueuedTransfer)]
+    QueuedTransfer { id: QueuedTransferId },
+    /// Enumerate all queued transfers.
+    #[returns(BTreeMap<QueuedTransferId, QueuedTransfer>)]
+    QueuedTransfers {
+        start_after: Option<QueuedTransferId>,
+        limit: Option<u32>,
+    },
+    /// Query the alloyed token of an underlying token.
+    #[returns(Denom)]
+    Alloy { underlying_denom: Denom },
+    /// Enumerate all underlying tokens and their alloyed tokens.
+    #[returns(BTreeMap<Denom, Denom>)]
+    Alloys {
+        start_after: Option<Denom>,
+        limit: Option<u32>,
+    },
+    /// Query the underlying tokens of an alloyed token.
+    #[returns(BTreeMap<Denom, AlloyComponent>)]
+    AlloyComposition { alloyed_denom: Denom },
+    /// Query the funds held for a recipient that didn't exist when they were
+    /// received.
+    #[returns(Coins)]
+    Deposit { owner: Addr32 },
+    /// Enumerate all funds held for recipients that didn't exist when they
+    /// were received.
+    #[returns(BTreeMap<Addr32, Coins>)]
+    Deposits {
+        start_after: Option<Addr32>,
+        limit: Option<u32>,
+    },
     /// Required Hyperlane recipient interface.
     #[returns(RecipientQueryResponse)]
     Recipient(RecipientQuery),
@@ -186,3 +375,38 @@ pub struct Handle {
     pub token: Denom,
     pub amount: Uint128,
 }
+
+#[grug::derive(Serde)]
+#[grug::event("transfer_queued")]
+pub struct TransferQueued {
+    pub id: QueuedTransferId,
+    pub release_at: Timestamp,
+}
+
+#[grug::derive(Serde)]
+#[grug::event("queued_transfer_released")]
+pub struct QueuedTransferReleased {
+    pub id: QueuedTransferId,
+}
+
+#[grug::derive(Serde)]
+#[grug::event("queued_transfer_cancelled")]
+pub struct QueuedTransferCancelled {
+    pub id: QueuedTransferId,
+}
+
+#[grug::derive(Serde)]
+#[grug::event("deposit_held")]
+pub struct DepositHeld {
+    pub owner: Addr32,
+    pub token: Denom,
+    pub amount: Uint128,
+}
+
+#[grug::derive(Serde)]
+#[grug::event("deposit_claimed")]
+pub struct DepositClaimed {
+    pub owner: Addr32,
+    pub recipient: Addr,
+    pub coins: Coins,
+}
@@ -1,12 +1,18 @@
 use {
-    crate::{POSITIONS, UNLOCKING_SCHEDULE},
+    crate::{
+        DELEGATED_POWERS, DELEGATIONS, DELEGATORS, NEXT_POSITION_ID, POSITIONS, UNLOCKING_SCHEDULE,
+    },
     anyhow::{bail, ensure},
     dango_types::{
         constants::DANGO_DENOM,
-        vesting::{ExecuteMsg, InstantiateMsg, Position, Schedule, VestingStatus},
+        vesting::{
+            ExecuteMsg, InstantiateMsg, Position, PositionId, Schedule, VestingSchedule,
+            VestingStatus,
+        },
     },
     grug::{
-        Addr, Coin, IsZero, Message, MutableCtx, Number, NumberConst, QuerierExt, Response, Uint128,
+        Addr, Coin, Coins, Denom, IsZero, Message, MutableCtx, Number, NumberConst, Order,
+        QuerierExt, Response, StdResult, Storage, Udec128, Uint128,
     },
 };
 
@@ -28,20 +34,40 @@ pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> anyhow::Result<Respo
 pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
     match msg {
         ExecuteMsg::Create { user, schedule } => create(ctx, user, schedule),
-        ExecuteMsg::Terminate { user } => terminate(ctx, user),
+        ExecuteMsg::Terminate { user, position_id } => terminate(ctx, user, position_id),
         ExecuteMsg::Claim {} => claim(ctx),
+        ExecuteMsg::Delegate { delegatee } => delegate(ctx, delegatee),
     }
 }
 
-fn create(ctx: MutableCtx, user: Addr, schedule: Schedule) -> anyhow::Result<Response> {
+/// The unlocking schedule only applies to the Dango token. Tokens of other
+/// denoms are only subject to their vesting schedules.
+pub(crate) fn unlocking_schedule_for(
+    storage: &dyn Storage,
+    denom: &Denom,
+) -> StdResult<Option<Schedule>> {
+    if denom == &*DANGO_DENOM {
+        UNLOCKING_SCHEDULE.load(storage).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn create(ctx: MutableCtx, user: Addr, schedule: VestingSchedule) -> anyhow::Result<Response> {
     ensure!(
         ctx.sender == ctx.querier.query_owner()?,
         "you don't have the right, O you don't have the right"
     );
 
-    let coin = ctx.funds.into_one_coin_of_denom(&DANGO_DENOM)?;
+    validate_schedule(&schedule)?;
+
+    let coin = ctx.funds.into_one_coin()?;
+    let (position_id, _) = NEXT_POSITION_ID.increment(ctx.storage)?;
+
+    increase_delegated_power(ctx.storage, user, &coin.denom, coin.amount)?;
 
-    POSITIONS.save(ctx.storage, user, &Position {
+    POSITIONS.save(ctx.storage, (user, position_id), &Position {
+        denom: coin.denom,
         vesting_status: VestingStatus::Active(schedule),
         total: coin.amount,
         claimed: Uint128::ZERO,
@@ -50,7 +76,41 @@ fn create(ctx: MutableCtx, user: Addr, schedule: Schedule) -> anyhow::Result<Res
     Ok(Response::new())
 }
 
-fn terminate(ctx: MutableCtx, user: Addr) -> anyhow::Result<Response> {
+fn validate_schedule(schedule: &VestingSchedule) -> anyhow::Result<()> {
+    match schedule {
+        VestingSchedule::Linear(_) => {},
+        VestingSchedule::Periodic {
+            interval,
+            num_periods,
+            ..
+        } => {
+            ensure!(interval.is_non_zero(), "interval can't be zero");
+            ensure!(*num_periods > 0, "number of periods can't be zero");
+        },
+        VestingSchedule::Steps(steps) => {
+            ensure!(!steps.is_empty(), "steps can't be empty");
+
+            ensure!(
+                steps.windows(2).all(|pair| pair[0].time < pair[1].time),
+                "step times must be strictly increasing"
+            );
+
+            let mut sum = Udec128::ZERO;
+
+            for step in steps {
+                ensure!(step.portion.is_non_zero(), "step portion can't be zero");
+
+                sum.checked_add_assign(step.portion)?;
+            }
+
+            ensure!(sum == Udec128::ONE, "step portions must sum up to one");
+        },
+    }
+
+    Ok(())
+}
+
+fn terminate(ctx: MutableCtx, user: Addr, position_id: PositionId) -> anyhow::Result<Response> {
     let owner = ctx.querier.query_owner()?;
 
     ensure!(
@@ -58,7 +118,7 @@ fn terminate(ctx: MutableCtx, user: Addr) -> anyhow::Result<Response> {
         "you don't have the right, O you don't have the right"
     );
 
-    let mut position = POSITIONS.load(ctx.storage, user)?;
+    let mut position = POSITIONS.load(ctx.storage, (user, position_id))?;
 
     let vested = if let VestingStatus::Active(schedule) = &position.vesting_status {
         schedule.compute_claimable(ctx.block.timestamp, position.total)?
@@ -70,34 +130,149 @@ fn terminate(ctx: MutableCtx, user: Addr) -> anyhow::Result<Response> {
 
     // Any unvested tokens is clawed back.
     let refund = position.total.checked_sub(vested)?;
+
+    decrease_delegated_power(ctx.storage, user, &position.denom, refund)?;
+
     let refund_msg = if refund.is_non_zero() {
         Some(Message::transfer(
             owner,
-            Coin::new(DANGO_DENOM.clone(), refund)?,
+            Coin::new(position.denom.clone(), refund)?,
         )?)
     } else {
         None
     };
 
-    POSITIONS.save(ctx.storage, user, &position)?;
+    POSITIONS.save(ctx.storage, (user, position_id), &position)?;
 
     Ok(Response::new().may_add_message(refund_msg))
 }
 
 fn claim(ctx: MutableCtx) -> anyhow::Result<Response> {
-    let unlocking_schedule = UNLOCKING_SCHEDULE.load(ctx.storage)?;
-    let mut position = POSITIONS.load(ctx.storage, ctx.sender)?;
+    let positions = POSITIONS
+        .prefix(ctx.sender)
+        .range(ctx.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut claimed = Coins::new();
+
+    for (position_id, mut position) in positions {
+        let unlocking_schedule = unlocking_schedule_for(ctx.storage, &position.denom)?;
+        let claimable =
+            position.compute_claimable(ctx.block.timestamp, unlocking_schedule.as_ref())?;
+
+        if claimable.is_zero() {
+            continue;
+        }
 
-    let claimable = position.compute_claimable(ctx.block.timestamp, &unlocking_schedule)?;
+        position.claimed.checked_add_assign(claimable)?;
 
-    ensure!(claimable.is_non_zero(), "nothing to claim");
+        decrease_delegated_power(ctx.storage, ctx.sender, &position.denom, claimable)?;
 
-    position.claimed.checked_add_assign(claimable)?;
+        claimed.insert(Coin::new(position.denom.clone(), claimable)?)?;
 
-    POSITIONS.save(ctx.storage, ctx.sender, &position)?;
+        POSITIONS.save(ctx.storage, (ctx.sender, position_id), &position)?;
+    }
+
+    ensure!(!claimed.is_empty(), "nothing to claim");
+
+    Ok(Response::new().add_message(Message::transfer(ctx.sender, claimed)?))
+}
+
+fn delegate(ctx: MutableCtx, delegatee: Option<Addr>) -> anyhow::Result<Response> {
+    let unclaimed = unclaimed_amounts(ctx.storage, ctx.sender)?;
+
+    // Move the sender's voting power away from the old delegatee, if any.
+    for coin in &unclaimed {
+        decrease_delegated_power(ctx.storage, ctx.sender, coin.denom, *coin.amount)?;
+    }
+
+    if let Some(old_delegatee) = DELEGATIONS.may_take(ctx.storage, ctx.sender)? {
+        DELEGATORS.remove(ctx.storage, (old_delegatee, ctx.sender));
+    }
+
+    if let Some(delegatee) = delegatee {
+        ensure!(delegatee != ctx.sender, "can't delegate to self");
+
+        ensure!(
+            POSITIONS
+                .prefix(ctx.sender)
+                .keys(ctx.storage, None, None, Order::Ascending)
+                .next()
+                .is_some(),
+            "can't delegate without a vesting position"
+        );
+
+        DELEGATIONS.save(ctx.storage, ctx.sender, &delegatee)?;
+        DELEGATORS.insert(ctx.storage, (delegatee, ctx.sender))?;
+
+        for coin in &unclaimed {
+            increase_delegated_power(ctx.storage, ctx.sender, coin.denom, *coin.amount)?;
+        }
+    }
+
+    Ok(Response::new())
+}
+
+/// Sum up the unclaimed amounts in a user's vesting positions, by denom.
+pub(crate) fn unclaimed_amounts(storage: &dyn Storage, user: Addr) -> StdResult<Coins> {
+    let mut unclaimed = Coins::new();
+
+    for res in POSITIONS
+        .prefix(user)
+        .values(storage, None, None, Order::Ascending)
+    {
+        let position = res?;
+
+        unclaimed.insert(Coin::new(position.denom, position.compute_unclaimed()?)?)?;
+    }
+
+    Ok(unclaimed)
+}
+
+/// If the user has delegated its voting power, add the amount to its
+/// delegatee's delegated power.
+fn increase_delegated_power(
+    storage: &mut dyn Storage,
+    delegator: Addr,
+    denom: &Denom,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    if let Some(delegatee) = DELEGATIONS.may_load(storage, delegator)? {
+        DELEGATED_POWERS.may_update(storage, (delegatee, denom), |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_add(amount)?)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// If the user has delegated its voting power, deduct the amount from its
+/// delegatee's delegated power.
+fn decrease_delegated_power(
+    storage: &mut dyn Storage,
+    delegator: Addr,
+    denom: &Denom,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    if let Some(delegatee) = DELEGATIONS.may_load(storage, delegator)? {
+        let power = DELEGATED_POWERS
+            .load(storage, (delegatee, denom))?
+            .checked_sub(amount)?;
+
+        if power.is_zero() {
+            DELEGATED_POWERS.remove(storage, (delegatee, denom));
+        } else {
+            DELEGATED_POWERS.save(storage, (delegatee, denom), &power)?;
+        }
+    }
 
-    Ok(Response::new().add_message(Message::transfer(
-        ctx.sender,
-        Coin::new(DANGO_DENOM.clone(), claimable)?,
-    )?))
+    Ok(())
 }
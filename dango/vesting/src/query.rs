@@ -1,8 +1,14 @@
 use {
-    crate::{POSITIONS, UNLOCKING_SCHEDULE},
-    dango_types::vesting::{PositionResponse, QueryMsg},
-    grug::{Addr, Bound, ImmutableCtx, Json, JsonSerExt, Order, StdResult},
-    std::collections::BTreeMap,
+    crate::{
+        execute::{unclaimed_amounts, unlocking_schedule_for},
+        DELEGATED_POWERS, DELEGATIONS, DELEGATORS, POSITIONS,
+    },
+    dango_types::vesting::{Position, PositionId, PositionResponse, QueryMsg, UserSummary},
+    grug::{
+        Addr, Bound, Coin, Coins, Denom, ImmutableCtx, Json, JsonSerExt, Number, NumberConst,
+        Order, StdResult, Uint128,
+    },
+    std::collections::{BTreeMap, BTreeSet},
 };
 
 const DEFAULT_PAGE_LIMIT: u32 = 30;
@@ -10,48 +16,143 @@ const DEFAULT_PAGE_LIMIT: u32 = 30;
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
     match msg {
-        QueryMsg::Position { user } => {
-            let res = query_position(ctx, user)?;
+        QueryMsg::Position { user, position_id } => {
+            let res = query_position(ctx, user, position_id)?;
             res.to_json_value()
         },
-        QueryMsg::Positions { start_after, limit } => {
-            let res = query_positions(ctx, start_after, limit)?;
+        QueryMsg::Positions {
+            user,
+            start_after,
+            limit,
+        } => {
+            let res = query_positions(ctx, user, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::UserSummary { user } => {
+            let res = query_user_summary(ctx, user)?;
+            res.to_json_value()
+        },
+        QueryMsg::VotingPower { address, denom } => {
+            let res = query_voting_power(ctx, address, denom)?;
+            res.to_json_value()
+        },
+        QueryMsg::Delegation { delegator } => {
+            let res = query_delegation(ctx, delegator)?;
+            res.to_json_value()
+        },
+        QueryMsg::Delegators {
+            delegatee,
+            start_after,
+            limit,
+        } => {
+            let res = query_delegators(ctx, delegatee, start_after, limit)?;
             res.to_json_value()
         },
     }
 }
 
-fn query_position(ctx: ImmutableCtx, user: Addr) -> StdResult<PositionResponse> {
-    let unlocking_schedule = UNLOCKING_SCHEDULE.load(ctx.storage)?;
-    let position = POSITIONS.load(ctx.storage, user)?;
-    let claimable = position.compute_claimable(ctx.block.timestamp, &unlocking_schedule)?;
+fn query_position(
+    ctx: ImmutableCtx,
+    user: Addr,
+    position_id: PositionId,
+) -> StdResult<PositionResponse> {
+    let position = POSITIONS.load(ctx.storage, (user, position_id))?;
 
-    Ok(PositionResponse {
-        position,
-        claimable,
-    })
+    position_response(&ctx, position)
 }
 
 fn query_positions(
     ctx: ImmutableCtx,
-    start_after: Option<Addr>,
+    user: Addr,
+    start_after: Option<PositionId>,
     limit: Option<u32>,
-) -> StdResult<BTreeMap<Addr, PositionResponse>> {
+) -> StdResult<BTreeMap<PositionId, PositionResponse>> {
     let start = start_after.map(Bound::Exclusive);
     let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
-    let unlocking_schedule = UNLOCKING_SCHEDULE.load(ctx.storage)?;
 
     POSITIONS
+        .prefix(user)
         .range(ctx.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|res| {
-            let (user, position) = res?;
-            let claimable = position.compute_claimable(ctx.block.timestamp, &unlocking_schedule)?;
+            let (position_id, position) = res?;
+            let res = position_response(&ctx, position)?;
 
-            Ok((user, PositionResponse {
-                position,
-                claimable,
-            }))
+            Ok((position_id, res))
         })
         .collect()
 }
+
+fn query_user_summary(ctx: ImmutableCtx, user: Addr) -> StdResult<UserSummary> {
+    let mut summary = UserSummary {
+        total: Coins::new(),
+        claimed: Coins::new(),
+        claimable: Coins::new(),
+    };
+
+    for res in POSITIONS
+        .prefix(user)
+        .values(ctx.storage, None, None, Order::Ascending)
+    {
+        let PositionResponse {
+            position,
+            claimable,
+        } = position_response(&ctx, res?)?;
+
+        summary
+            .total
+            .insert(Coin::new(position.denom.clone(), position.total)?)?;
+        summary
+            .claimed
+            .insert(Coin::new(position.denom.clone(), position.claimed)?)?;
+        summary
+            .claimable
+            .insert(Coin::new(position.denom, claimable)?)?;
+    }
+
+    Ok(summary)
+}
+
+fn query_voting_power(ctx: ImmutableCtx, address: Addr, denom: Denom) -> StdResult<Uint128> {
+    let mut voting_power = DELEGATED_POWERS
+        .may_load(ctx.storage, (address, &denom))?
+        .unwrap_or_default();
+
+    // The address' own positions count, unless it has delegated them elsewhere.
+    if !DELEGATIONS.has(ctx.storage, address) {
+        voting_power
+            .checked_add_assign(unclaimed_amounts(ctx.storage, address)?.amount_of(&denom))?;
+    }
+
+    Ok(voting_power)
+}
+
+fn query_delegation(ctx: ImmutableCtx, delegator: Addr) -> StdResult<Option<Addr>> {
+    DELEGATIONS.may_load(ctx.storage, delegator)
+}
+
+fn query_delegators(
+    ctx: ImmutableCtx,
+    delegatee: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BTreeSet<Addr>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT) as usize;
+
+    DELEGATORS
+        .prefix(delegatee)
+        .keys(ctx.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+fn position_response(ctx: &ImmutableCtx, position: Position) -> StdResult<PositionResponse> {
+    let unlocking_schedule = unlocking_schedule_for(ctx.storage, &position.denom)?;
+    let claimable = position.compute_claimable(ctx.block.timestamp, unlocking_schedule.as_ref())?;
+
+    Ok(PositionResponse {
+        position,
+        claimable,
+    })
+}
@@ -1,8 +1,19 @@
 use {
-    dango_types::vesting::{Position, Schedule},
-    grug::{Addr, Item, Map},
+    dango_types::vesting::{Position, PositionId, Schedule},
+    grug::{Addr, Counter, Denom, Item, Map, Set, Uint128},
 };
 
 pub const UNLOCKING_SCHEDULE: Item<Schedule> = Item::new("unlocking_schedule");
 
-pub const POSITIONS: Map<Addr, Position> = Map::new("position");
+pub const NEXT_POSITION_ID: Counter<PositionId> = Counter::new("next_position_id", 1, 1);
+
+pub const POSITIONS: Map<(Addr, PositionId), Position> = Map::new("position");
+
+// delegator -> delegatee
+pub const DELEGATIONS: Map<Addr, Addr> = Map::new("delegation");
+
+// (delegatee, delegator)
+pub const DELEGATORS: Set<(Addr, Addr)> = Set::new("delegator");
+
+// (delegatee, denom) -> unclaimed amount in the positions of its delegators
+pub const DELEGATED_POWERS: Map<(Addr, &Denom), Uint128> = Map::new("delegated_power");
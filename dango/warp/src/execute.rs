@@ -1,15 +1,23 @@
 use {
-    crate::{MAILBOX, REVERSE_ROUTES, ROUTES},
-    anyhow::{anyhow, ensure},
+    crate::{
+        Direction, WindowUsage, ALLOYS, ALLOY_COMPONENTS, DEPOSITS, INBOUND_USAGES, ISM, MAILBOX,
+        NEXT_QUEUED_TRANSFER_ID, OUTBOUND_USAGES, PAUSED_ROUTES, QUEUED_TRANSFERS, RATE_LIMITS,
+        REVERSE_ROUTES, ROUTES,
+    },
+    anyhow::{anyhow, bail, ensure},
     dango_types::{
-        bank,
+        bank, taxman,
         warp::{
-            ExecuteMsg, Handle, InstantiateMsg, Route, TokenMessage, TransferRemote, NAMESPACE,
+            AlloyComponent, DepositClaimed, DepositHeld, ExecuteMsg, Handle, InstantiateMsg,
+            QueuedTransfer, QueuedTransferCancelled, QueuedTransferId, QueuedTransferReleased,
+            RateLimits, Route, TokenMessage, TransferQueued, TransferRemote, ALLOY_NAMESPACE,
+            NAMESPACE,
         },
+        DangoQuerier,
     },
     grug::{
-        Coin, Coins, Denom, HexBinary, IsZero, Message, MutableCtx, Number, QuerierExt, Response,
-        StdResult,
+        Addr, Coin, Coins, Denom, HexBinary, IsZero, Message, MutableCtx, Number, Order,
+        QuerierExt, Response, StdError, StdResult, Storage, Timestamp, Uint128,
     },
     hyperlane_types::{
         mailbox::{self, Domain},
@@ -32,12 +40,42 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             destination_domain,
             recipient,
             metadata,
-        } => transfer_remote(ctx, destination_domain, recipient, metadata),
+            hook,
+            hook_metadata,
+            hook_fee,
+        } => transfer_remote(
+            ctx,
+            destination_domain,
+            recipient,
+            metadata,
+            hook,
+            hook_metadata,
+            hook_fee,
+        ),
         ExecuteMsg::SetRoute {
             denom,
             destination_domain,
             route,
         } => set_route(ctx, denom, destination_domain, route),
+        ExecuteMsg::SetAlloy {
+            underlying_denom,
+            alloyed_denom,
+            cap,
+        } => set_alloy(ctx, underlying_denom, alloyed_denom, cap),
+        ExecuteMsg::SetRateLimits {
+            denom,
+            domain,
+            limits,
+        } => set_rate_limits(ctx, denom, domain, limits),
+        ExecuteMsg::SetRoutePaused {
+            denom,
+            domain,
+            paused,
+        } => set_route_paused(ctx, denom, domain, paused),
+        ExecuteMsg::SetIsm { ism } => set_ism(ctx, ism),
+        ExecuteMsg::ReleaseQueuedTransfer { id } => release_queued_transfer(ctx, id),
+        ExecuteMsg::CancelQueuedTransfer { id } => cancel_queued_transfer(ctx, id),
+        ExecuteMsg::ClaimDeposit { owner, recipient } => claim_deposit(ctx, owner, recipient),
         ExecuteMsg::Recipient(RecipientMsg::Handle {
             origin_domain,
             sender,
@@ -64,33 +102,572 @@ fn set_route(
     Ok(Response::new())
 }
 
+#[inline]
+fn set_alloy(
+    ctx: MutableCtx,
+    underlying_denom: Denom,
+    alloyed_denom: Denom,
+    cap: Option<Uint128>,
+) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only chain owner can call `set_alloy`"
+    );
+
+    ensure!(
+        underlying_denom.namespace() == Some(&NAMESPACE),
+        "underlying denom must be synthetic: `{underlying_denom}`"
+    );
+
+    ensure!(
+        alloyed_denom.namespace() == Some(&ALLOY_NAMESPACE),
+        "alloyed denom must be under the alloy namespace: `{alloyed_denom}`"
+    );
+
+    if let Some(existing) = ALLOYS.may_load(ctx.storage, &underlying_denom)? {
+        ensure!(
+            existing == alloyed_denom,
+            "`{underlying_denom}` is already alloyed into `{existing}`"
+        );
+    }
+
+    ALLOYS.save(ctx.storage, &underlying_denom, &alloyed_denom)?;
+    ALLOY_COMPONENTS.may_update(
+        ctx.storage,
+        (&alloyed_denom, &underlying_denom),
+        |maybe_component| {
+            let supply = maybe_component
+                .map(|component| component.supply)
+                .unwrap_or_default();
+            Ok::<_, StdError>(AlloyComponent { cap, supply })
+        },
+    )?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn set_rate_limits(
+    ctx: MutableCtx,
+    denom: Denom,
+    domain: Domain,
+    limits: RateLimits,
+) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only chain owner can call `set_rate_limits`"
+    );
+
+    for limit in limits.inbound.iter().chain(&limits.outbound) {
+        ensure!(
+            limit.window.into_nanos() > 0,
+            "rate limit window can't be zero"
+        );
+    }
+
+    RATE_LIMITS.save(ctx.storage, (&denom, domain), &limits)?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn set_route_paused(
+    ctx: MutableCtx,
+    denom: Denom,
+    domain: Domain,
+    paused: bool,
+) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only chain owner can call `set_route_paused`"
+    );
+
+    if paused {
+        PAUSED_ROUTES.insert(ctx.storage, (&denom, domain))?;
+    } else {
+        PAUSED_ROUTES.remove(ctx.storage, (&denom, domain));
+    }
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn set_ism(ctx: MutableCtx, ism: Option<Addr>) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only chain owner can call `set_ism`"
+    );
+
+    if let Some(ism) = ism {
+        ensure!(
+            ctx.querier.query_contract(ism).is_ok(),
+            "ISM contract {ism} doesn't exist"
+        );
+
+        ISM.save(ctx.storage, &ism)?;
+    } else {
+        ISM.remove(ctx.storage);
+    }
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn claim_deposit(ctx: MutableCtx, owner: Addr32, recipient: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_account_factory()?,
+        "only account factory can call `claim_deposit`"
+    );
+
+    let coins = DEPOSITS.take(ctx.storage, owner)?;
+
+    Ok(Response::new()
+        .add_message(Message::transfer(recipient, coins.clone())?)
+        .add_event(DepositClaimed {
+            owner,
+            recipient,
+            coins,
+        })?)
+}
+
+#[inline]
+fn release_queued_transfer(ctx: MutableCtx, id: QueuedTransferId) -> anyhow::Result<Response> {
+    let transfer = QUEUED_TRANSFERS.load(ctx.storage, id)?;
+
+    ensure!(
+        ctx.block.timestamp >= transfer.release_at(),
+        "queued transfer {id} is not yet releasable"
+    );
+
+    let (denom, domain) = match &transfer {
+        QueuedTransfer::Inbound {
+            denom,
+            origin_domain,
+            ..
+        } => (denom, *origin_domain),
+        QueuedTransfer::Outbound {
+            route_denom,
+            destination_domain,
+            ..
+        } => (route_denom, *destination_domain),
+    };
+
+    ensure!(
+        !PAUSED_ROUTES.has(ctx.storage, (denom, domain)),
+        "route of `{denom}` to or from domain {domain} is paused"
+    );
+
+    QUEUED_TRANSFERS.remove(ctx.storage, id);
+
+    let response = match transfer {
+        QueuedTransfer::Inbound {
+            origin_domain,
+            denom,
+            recipient,
+            amount,
+            ..
+        } => {
+            ensure!(
+                ctx.funds.is_empty(),
+                "releasing an inbound transfer doesn't require funds"
+            );
+
+            record_released_transfer(
+                ctx.storage,
+                ctx.block.timestamp,
+                Direction::Inbound,
+                &denom,
+                origin_domain,
+                amount,
+            )?;
+
+            receive(ctx, recipient, denom, amount)?
+        },
+        QueuedTransfer::Outbound {
+            sender,
+            destination_domain,
+            route,
+            recipient,
+            token,
+            metadata,
+            hook,
+            hook_metadata,
+            hook_fee,
+            route_denom,
+            ..
+        } => {
+            record_released_transfer(
+                ctx.storage,
+                ctx.block.timestamp,
+                Direction::Outbound,
+                &route_denom,
+                destination_domain,
+                token.amount,
+            )?;
+
+            // Send to the route's current address, in case it has changed since
+            // the transfer was queued. The withdrawal fee was already deducted
+            // at the time, so it's kept as is.
+            let route = Route {
+                address: ROUTES
+                    .load(ctx.storage, (&route_denom, destination_domain))?
+                    .address,
+                fee: route.fee,
+            };
+
+            // The hook fee reserved at the time may no longer match the
+            // mailbox's quote. Cover any shortfall with the funds attached by
+            // the releaser, and refund what's left over.
+            let quote = quote_dispatch(
+                &ctx,
+                destination_domain,
+                &route,
+                recipient,
+                token.amount,
+                &metadata,
+                hook,
+                &hook_metadata,
+            )?;
+
+            let mut excess = hook_fee;
+            let shortfall = excess.saturating_deduct_many(quote.clone())?;
+            let mut change = ctx.funds.clone();
+
+            change.deduct_many(shortfall.clone()).map_err(|_| {
+                anyhow!(
+                    "attached funds don't cover the hook fee shortfall: {} < {}",
+                    ctx.funds,
+                    shortfall
+                )
+            })?;
+
+            send(
+                &ctx,
+                Response::new(),
+                sender,
+                destination_domain,
+                route,
+                recipient,
+                token,
+                metadata,
+                hook,
+                hook_metadata,
+                quote,
+            )?
+            .may_add_message(if excess.is_non_empty() {
+                Some(Message::transfer(sender, excess)?)
+            } else {
+                None
+            })
+            .may_add_message(if change.is_non_empty() {
+                Some(Message::transfer(ctx.sender, change)?)
+            } else {
+                None
+            })
+        },
+    };
+
+    Ok(response.add_event(QueuedTransferReleased { id })?)
+}
+
+#[inline]
+fn cancel_queued_transfer(ctx: MutableCtx, id: QueuedTransferId) -> anyhow::Result<Response> {
+    let transfer = QUEUED_TRANSFERS.take(ctx.storage, id)?;
+
+    // The chain owner can cancel any transfer, while the sender of an outbound
+    // transfer can cancel their own.
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?
+            || matches!(
+                transfer,
+                QueuedTransfer::Outbound { sender, .. } if sender == ctx.sender
+            ),
+        "only chain owner or the sender can call `cancel_queued_transfer`"
+    );
+
+    let refund_msg = match transfer {
+        // The tokens haven't been minted or released yet, so there's nothing
+        // to undo.
+        QueuedTransfer::Inbound { .. } => None,
+        QueuedTransfer::Outbound {
+            sender,
+            route_denom,
+            route,
+            token,
+            hook_fee,
+            ..
+        } => {
+            // If the token is alloyed, give back the underlying token's supply
+            // taken when the transfer was queued.
+            if token.denom != route_denom {
+                ALLOY_COMPONENTS.update(
+                    ctx.storage,
+                    (&token.denom, &route_denom),
+                    |mut component| -> StdResult<_> {
+                        component.supply.checked_add_assign(token.amount)?;
+                        Ok(component)
+                    },
+                )?;
+            }
+
+            let mut refund = hook_fee;
+            refund.insert(Coin::new(
+                token.denom,
+                token.amount.checked_add(route.fee)?,
+            )?)?;
+
+            Some(Message::transfer(sender, refund)?)
+        },
+    };
+
+    Ok(Response::new()
+        .may_add_message(refund_msg)
+        .add_event(QueuedTransferCancelled { id })?)
+}
+
 #[inline]
 fn transfer_remote(
     ctx: MutableCtx,
     destination_domain: Domain,
     recipient: Addr32,
     metadata: Option<HexBinary>,
+    hook: Option<Addr>,
+    hook_metadata: Option<HexBinary>,
+    hook_fee: Option<Coins>,
 ) -> anyhow::Result<Response> {
-    // Sender must attach exactly one token.
-    let mut token = ctx.funds.into_one_coin()?;
+    let metadata = metadata.unwrap_or_default();
 
-    // The token must have a route set.
-    let route = ROUTES.load(ctx.storage, (&token.denom, destination_domain))?;
+    // Set aside the funds for the hooks. The rest of the attached funds are the
+    // tokens to send.
+    let mut hook_fee = hook_fee.unwrap_or_default();
+    let mut tokens = ctx.funds.clone();
 
-    token.amount.checked_sub_assign(route.fee).map_err(|_| {
+    tokens.deduct_many(hook_fee.clone()).map_err(|_| {
         anyhow!(
-            "withdrawal amount not sufficient to cover fee: {} < {}",
-            token.amount,
-            route.fee
+            "attached funds don't cover the hook fee: {} < {}",
+            ctx.funds,
+            hook_fee
         )
     })?;
 
-    Ok(Response::new()
+    // Sender must attach at least one token.
+    ensure!(tokens.is_non_empty(), "no token attached");
+
+    let mut response = Response::new();
+
+    // Send each token separately, through its own route.
+    for mut token in tokens {
+        // The token must have a route set. If the token is alloyed, use the
+        // route of an underlying token instead.
+        let (route_denom, route) = if token.denom.namespace() == Some(&ALLOY_NAMESPACE) {
+            take_alloy_route(ctx.storage, &token, destination_domain)?
+        } else {
+            let route = ROUTES.load(ctx.storage, (&token.denom, destination_domain))?;
+            (token.denom.clone(), route)
+        };
+
+        ensure!(
+            !PAUSED_ROUTES.has(ctx.storage, (&route_denom, destination_domain)),
+            "route of `{route_denom}` to domain {destination_domain} is paused"
+        );
+
+        token.amount.checked_sub_assign(route.fee).map_err(|_| {
+            anyhow!(
+                "withdrawal amount not sufficient to cover fee: {} < {}",
+                token.amount,
+                route.fee
+            )
+        })?;
+
+        // Take the mailbox's quote for dispatching the token out of the hook
+        // fee.
+        let quote = quote_dispatch(
+            &ctx,
+            destination_domain,
+            &route,
+            recipient,
+            token.amount,
+            &metadata,
+            hook,
+            &hook_metadata,
+        )?;
+
+        let mut remaining = hook_fee.clone();
+
+        remaining.deduct_many(quote.clone()).map_err(|_| {
+            anyhow!(
+                "insufficient hook fee for sending `{}`! required: {}, remaining: {}",
+                token.denom,
+                quote,
+                hook_fee
+            )
+        })?;
+
+        hook_fee = remaining;
+
+        // If the transfer exceeds the route's outbound rate limit, hold on to
+        // the token and queue the transfer.
+        if let Some(release_at) = apply_rate_limit(
+            ctx.storage,
+            ctx.block.timestamp,
+            Direction::Outbound,
+            &route_denom,
+            destination_domain,
+            token.amount,
+        )? {
+            response =
+                response.add_event(queue_transfer(ctx.storage, QueuedTransfer::Outbound {
+                    sender: ctx.sender,
+                    destination_domain,
+                    route_denom,
+                    route,
+                    recipient,
+                    token,
+                    metadata: metadata.clone(),
+                    hook,
+                    hook_metadata: hook_metadata.clone(),
+                    hook_fee: quote,
+                    release_at,
+                })?)?;
+
+            continue;
+        }
+
+        response = send(
+            &ctx,
+            response,
+            ctx.sender,
+            destination_domain,
+            route,
+            recipient,
+            token,
+            metadata.clone(),
+            hook,
+            hook_metadata.clone(),
+            quote,
+        )?;
+    }
+
+    // Refund the part of the hook fee that isn't needed.
+    if hook_fee.is_non_empty() {
+        response = response.add_message(Message::transfer(ctx.sender, hook_fee)?);
+    }
+
+    Ok(response)
+}
+
+#[inline]
+fn handle(
+    ctx: MutableCtx,
+    origin_domain: Domain,
+    sender: Addr32,
+    body: HexBinary,
+) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == MAILBOX.load(ctx.storage)?,
+        "only mailbox can call `handle`"
+    );
+
+    // Deserialize the message.
+    let body = TokenMessage::decode(&body)?;
+    let denom = REVERSE_ROUTES.load(ctx.storage, (origin_domain, sender))?;
+    let recipient = body.recipient;
+
+    // If the route is paused, or the transfer exceeds the route's inbound rate
+    // limit, queue the transfer.
+    let release_at = if PAUSED_ROUTES.has(ctx.storage, (&denom, origin_domain)) {
+        Some(ctx.block.timestamp)
+    } else {
+        apply_rate_limit(
+            ctx.storage,
+            ctx.block.timestamp,
+            Direction::Inbound,
+            &denom,
+            origin_domain,
+            body.amount,
+        )?
+    };
+
+    if let Some(release_at) = release_at {
+        let event = queue_transfer(ctx.storage, QueuedTransfer::Inbound {
+            origin_domain,
+            denom,
+            recipient,
+            amount: body.amount,
+            release_at,
+        })?;
+
+        return Ok(Response::new().add_event(event)?);
+    }
+
+    receive(ctx, recipient, denom, body.amount)
+}
+
+/// Query the mailbox's quote for dispatching a transfer through the route.
+fn quote_dispatch(
+    ctx: &MutableCtx,
+    destination_domain: Domain,
+    route: &Route,
+    recipient: Addr32,
+    amount: Uint128,
+    metadata: &HexBinary,
+    hook: Option<Addr>,
+    hook_metadata: &Option<HexBinary>,
+) -> StdResult<Coins> {
+    let mailbox = MAILBOX.load(ctx.storage)?;
+
+    ctx.querier
+        .query_wasm_smart(mailbox, mailbox::QueryQuoteDispatchRequest {
+            sender: ctx.contract,
+            destination_domain,
+            recipient: route.address,
+            body: TokenMessage {
+                recipient,
+                amount,
+                metadata: metadata.clone(),
+            }
+            .encode(),
+            metadata: hook_metadata.clone(),
+            hook,
+        })
+}
+
+/// Send tokens to the remote domain through the route.
+///
+/// The withdrawal fee is paid to the taxman, while the hook fee is attached to
+/// the dispatch to pay the hooks.
+fn send(
+    ctx: &MutableCtx,
+    response: Response,
+    sender: Addr,
+    destination_domain: Domain,
+    route: Route,
+    recipient: Addr32,
+    token: Coin,
+    metadata: HexBinary,
+    hook: Option<Addr>,
+    hook_metadata: Option<HexBinary>,
+    hook_fee: Coins,
+) -> anyhow::Result<Response> {
+    let mailbox = MAILBOX.load(ctx.storage)?;
+
+    let fee_msg = if route.fee.is_non_zero() {
+        let taxman = ctx.querier.query_config()?.taxman;
+        Some(Message::execute(
+            taxman,
+            &taxman::ExecuteMsg::Pay { payer: sender },
+            Coins::one(token.denom.clone(), route.fee)?,
+        )?)
+    } else {
+        None
+    };
+
+    Ok(response
         // If the token is collateral, then escrow it (no need to do anything).
-        // If it's synthetic, burn it.
-        // We determine whether it's synthetic by checking whether its denom is
-        // under the `hyp` namespace.
-        .may_add_message(if token.denom.namespace() == Some(&NAMESPACE) {
+        // If it's synthetic or alloyed, burn it.
+        // We determine whether it's synthetic or alloyed by checking whether its
+        // denom is under the `hyp` or `alloy` namespace.
+        .may_add_message(if is_mintable(&token.denom) {
             let bank = ctx.querier.query_bank()?;
             Some(Message::execute(
                 bank,
@@ -104,8 +681,9 @@ fn transfer_remote(
         } else {
             None
         })
+        .may_add_message(fee_msg)
         .add_message(Message::execute(
-            MAILBOX.load(ctx.storage)?,
+            mailbox,
             &mailbox::ExecuteMsg::Dispatch {
                 destination_domain,
                 // Note, this is the message recipient, not the token recipient.
@@ -113,76 +691,272 @@ fn transfer_remote(
                 body: TokenMessage {
                     recipient,
                     amount: token.amount,
-                    metadata: metadata.unwrap_or_default(),
+                    metadata,
                 }
                 .encode(),
-                // For sending tokens, we currently don't support metadata.
-                metadata: None,
-                // Always use the mailbox's default hook, which is set to the
-                // fee hook. This hook will get the withdrawal fee. We don't
-                // want the user to specify a different hook and steal the fee.
-                hook: None,
-            },
-            {
-                if route.fee.is_zero() {
-                    Coins::new()
-                } else {
-                    Coins::one(token.denom.clone(), route.fee)?
-                }
+                metadata: hook_metadata,
+                hook,
             },
+            hook_fee,
         )?)
         .add_event(TransferRemote {
-            sender: ctx.sender,
+            sender,
             destination_domain,
             recipient,
             token: token.denom,
             amount: token.amount,
-            hook: None,
+            hook,
             metadata: None,
         })?)
 }
 
-// TODO: handle any the error that can happen here
-#[inline]
-fn handle(
+/// Deliver tokens received through the route with the given denom.
+///
+/// If the recipient doesn't exist, the tokens are held by this contract, until
+/// claimed by the account factory when onboarding the recipient.
+fn receive(
     ctx: MutableCtx,
-    origin_domain: Domain,
-    sender: Addr32,
-    body: HexBinary,
+    recipient: Addr32,
+    denom: Denom,
+    amount: Uint128,
 ) -> anyhow::Result<Response> {
-    ensure!(
-        ctx.sender == MAILBOX.load(ctx.storage)?,
-        "only mailbox can call `handle`"
-    );
+    // If the denom is synthetic and alloyed, mint the alloyed token instead.
+    let denom = if denom.namespace() == Some(&NAMESPACE) {
+        alloy_or_underlying(ctx.storage, denom, amount)?
+    } else {
+        denom
+    };
 
-    // Deserialize the message.
-    let body = TokenMessage::decode(&body)?;
-    let denom = REVERSE_ROUTES.load(ctx.storage, (origin_domain, sender))?;
+    let existing_recipient = Addr::try_from(recipient)
+        .ok()
+        .filter(|addr| ctx.querier.query_contract(*addr).is_ok());
 
-    Ok(Response::new()
-        // If the denom is synthetic, then mint the token.
+    let response = if let Some(recipient) = existing_recipient {
+        // If the denom is synthetic or alloyed, then mint the token.
         // Otherwise, if it's a collateral, then release the collateral.
-        .add_message(if denom.namespace() == Some(&NAMESPACE) {
+        Response::new().add_message(if is_mintable(&denom) {
             let bank = ctx.querier.query_bank()?;
             Message::execute(
                 bank,
                 &bank::ExecuteMsg::Mint {
-                    to: body.recipient.try_into()?,
+                    to: recipient,
                     denom: denom.clone(),
-                    amount: body.amount,
+                    amount,
                 },
                 Coins::new(),
             )?
         } else {
-            // TODO: check whether the recipient exists; if not, register it at account factory.
-            Message::transfer(body.recipient.try_into()?, Coin {
+            Message::transfer(recipient, Coin {
                 denom: denom.clone(),
-                amount: body.amount,
+                amount,
             })?
         })
-        .add_event(Handle {
-            recipient: body.recipient,
-            token: denom,
-            amount: body.amount,
-        })?)
+    } else {
+        DEPOSITS.may_update(ctx.storage, recipient, |maybe_coins| {
+            let mut coins = maybe_coins.unwrap_or_default();
+            coins.insert(Coin {
+                denom: denom.clone(),
+                amount,
+            })?;
+            Ok::<_, StdError>(coins)
+        })?;
+
+        // If the denom is synthetic or alloyed, then mint the token to this
+        // contract. Otherwise, if it's a collateral, it's already here.
+        Response::new()
+            .may_add_message(if is_mintable(&denom) {
+                let bank = ctx.querier.query_bank()?;
+                Some(Message::execute(
+                    bank,
+                    &bank::ExecuteMsg::Mint {
+                        to: ctx.contract,
+                        denom: denom.clone(),
+                        amount,
+                    },
+                    Coins::new(),
+                )?)
+            } else {
+                None
+            })
+            .add_event(DepositHeld {
+                owner: recipient,
+                token: denom.clone(),
+                amount,
+            })?
+    };
+
+    Ok(response.add_event(Handle {
+        recipient,
+        token: denom,
+        amount,
+    })?)
+}
+
+fn queue_transfer(
+    storage: &mut dyn Storage,
+    transfer: QueuedTransfer,
+) -> StdResult<TransferQueued> {
+    let (id, _) = NEXT_QUEUED_TRANSFER_ID.increment(storage)?;
+    let release_at = transfer.release_at();
+
+    QUEUED_TRANSFERS.save(storage, id, &transfer)?;
+
+    Ok(TransferQueued { id, release_at })
+}
+
+#[inline]
+fn is_mintable(denom: &Denom) -> bool {
+    denom.namespace() == Some(&NAMESPACE) || denom.namespace() == Some(&ALLOY_NAMESPACE)
+}
+
+/// Find an underlying token of the alloyed token that has an unpaused route to
+/// the destination domain and enough supply to cover the transfer, and deduct
+/// the transfer amount from its supply.
+fn take_alloy_route(
+    storage: &mut dyn Storage,
+    token: &Coin,
+    destination_domain: Domain,
+) -> anyhow::Result<(Denom, Route)> {
+    let components = ALLOY_COMPONENTS
+        .prefix(&token.denom)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (underlying_denom, mut component) in components {
+        let Some(route) = ROUTES.may_load(storage, (&underlying_denom, destination_domain))? else {
+            continue;
+        };
+
+        if PAUSED_ROUTES.has(storage, (&underlying_denom, destination_domain)) {
+            continue;
+        }
+
+        let Ok(amount) = token.amount.checked_sub(route.fee) else {
+            continue;
+        };
+
+        if component.supply < amount {
+            continue;
+        }
+
+        component.supply -= amount;
+
+        ALLOY_COMPONENTS.save(storage, (&token.denom, &underlying_denom), &component)?;
+
+        return Ok((underlying_denom, route));
+    }
+
+    bail!(
+        "no route for `{}` to domain {} has sufficient liquidity",
+        token.denom,
+        destination_domain
+    );
+}
+
+/// If the synthetic token is alloyed, and minting the alloyed token doesn't
+/// exceed the underlying token's cap, return the alloyed denom and record the
+/// increased supply. Otherwise, return the synthetic denom.
+fn alloy_or_underlying(
+    storage: &mut dyn Storage,
+    underlying_denom: Denom,
+    amount: Uint128,
+) -> StdResult<Denom> {
+    let Some(alloyed_denom) = ALLOYS.may_load(storage, &underlying_denom)? else {
+        return Ok(underlying_denom);
+    };
+
+    let mut component = ALLOY_COMPONENTS.load(storage, (&alloyed_denom, &underlying_denom))?;
+    let supply = component.supply.checked_add(amount)?;
+
+    if component.cap.is_some_and(|cap| supply > cap) {
+        return Ok(underlying_denom);
+    }
+
+    component.supply = supply;
+
+    ALLOY_COMPONENTS.save(storage, (&alloyed_denom, &underlying_denom), &component)?;
+
+    Ok(alloyed_denom)
+}
+
+/// Record the transfer against the route's rate limit in the given direction,
+/// if any. If the transfer would exceed the limit, nothing is recorded, and the
+/// time at which the transfer can be released is returned instead.
+fn apply_rate_limit(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    direction: Direction,
+    denom: &Denom,
+    domain: Domain,
+    amount: Uint128,
+) -> StdResult<Option<Timestamp>> {
+    let limits = RATE_LIMITS
+        .may_load(storage, (denom, domain))?
+        .unwrap_or_default();
+
+    let (limit, usages) = match direction {
+        Direction::Inbound => (limits.inbound, INBOUND_USAGES),
+        Direction::Outbound => (limits.outbound, OUTBOUND_USAGES),
+    };
+
+    let Some(limit) = limit else {
+        return Ok(None);
+    };
+
+    let mut usage = usages
+        .may_load(storage, (denom, domain))?
+        .unwrap_or_else(|| WindowUsage {
+            window_start: now,
+            ..Default::default()
+        });
+
+    usage.roll(now, &limit);
+
+    if usage.amount(now, &limit)?.checked_add(amount)? > limit.cap {
+        return Ok(Some(now + limit.release_delay));
+    }
+
+    usage.current.checked_add_assign(amount)?;
+
+    usages.save(storage, (denom, domain), &usage)?;
+
+    Ok(None)
+}
+
+/// Record a released queued transfer against the route's rate limit in the
+/// given direction, if any. Unlike `apply_rate_limit`, the transfer is recorded
+/// even if it exceeds the limit, so that the transfers that follow it are
+/// delayed as if it had gone through when it was made.
+fn record_released_transfer(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    direction: Direction,
+    denom: &Denom,
+    domain: Domain,
+    amount: Uint128,
+) -> StdResult<()> {
+    let limits = RATE_LIMITS
+        .may_load(storage, (denom, domain))?
+        .unwrap_or_default();
+
+    let (limit, usages) = match direction {
+        Direction::Inbound => (limits.inbound, INBOUND_USAGES),
+        Direction::Outbound => (limits.outbound, OUTBOUND_USAGES),
+    };
+
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+
+    let mut usage = usages
+        .may_load(storage, (denom, domain))?
+        .unwrap_or_else(|| WindowUsage {
+            window_start: now,
+            ..Default::default()
+        });
+
+    usage.roll(now, &limit);
+    usage.current.checked_add_assign(amount)?;
+
+    usages.save(storage, (denom, domain), &usage)
 }
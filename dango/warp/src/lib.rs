@@ -1,5 +1,6 @@
 mod execute;
 mod query;
+mod rate_limit;
 mod state;
 
-pub use {execute::*, query::*, state::*};
+pub use {execute::*, query::*, rate_limit::*, state::*};
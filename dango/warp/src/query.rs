@@ -1,11 +1,22 @@
 use {
-    crate::{MAILBOX, ROUTES},
-    dango_types::warp::{QueryMsg, QueryRoutesPageParam, QueryRoutesResponseItem, Route},
-    grug::{Addr, Bound, Denom, ImmutableCtx, Json, JsonSerExt, Order, StdResult},
+    crate::{
+        WindowUsage, ALLOYS, ALLOY_COMPONENTS, DEPOSITS, INBOUND_USAGES, ISM, MAILBOX,
+        OUTBOUND_USAGES, PAUSED_ROUTES, QUEUED_TRANSFERS, RATE_LIMITS, ROUTES,
+    },
+    dango_types::warp::{
+        AlloyComponent, QueryMsg, QueryRoutesPageParam, QueryRoutesResponseItem, QueuedTransfer,
+        QueuedTransferId, RateLimit, RateLimitUsage, RateLimits, Route,
+    },
+    grug::{
+        Addr, Bound, Coins, Denom, ImmutableCtx, Json, JsonSerExt, Map, NumberConst, Order,
+        StdResult, Uint128,
+    },
     hyperlane_types::{
         mailbox::Domain,
         recipients::{RecipientQuery, RecipientQueryResponse},
+        Addr32,
     },
+    std::collections::BTreeMap,
 };
 
 const DEFAULT_PAGE_LIMIT: u32 = 30;
@@ -28,8 +39,48 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
             let res = query_routes(ctx, start_after, limit)?;
             res.to_json_value()
         },
+        QueryMsg::Alloy { underlying_denom } => {
+            let res = query_alloy(ctx, underlying_denom)?;
+            res.to_json_value()
+        },
+        QueryMsg::Alloys { start_after, limit } => {
+            let res = query_alloys(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::AlloyComposition { alloyed_denom } => {
+            let res = query_alloy_composition(ctx, alloyed_denom)?;
+            res.to_json_value()
+        },
+        QueryMsg::RateLimits { denom, domain } => {
+            let res = query_rate_limits(ctx, denom, domain)?;
+            res.to_json_value()
+        },
+        QueryMsg::RateLimitUsage { denom, domain } => {
+            let res = query_rate_limit_usage(ctx, denom, domain)?;
+            res.to_json_value()
+        },
+        QueryMsg::RoutePaused { denom, domain } => {
+            let res = query_route_paused(ctx, denom, domain);
+            res.to_json_value()
+        },
+        QueryMsg::QueuedTransfer { id } => {
+            let res = query_queued_transfer(ctx, id)?;
+            res.to_json_value()
+        },
+        QueryMsg::QueuedTransfers { start_after, limit } => {
+            let res = query_queued_transfers(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::Deposit { owner } => {
+            let res = query_deposit(ctx, owner)?;
+            res.to_json_value()
+        },
+        QueryMsg::Deposits { start_after, limit } => {
+            let res = query_deposits(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
         QueryMsg::Recipient(RecipientQuery::InterchainSecurityModule {}) => {
-            let ism = query_interchain_security_module(ctx);
+            let ism = query_interchain_security_module(ctx)?;
             let res = RecipientQueryResponse::InterchainSecurityModule(ism);
             res.to_json_value()
         },
@@ -72,7 +123,137 @@ fn query_routes(
 }
 
 #[inline]
-fn query_interchain_security_module(_ctx: ImmutableCtx) -> Option<Addr> {
-    // Currently we just use the default ISM.
-    None
+fn query_alloy(ctx: ImmutableCtx, underlying_denom: Denom) -> StdResult<Denom> {
+    ALLOYS.load(ctx.storage, &underlying_denom)
+}
+
+#[inline]
+fn query_alloys(
+    ctx: ImmutableCtx,
+    start_after: Option<Denom>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Denom, Denom>> {
+    let start = start_after.as_ref().map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    ALLOYS
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+#[inline]
+fn query_alloy_composition(
+    ctx: ImmutableCtx,
+    alloyed_denom: Denom,
+) -> StdResult<BTreeMap<Denom, AlloyComponent>> {
+    ALLOY_COMPONENTS
+        .prefix(&alloyed_denom)
+        .range(ctx.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+#[inline]
+fn query_rate_limits(ctx: ImmutableCtx, denom: Denom, domain: Domain) -> StdResult<RateLimits> {
+    Ok(RATE_LIMITS
+        .may_load(ctx.storage, (&denom, domain))?
+        .unwrap_or_default())
+}
+
+#[inline]
+fn query_rate_limit_usage(
+    ctx: ImmutableCtx,
+    denom: Denom,
+    domain: Domain,
+) -> StdResult<RateLimitUsage> {
+    let limits = RATE_LIMITS
+        .may_load(ctx.storage, (&denom, domain))?
+        .unwrap_or_default();
+
+    Ok(RateLimitUsage {
+        inbound: usage_of(
+            &ctx,
+            INBOUND_USAGES,
+            limits.inbound.as_ref(),
+            &denom,
+            domain,
+        )?,
+        outbound: usage_of(
+            &ctx,
+            OUTBOUND_USAGES,
+            limits.outbound.as_ref(),
+            &denom,
+            domain,
+        )?,
+    })
+}
+
+#[inline]
+fn query_route_paused(ctx: ImmutableCtx, denom: Denom, domain: Domain) -> bool {
+    PAUSED_ROUTES.has(ctx.storage, (&denom, domain))
+}
+
+#[inline]
+fn query_queued_transfer(ctx: ImmutableCtx, id: QueuedTransferId) -> StdResult<QueuedTransfer> {
+    QUEUED_TRANSFERS.load(ctx.storage, id)
+}
+
+#[inline]
+fn query_queued_transfers(
+    ctx: ImmutableCtx,
+    start_after: Option<QueuedTransferId>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<QueuedTransferId, QueuedTransfer>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    QUEUED_TRANSFERS
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+#[inline]
+fn query_deposit(ctx: ImmutableCtx, owner: Addr32) -> StdResult<Coins> {
+    Ok(DEPOSITS.may_load(ctx.storage, owner)?.unwrap_or_default())
+}
+
+#[inline]
+fn query_deposits(
+    ctx: ImmutableCtx,
+    start_after: Option<Addr32>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Addr32, Coins>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    DEPOSITS
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+#[inline]
+fn query_interchain_security_module(ctx: ImmutableCtx) -> StdResult<Option<Addr>> {
+    ISM.may_load(ctx.storage)
+}
+
+fn usage_of(
+    ctx: &ImmutableCtx,
+    usages: Map<(&Denom, Domain), WindowUsage>,
+    limit: Option<&RateLimit>,
+    denom: &Denom,
+    domain: Domain,
+) -> StdResult<Uint128> {
+    // Usage isn't tracked for routes without a limit.
+    let Some(limit) = limit else {
+        return Ok(Uint128::ZERO);
+    };
+
+    let Some(mut usage) = usages.may_load(ctx.storage, (denom, domain))? else {
+        return Ok(Uint128::ZERO);
+    };
+
+    usage.roll(ctx.block.timestamp, limit);
+    usage.amount(ctx.block.timestamp, limit)
 }
@@ -0,0 +1,60 @@
+use {
+    dango_types::warp::RateLimit,
+    grug::{MultiplyFraction, Number, NumberConst, StdResult, Timestamp, Udec128, Uint128},
+};
+
+/// Direction of a transfer relative to this chain.
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// Amount of tokens transferred through a route in one direction.
+///
+/// The amount transferred within the rolling window is approximated by the
+/// amount transferred in the current fixed window, plus the amount transferred
+/// in the previous fixed window weighted by how much of it still overlaps with
+/// the rolling window.
+#[grug::derive(Borsh)]
+#[derive(Default)]
+pub struct WindowUsage {
+    pub window_start: Timestamp,
+    pub current: Uint128,
+    pub previous: Uint128,
+}
+
+impl WindowUsage {
+    /// Advance the fixed windows to the one containing the current time.
+    pub fn roll(&mut self, now: Timestamp, limit: &RateLimit) {
+        let elapsed = now - self.window_start;
+
+        if elapsed.into_nanos() >= limit.window.into_nanos() * 2 {
+            self.window_start = now;
+            self.previous = Uint128::ZERO;
+            self.current = Uint128::ZERO;
+        } else if elapsed >= limit.window {
+            self.window_start = self.window_start + limit.window;
+            self.previous = self.current;
+            self.current = Uint128::ZERO;
+        }
+    }
+
+    /// Return the amount transferred within the rolling window ending at the
+    /// current time. Must be called after `roll`.
+    pub fn amount(&self, now: Timestamp, limit: &RateLimit) -> StdResult<Uint128> {
+        let window = limit.window.into_nanos();
+
+        if window == 0 {
+            return Ok(self.current);
+        }
+
+        let elapsed = (now - self.window_start).into_nanos().min(window);
+        let overlap = Udec128::checked_from_ratio(window - elapsed, window)?;
+
+        Ok(self
+            .previous
+            .checked_mul_dec_ceil(overlap)?
+            .checked_add(self.current)?)
+    }
+}
@@ -1,13 +1,43 @@
 use {
-    dango_types::warp::Route,
-    grug::{Addr, Denom, Item, Map},
+    crate::WindowUsage,
+    dango_types::warp::{AlloyComponent, QueuedTransfer, QueuedTransferId, RateLimits, Route},
+    grug::{Addr, Coins, Counter, Denom, Item, Map, Set},
     hyperlane_types::{mailbox::Domain, Addr32},
 };
 
 pub const MAILBOX: Item<Addr> = Item::new("mailbox");
 
+// If not set, the mailbox's default ISM is used.
+pub const ISM: Item<Addr> = Item::new("ism");
+
 // (denom, destination_domain) => (recipient, withdrawal_fee)
 pub const ROUTES: Map<(&Denom, Domain), Route> = Map::new("route");
 
 // (destination_domain, sender) => denom
 pub const REVERSE_ROUTES: Map<(Domain, Addr32), Denom> = Map::new("collateral");
+
+// underlying_denom => alloyed_denom
+pub const ALLOYS: Map<&Denom, Denom> = Map::new("alloy");
+
+// (alloyed_denom, underlying_denom) => component
+pub const ALLOY_COMPONENTS: Map<(&Denom, &Denom), AlloyComponent> = Map::new("alloy_component");
+
+// (denom, domain) => rate limits
+pub const RATE_LIMITS: Map<(&Denom, Domain), RateLimits> = Map::new("rate_limits");
+
+// (denom, origin_domain) => inbound usage
+pub const INBOUND_USAGES: Map<(&Denom, Domain), WindowUsage> = Map::new("inbound_usage");
+
+// (denom, destination_domain) => outbound usage
+pub const OUTBOUND_USAGES: Map<(&Denom, Domain), WindowUsage> = Map::new("outbound_usage");
+
+// (denom, domain)
+pub const PAUSED_ROUTES: Set<(&Denom, Domain)> = Set::new("paused_route");
+
+pub const NEXT_QUEUED_TRANSFER_ID: Counter<QueuedTransferId> =
+    Counter::new("next_queued_transfer_id", 1, 1);
+
+pub const QUEUED_TRANSFERS: Map<QueuedTransferId, QueuedTransfer> = Map::new("queued_transfer");
+
+// recipient => coins held for the recipient until it's onboarded
+pub const DEPOSITS: Map<Addr32, Coins> = Map::new("deposit");
@@ -256,18 +256,36 @@ where
         mode: None,
     };
 
-    call_in_1_out_1::<_, _, GenericResult<Json>>(
+    // If the request specifies a gas limit, run the query with a separate gas
+    // tracker capped at that limit (or the caller's remaining gas, whichever is
+    // smaller), then charge the gas it used to the caller.
+    let (gas_tracker, parent_gas_tracker) = match req.gas_limit {
+        Some(limit) => {
+            let limit = gas_tracker
+                .remaining()
+                .map_or(limit, |remaining| remaining.min(limit));
+            (GasTracker::new_limited(limit), Some(gas_tracker))
+        },
+        None => (gas_tracker, None),
+    };
+
+    let res = call_in_1_out_1::<_, _, GenericResult<Json>>(
         vm,
         storage,
-        gas_tracker,
+        gas_tracker.clone(),
         query_depth,
         false,
         "query",
         code_hash,
         &ctx,
         &req.msg,
-    )?
-    .map_err(|msg| AppError::Guest {
+    );
+
+    if let Some(parent_gas_tracker) = parent_gas_tracker {
+        parent_gas_tracker.consume(gas_tracker.used(), "query_wasm_smart")?;
+    }
+
+    res?.map_err(|msg| AppError::Guest {
         address: ctx.contract,
         name: "query",
         msg,
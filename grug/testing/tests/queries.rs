@@ -1,6 +1,6 @@
 use {
     grug_testing::TestBuilder,
-    grug_types::{Coins, Empty, ResultExt},
+    grug_types::{Coins, Empty, Querier, Query, ResultExt},
     grug_vm_rust::ContractBuilder,
 };
 
@@ -48,3 +48,136 @@ fn handling_multi_query() {
         )
         .should_succeed();
 }
+
+/// A contract whose query consumes an amount of gas proportional to the number
+/// of iterations requested, by repeatedly querying its own contract info.
+mod gas_guzzler {
+    use grug_types::{
+        Empty, ImmutableCtx, Json, JsonSerExt, MutableCtx, QuerierExt, Response, StdResult,
+    };
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query(ctx: ImmutableCtx, iterations: u32) -> StdResult<Json> {
+        for _ in 0..iterations {
+            ctx.querier.query_contract(ctx.contract)?;
+        }
+
+        Empty {}.to_json_value()
+    }
+}
+
+/// A contract that queries another contract under a gas limit.
+mod limited_query_maker {
+    use {
+        grug_types::{Addr, Empty, MutableCtx, Querier, Query, Response, StdResult},
+        serde::{Deserialize, Serialize},
+    };
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ExecuteMsg {
+        pub contract: Addr,
+        pub iterations: u32,
+        pub gas_limit: u64,
+    }
+
+    pub fn instantiate(_ctx: MutableCtx, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> StdResult<Response> {
+        ctx.querier.query_chain(Query::wasm_smart_with_gas_limit(
+            msg.contract,
+            &msg.iterations,
+            msg.gas_limit,
+        )?)?;
+
+        Ok(Response::new())
+    }
+}
+
+#[test]
+fn handling_query_with_gas_limit() {
+    let (mut suite, mut accounts) = TestBuilder::new()
+        .add_account("larry", Coins::new())
+        .set_chain_id("kebab")
+        .set_owner("larry")
+        .build();
+
+    let guzzler = suite
+        .upload_and_instantiate(
+            &mut accounts["larry"],
+            ContractBuilder::new(Box::new(gas_guzzler::instantiate))
+                .with_query(Box::new(gas_guzzler::query))
+                .build(),
+            &Empty {},
+            "gas_guzzler",
+            Some("gas_guzzler"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    let query_maker = suite
+        .upload_and_instantiate(
+            &mut accounts["larry"],
+            ContractBuilder::new(Box::new(limited_query_maker::instantiate))
+                .with_execute(Box::new(limited_query_maker::execute))
+                .build(),
+            &Empty {},
+            "limited_query_maker",
+            Some("limited_query_maker"),
+            None,
+            Coins::new(),
+        )
+        .should_succeed()
+        .address;
+
+    // The query succeeds if it stays within the gas limit, and fails otherwise.
+    suite
+        .query_chain(Query::wasm_smart_with_gas_limit(guzzler, &1_u32, 10_000).unwrap())
+        .should_succeed();
+
+    suite
+        .query_chain(Query::wasm_smart_with_gas_limit(guzzler, &100_u32, 10_000).unwrap())
+        .should_fail_with_error("out of gas! limit: 10000");
+
+    // The same goes for queries made by contracts, even if the transaction has
+    // plenty of gas left.
+    suite
+        .execute(
+            &mut accounts["larry"],
+            query_maker,
+            &limited_query_maker::ExecuteMsg {
+                contract: guzzler,
+                iterations: 100,
+                gas_limit: 10_000,
+            },
+            Coins::new(),
+        )
+        .should_fail_with_error("out of gas! limit: 10000");
+
+    // The gas consumed by the query is charged to the transaction.
+    let [gas_used_0, gas_used_10] = [0, 10].map(|iterations| {
+        suite
+            .execute(
+                &mut accounts["larry"],
+                query_maker,
+                &limited_query_maker::ExecuteMsg {
+                    contract: guzzler,
+                    iterations,
+                    gas_limit: 1_000_000,
+                },
+                Coins::new(),
+            )
+            .should_succeed()
+            .gas_used
+    });
+
+    // Each iteration reads the contract info from storage, which costs at
+    // least 588 gas units.
+    assert!(gas_used_10 >= gas_used_0 + 10 * 588);
+}
@@ -150,6 +150,19 @@ impl Query {
         Ok(QueryWasmSmartRequest {
             contract,
             msg: msg.to_json_value()?,
+            gas_limit: None,
+        }
+        .into())
+    }
+
+    pub fn wasm_smart_with_gas_limit<M>(contract: Addr, msg: &M, gas_limit: u64) -> StdResult<Self>
+    where
+        M: Serialize,
+    {
+        Ok(QueryWasmSmartRequest {
+            contract,
+            msg: msg.to_json_value()?,
+            gas_limit: Some(gas_limit),
         }
         .into())
     }
@@ -243,6 +256,14 @@ pub struct QueryWasmScanRequest {
 pub struct QueryWasmSmartRequest {
     pub contract: Addr,
     pub msg: Json,
+    /// If provided, the query is executed under this gas limit, instead of the
+    /// remaining gas of the caller. The gas consumed is still charged to the
+    /// caller.
+    ///
+    /// This allows a contract to call into an untrusted contract, without the
+    /// risk of the untrusted contract consuming all of its gas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
 }
 
 macro_rules! impl_into_query {
@@ -0,0 +1,96 @@
+use {
+    crate::{HOOKS, MAILBOX},
+    anyhow::ensure,
+    grug::{Addr, HexBinary, Message, MutableCtx, QuerierExt, Response, StdResult},
+    hyperlane_types::hooks::{
+        self,
+        aggregation::{ExecuteMsg, InstantiateMsg},
+        HookMsg, HookQuery, QueryHookRequest, StandardHookMetadata,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    MAILBOX.save(ctx.storage, &msg.mailbox)?;
+    HOOKS.save(ctx.storage, &msg.hooks)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::SetHooks { hooks } => set_hooks(ctx, hooks),
+        ExecuteMsg::Hook(HookMsg::PostDispatch {
+            raw_message,
+            raw_metadata,
+        }) => post_dispatch(ctx, raw_message, raw_metadata),
+    }
+}
+
+#[inline]
+fn set_hooks(ctx: MutableCtx, hooks: Vec<Addr>) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_hooks`"
+    );
+
+    HOOKS.save(ctx.storage, &hooks)?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn post_dispatch(
+    ctx: MutableCtx,
+    raw_message: HexBinary,
+    raw_metadata: HexBinary,
+) -> anyhow::Result<Response> {
+    // Similar to the other hooks, we trust the mailbox to only call this right
+    // after dispatching the message.
+    ensure!(
+        ctx.sender == MAILBOX.load(ctx.storage)?,
+        "sender is not mailbox"
+    );
+
+    let mut funds = ctx.funds;
+    let mut msgs = Vec::new();
+
+    // Call each hook with the amount it quotes.
+    for hook in HOOKS.load(ctx.storage)? {
+        let quote = ctx
+            .querier
+            .query_wasm_smart(
+                hook,
+                QueryHookRequest(HookQuery::QuoteDispatch {
+                    raw_message: raw_message.clone(),
+                    raw_metadata: raw_metadata.clone(),
+                }),
+            )?
+            .as_quote_dispatch();
+
+        ensure!(
+            funds.deduct_many(quote.clone()).is_ok(),
+            "insufficient fee for hook {hook}! required: {quote}"
+        );
+
+        msgs.push(Message::execute(
+            hook,
+            &hooks::ExecuteMsg::Hook(HookMsg::PostDispatch {
+                raw_message: raw_message.clone(),
+                raw_metadata: raw_metadata.clone(),
+            }),
+            quote,
+        )?);
+    }
+
+    // Refund the excess to the refund address, if the sender specified one in
+    // standard hook metadata. Otherwise, the excess is kept.
+    if funds.is_non_empty() {
+        if let Ok(metadata) = StandardHookMetadata::decode(&raw_metadata) {
+            msgs.push(Message::transfer(metadata.refund_address, funds)?);
+        }
+    }
+
+    Ok(Response::new().add_messages(msgs))
+}
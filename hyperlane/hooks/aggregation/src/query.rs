@@ -0,0 +1,51 @@
+use {
+    crate::{HOOKS, MAILBOX},
+    grug::{Addr, Coins, ImmutableCtx, Json, JsonSerExt, QuerierExt, StdResult},
+    hyperlane_types::hooks::{
+        aggregation::QueryMsg, HookQuery, HookQueryResponse, QueryHookRequest,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
+    match msg {
+        QueryMsg::Mailbox {} => {
+            let res = query_mailbox(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hooks {} => {
+            let res = query_hooks(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hook(query) => {
+            let res = HookQueryResponse::QuoteDispatch(quote_dispatch(ctx, query)?);
+            res.to_json_value()
+        },
+    }
+}
+
+#[inline]
+fn query_mailbox(ctx: ImmutableCtx) -> StdResult<Addr> {
+    MAILBOX.load(ctx.storage)
+}
+
+#[inline]
+fn query_hooks(ctx: ImmutableCtx) -> StdResult<Vec<Addr>> {
+    HOOKS.load(ctx.storage)
+}
+
+#[inline]
+fn quote_dispatch(ctx: ImmutableCtx, query: HookQuery) -> StdResult<Coins> {
+    let mut fees = Coins::new();
+
+    for hook in HOOKS.load(ctx.storage)? {
+        let quote = ctx
+            .querier
+            .query_wasm_smart(hook, QueryHookRequest(query.clone()))?
+            .as_quote_dispatch();
+
+        fees.insert_many(quote)?;
+    }
+
+    Ok(fees)
+}
@@ -0,0 +1,5 @@
+use grug::{Addr, Item};
+
+pub const MAILBOX: Item<Addr> = Item::new("mailbox");
+
+pub const HOOKS: Item<Vec<Addr>> = Item::new("hooks");
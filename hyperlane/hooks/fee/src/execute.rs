@@ -1,10 +1,13 @@
 use {
-    crate::MAILBOX,
-    anyhow::ensure,
-    grug::{HexBinary, MutableCtx, Response, StdResult},
-    hyperlane_types::hooks::{
-        fee::{ExecuteMsg, InstantiateMsg},
-        HookMsg,
+    crate::{FEES, MAILBOX},
+    anyhow::{anyhow, ensure},
+    grug::{Addr, Coins, HexBinary, Message, MutableCtx, QuerierExt, Response, StdResult},
+    hyperlane_types::{
+        hooks::{
+            fee::{ExecuteMsg, InstantiateMsg},
+            HookMsg,
+        },
+        mailbox::{self, Domain},
     },
 };
 
@@ -12,12 +15,21 @@ use {
 pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
     MAILBOX.save(ctx.storage, &msg.mailbox)?;
 
+    for (destination_domain, fee) in msg.fees {
+        FEES.save(ctx.storage, destination_domain, &fee)?;
+    }
+
     Ok(Response::new())
 }
 
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
     match msg {
+        ExecuteMsg::SetFee {
+            destination_domain,
+            fee,
+        } => set_fee(ctx, destination_domain, fee),
+        ExecuteMsg::Withdraw { recipient, coins } => withdraw(ctx, recipient, coins),
         ExecuteMsg::Hook(HookMsg::PostDispatch { raw_message, .. }) => {
             post_dispatch(ctx, raw_message)
         },
@@ -25,7 +37,33 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
 }
 
 #[inline]
-fn post_dispatch(ctx: MutableCtx, _raw_message: HexBinary) -> anyhow::Result<Response> {
+fn set_fee(ctx: MutableCtx, destination_domain: Domain, fee: Coins) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_fee`"
+    );
+
+    if fee.is_empty() {
+        FEES.remove(ctx.storage, destination_domain);
+    } else {
+        FEES.save(ctx.storage, destination_domain, &fee)?;
+    }
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn withdraw(ctx: MutableCtx, recipient: Addr, coins: Coins) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `withdraw`"
+    );
+
+    Ok(Response::new().add_message(Message::transfer(recipient, coins)?))
+}
+
+#[inline]
+fn post_dispatch(ctx: MutableCtx, raw_message: HexBinary) -> anyhow::Result<Response> {
     // In the reference implementation, we should check here that the message ID
     // matches the mailbox's last dispatched ID.
     // Here instead, we just ensure the sender is the mailbox, and trust the
@@ -36,7 +74,23 @@ fn post_dispatch(ctx: MutableCtx, _raw_message: HexBinary) -> anyhow::Result<Res
         "sender is not mailbox"
     );
 
-    // TODO
+    let message = mailbox::Message::decode(&raw_message)?;
+    let fee = FEES
+        .may_load(ctx.storage, message.destination_domain)?
+        .unwrap_or_default();
+
+    // The attached funds must cover the fee. Any excess is kept as well.
+    // Senders are expected to attach the mailbox's quote, so normally there is
+    // no excess.
+    let mut funds = ctx.funds.clone();
+
+    funds.deduct_many(fee.clone()).map_err(|_| {
+        anyhow!(
+            "insufficient fee! required: {}, received: {}",
+            fee,
+            ctx.funds
+        )
+    })?;
 
     Ok(Response::new())
 }
@@ -1,21 +1,36 @@
 use {
-    crate::MAILBOX,
-    grug::{Addr, Coins, ImmutableCtx, Json, JsonSerExt, StdResult},
-    hyperlane_types::hooks::{fee::QueryMsg, HookQuery, HookQueryResponse},
+    crate::{FEES, MAILBOX},
+    grug::{Addr, Bound, Coins, ImmutableCtx, Json, JsonSerExt, Order, StdResult},
+    hyperlane_types::{
+        hooks::{fee::QueryMsg, HookQuery, HookQueryResponse},
+        mailbox::{Domain, Message},
+    },
+    std::collections::BTreeMap,
 };
 
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), grug::export)]
-pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
     match msg {
         QueryMsg::Mailbox {} => {
             let res = query_mailbox(ctx)?;
             res.to_json_value()
         },
-        QueryMsg::Hook(HookQuery::QuoteDispatch { .. }) => {
-            let res = HookQueryResponse::QuoteDispatch(quote_dispatch());
+        QueryMsg::Fee { destination_domain } => {
+            let res = query_fee(ctx, destination_domain)?;
+            res.to_json_value()
+        },
+        QueryMsg::Fees { start_after, limit } => {
+            let res = query_fees(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hook(HookQuery::QuoteDispatch { raw_message, .. }) => {
+            let res = HookQueryResponse::QuoteDispatch(quote_dispatch(ctx, &raw_message)?);
             res.to_json_value()
         },
     }
+    .map_err(Into::into)
 }
 
 #[inline]
@@ -24,6 +39,29 @@ fn query_mailbox(ctx: ImmutableCtx) -> StdResult<Addr> {
 }
 
 #[inline]
-fn quote_dispatch() -> Coins {
-    Coins::new()
+fn query_fee(ctx: ImmutableCtx, destination_domain: Domain) -> StdResult<Coins> {
+    Ok(FEES
+        .may_load(ctx.storage, destination_domain)?
+        .unwrap_or_default())
+}
+
+#[inline]
+fn query_fees(
+    ctx: ImmutableCtx,
+    start_after: Option<Domain>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Domain, Coins>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    FEES.range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+#[inline]
+fn quote_dispatch(ctx: ImmutableCtx, raw_message: &[u8]) -> anyhow::Result<Coins> {
+    let message = Message::decode(raw_message)?;
+
+    Ok(query_fee(ctx, message.destination_domain)?)
 }
@@ -1,3 +1,8 @@
-use grug::{Addr, Item};
+use {
+    grug::{Addr, Coins, Item, Map},
+    hyperlane_types::mailbox::Domain,
+};
 
 pub const MAILBOX: Item<Addr> = Item::new("mailbox");
+
+pub const FEES: Map<Domain, Coins> = Map::new("fee");
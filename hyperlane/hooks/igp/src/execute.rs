@@ -0,0 +1,129 @@
+use {
+    crate::{
+        query::{gas_limit, quote_gas_payment},
+        CONFIG, GAS_ORACLES, MAILBOX,
+    },
+    anyhow::{anyhow, ensure},
+    grug::{
+        Addr, Coins, Hash256, HexBinary, IsZero, Message, MutableCtx, QuerierExt, Response,
+        StdResult,
+    },
+    hyperlane_types::{
+        hooks::{
+            igp::{ExecuteMsg, GasOracle, GasPayment, InstantiateMsg},
+            HookMsg, StandardHookMetadata,
+        },
+        mailbox::{self, Domain},
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    MAILBOX.save(ctx.storage, &msg.mailbox)?;
+    CONFIG.save(ctx.storage, &msg.config)?;
+
+    for (destination_domain, gas_oracle) in msg.gas_oracles {
+        GAS_ORACLES.save(ctx.storage, destination_domain, &gas_oracle)?;
+    }
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::SetGasOracle {
+            destination_domain,
+            gas_oracle,
+        } => set_gas_oracle(ctx, destination_domain, gas_oracle),
+        ExecuteMsg::Claim { recipient } => claim(ctx, recipient),
+        ExecuteMsg::Hook(HookMsg::PostDispatch {
+            raw_message,
+            raw_metadata,
+        }) => post_dispatch(ctx, raw_message, raw_metadata),
+    }
+}
+
+#[inline]
+fn set_gas_oracle(
+    ctx: MutableCtx,
+    destination_domain: Domain,
+    gas_oracle: GasOracle,
+) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_gas_oracle`"
+    );
+
+    GAS_ORACLES.save(ctx.storage, destination_domain, &gas_oracle)?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn claim(ctx: MutableCtx, recipient: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `claim`"
+    );
+
+    let gas_denom = CONFIG.load(ctx.storage)?.gas_denom;
+    let balance = ctx.querier.query_balance(ctx.contract, gas_denom.clone())?;
+
+    ensure!(balance.is_non_zero(), "no gas payment to claim");
+
+    Ok(Response::new().add_message(Message::transfer(
+        recipient,
+        Coins::one(gas_denom, balance)?,
+    )?))
+}
+
+#[inline]
+fn post_dispatch(
+    ctx: MutableCtx,
+    raw_message: HexBinary,
+    raw_metadata: HexBinary,
+) -> anyhow::Result<Response> {
+    // Similar to the fee hook, we trust the mailbox to only call this right
+    // after dispatching the message.
+    ensure!(
+        ctx.sender == MAILBOX.load(ctx.storage)?,
+        "sender is not mailbox"
+    );
+
+    let message = mailbox::Message::decode(&raw_message)?;
+    let message_id = Hash256::from_inner(ctx.api.keccak256(&raw_message));
+    let gas_limit = gas_limit(ctx.storage, &raw_metadata)?;
+    let payment = quote_gas_payment(ctx.storage, message.destination_domain, gas_limit)?;
+
+    let mut funds = ctx.funds.clone();
+
+    if payment.amount.is_non_zero() {
+        funds.deduct(payment.clone()).map_err(|_| {
+            anyhow!(
+                "insufficient gas payment! required: {}, received: {}",
+                payment,
+                ctx.funds
+            )
+        })?;
+    }
+
+    // Refund the excess to the refund address, if the sender specified one in
+    // standard hook metadata. Otherwise, the excess is kept, same as in the fee
+    // hook. Non-standard metadata, e.g. meant for another hook, isn't an error.
+    let refund = match StandardHookMetadata::decode(&raw_metadata) {
+        Ok(metadata) if funds.is_non_empty() => {
+            Some(Message::transfer(metadata.refund_address, funds)?)
+        },
+        _ => None,
+    };
+
+    Ok(Response::new()
+        .may_add_message(refund)
+        .add_event(GasPayment {
+            message_id,
+            destination_domain: message.destination_domain,
+            gas_limit,
+            payment: payment.amount,
+        })?)
+}
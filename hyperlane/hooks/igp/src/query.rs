@@ -0,0 +1,139 @@
+use {
+    crate::{CONFIG, GAS_ORACLES, MAILBOX},
+    anyhow::anyhow,
+    grug::{
+        Addr, Bound, Coin, Coins, ImmutableCtx, Json, JsonSerExt, MultiplyFraction, Number, Order,
+        StdResult, Storage, Uint128,
+    },
+    hyperlane_types::{
+        hooks::{
+            igp::{Config, GasOracle, QueryMsg},
+            HookQuery, HookQueryResponse, StandardHookMetadata,
+        },
+        mailbox::{Domain, Message},
+    },
+    std::collections::BTreeMap,
+};
+
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
+    match msg {
+        QueryMsg::Mailbox {} => {
+            let res = query_mailbox(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Config {} => {
+            let res = query_config(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::GasOracle { destination_domain } => {
+            let res = query_gas_oracle(ctx, destination_domain)?;
+            res.to_json_value()
+        },
+        QueryMsg::GasOracles { start_after, limit } => {
+            let res = query_gas_oracles(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::QuoteGasPayment {
+            destination_domain,
+            gas_limit,
+        } => {
+            let res = quote_gas_payment(ctx.storage, destination_domain, gas_limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hook(HookQuery::QuoteDispatch {
+            raw_message,
+            raw_metadata,
+        }) => {
+            let res =
+                HookQueryResponse::QuoteDispatch(quote_dispatch(ctx, &raw_message, &raw_metadata)?);
+            res.to_json_value()
+        },
+    }
+    .map_err(Into::into)
+}
+
+#[inline]
+fn query_mailbox(ctx: ImmutableCtx) -> StdResult<Addr> {
+    MAILBOX.load(ctx.storage)
+}
+
+#[inline]
+fn query_config(ctx: ImmutableCtx) -> StdResult<Config> {
+    CONFIG.load(ctx.storage)
+}
+
+#[inline]
+fn query_gas_oracle(ctx: ImmutableCtx, destination_domain: Domain) -> StdResult<GasOracle> {
+    GAS_ORACLES.load(ctx.storage, destination_domain)
+}
+
+#[inline]
+fn query_gas_oracles(
+    ctx: ImmutableCtx,
+    start_after: Option<Domain>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Domain, GasOracle>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    GAS_ORACLES
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+#[inline]
+fn quote_dispatch(
+    ctx: ImmutableCtx,
+    raw_message: &[u8],
+    raw_metadata: &[u8],
+) -> anyhow::Result<Coins> {
+    let message = Message::decode(raw_message)?;
+    let gas_limit = gas_limit(ctx.storage, raw_metadata)?;
+    let payment = quote_gas_payment(ctx.storage, message.destination_domain, gas_limit)?;
+
+    // Use `insert` instead of `Coins::one`, which errors if the payment is zero.
+    let mut coins = Coins::new();
+    coins.insert(payment)?;
+
+    Ok(coins)
+}
+
+/// Return the gas limit specified in the hook metadata, or the default gas
+/// limit if the metadata isn't standard hook metadata, including if it's empty.
+pub(crate) fn gas_limit(storage: &dyn Storage, raw_metadata: &[u8]) -> anyhow::Result<Uint128> {
+    if let Ok(metadata) = StandardHookMetadata::decode(raw_metadata) {
+        return Ok(metadata.gas_limit);
+    }
+
+    Ok(CONFIG.load(storage)?.default_gas_limit)
+}
+
+/// Compute the payment, in the gas token, for delivering a message to the
+/// destination domain with the given gas limit:
+///
+/// ```plain
+/// payment = (gas_limit + gas_overhead) * gas_price * token_exchange_rate
+/// ```
+///
+/// Rounded up, so that the relayer is never underpaid.
+pub(crate) fn quote_gas_payment(
+    storage: &dyn Storage,
+    destination_domain: Domain,
+    gas_limit: Uint128,
+) -> anyhow::Result<Coin> {
+    let config = CONFIG.load(storage)?;
+    let gas_oracle = GAS_ORACLES
+        .may_load(storage, destination_domain)?
+        .ok_or_else(|| anyhow!("no gas oracle for domain {destination_domain}"))?;
+
+    let payment = gas_limit
+        .checked_add(gas_oracle.gas_overhead)?
+        .checked_mul(gas_oracle.gas_price)?
+        .checked_mul_dec_ceil(gas_oracle.token_exchange_rate)?;
+
+    Ok(Coin::new(config.gas_denom, payment)?)
+}
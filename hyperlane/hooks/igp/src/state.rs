@@ -0,0 +1,13 @@
+use {
+    grug::{Addr, Item, Map},
+    hyperlane_types::{
+        hooks::igp::{Config, GasOracle},
+        mailbox::Domain,
+    },
+};
+
+pub const MAILBOX: Item<Addr> = Item::new("mailbox");
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+pub const GAS_ORACLES: Map<Domain, GasOracle> = Map::new("gas_oracle");
@@ -0,0 +1,49 @@
+use {
+    crate::{MAILBOX, PAUSED},
+    anyhow::ensure,
+    grug::{MutableCtx, QuerierExt, Response, StdResult},
+    hyperlane_types::hooks::{
+        pausable::{ExecuteMsg, InstantiateMsg},
+        HookMsg,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    MAILBOX.save(ctx.storage, &msg.mailbox)?;
+    PAUSED.save(ctx.storage, &msg.paused)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::SetPaused { paused } => set_paused(ctx, paused),
+        ExecuteMsg::Hook(HookMsg::PostDispatch { .. }) => post_dispatch(ctx),
+    }
+}
+
+#[inline]
+fn set_paused(ctx: MutableCtx, paused: bool) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_paused`"
+    );
+
+    PAUSED.save(ctx.storage, &paused)?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn post_dispatch(ctx: MutableCtx) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == MAILBOX.load(ctx.storage)?,
+        "sender is not mailbox"
+    );
+
+    ensure!(!PAUSED.load(ctx.storage)?, "hook is paused");
+
+    Ok(Response::new())
+}
@@ -0,0 +1,34 @@
+use {
+    crate::{MAILBOX, PAUSED},
+    grug::{Addr, Coins, ImmutableCtx, Json, JsonSerExt, StdResult},
+    hyperlane_types::hooks::{pausable::QueryMsg, HookQuery, HookQueryResponse},
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
+    match msg {
+        QueryMsg::Mailbox {} => {
+            let res = query_mailbox(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Paused {} => {
+            let res = query_paused(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Hook(HookQuery::QuoteDispatch { .. }) => {
+            // Pausing doesn't cost anything.
+            let res = HookQueryResponse::QuoteDispatch(Coins::new());
+            res.to_json_value()
+        },
+    }
+}
+
+#[inline]
+fn query_mailbox(ctx: ImmutableCtx) -> StdResult<Addr> {
+    MAILBOX.load(ctx.storage)
+}
+
+#[inline]
+fn query_paused(ctx: ImmutableCtx) -> StdResult<bool> {
+    PAUSED.load(ctx.storage)
+}
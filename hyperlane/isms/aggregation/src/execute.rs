@@ -0,0 +1,53 @@
+use {
+    crate::ISM_SET,
+    anyhow::ensure,
+    grug::{Addr, MutableCtx, QuerierExt, Response, Storage},
+    hyperlane_types::isms::aggregation::{ExecuteMsg, InstantiateMsg, IsmSet},
+    std::collections::BTreeSet,
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> anyhow::Result<Response> {
+    save_ism_set(ctx.storage, msg.threshold, msg.isms)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::SetIsms { threshold, isms } => set_isms(ctx, threshold, isms),
+    }
+}
+
+#[inline]
+fn set_isms(ctx: MutableCtx, threshold: u32, isms: Vec<Addr>) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_isms`"
+    );
+
+    save_ism_set(ctx.storage, threshold, isms)?;
+
+    Ok(Response::new())
+}
+
+fn save_ism_set(storage: &mut dyn Storage, threshold: u32, isms: Vec<Addr>) -> anyhow::Result<()> {
+    ensure!(threshold > 0, "threshold must be non-zero");
+
+    ensure!(
+        isms.len() >= threshold as usize,
+        "not enough ISMs! threshold: {}, ISMs: {}",
+        threshold,
+        isms.len()
+    );
+
+    ensure!(
+        isms.iter().collect::<BTreeSet<_>>().len() == isms.len(),
+        "duplicate ISMs"
+    );
+
+    ISM_SET.save(storage, &IsmSet { threshold, isms })?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use {
+    crate::ISM_SET,
+    anyhow::{anyhow, ensure},
+    grug::{HexBinary, ImmutableCtx, Json, JsonSerExt, QuerierExt, StdResult},
+    hyperlane_types::isms::{
+        aggregation::{IsmSet, Metadata, QueryMsg},
+        IsmQuery, IsmQueryResponse, QueryIsmRequest,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
+    match msg {
+        QueryMsg::IsmSet {} => {
+            let res = query_ism_set(ctx)?;
+            res.to_json_value()
+        },
+        QueryMsg::Ism(IsmQuery::Verify {
+            raw_message,
+            raw_metadata,
+        }) => {
+            let res = IsmQueryResponse::Verify(verify(ctx, raw_message, &raw_metadata)?);
+            res.to_json_value()
+        },
+    }
+    .map_err(Into::into)
+}
+
+#[inline]
+fn query_ism_set(ctx: ImmutableCtx) -> StdResult<IsmSet> {
+    ISM_SET.load(ctx.storage)
+}
+
+fn verify(ctx: ImmutableCtx, raw_message: HexBinary, raw_metadata: &[u8]) -> anyhow::Result<()> {
+    let ism_set = ISM_SET.load(ctx.storage)?;
+    let metadata = Metadata::decode(raw_metadata, ism_set.isms.len())?;
+
+    // Verify the message with each ISM that metadata is provided for. All of
+    // them must succeed, and there must be at least `threshold` of them.
+    let mut verified = 0;
+
+    for (ism, raw_metadata) in ism_set.isms.into_iter().zip(metadata.0) {
+        let Some(raw_metadata) = raw_metadata else {
+            continue;
+        };
+
+        ctx.querier
+            .query_wasm_smart(
+                ism,
+                QueryIsmRequest(IsmQuery::Verify {
+                    raw_message: raw_message.clone(),
+                    raw_metadata,
+                }),
+            )
+            .map(|res| res.as_verify())
+            .map_err(|err| anyhow!("verification by ISM {ism} failed: {err}"))?;
+
+        verified += 1;
+    }
+
+    ensure!(
+        verified >= ism_set.threshold,
+        "not enough ISMs verified the message! expecting at least {}, got {}",
+        ism_set.threshold,
+        verified
+    );
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+use {grug::Item, hyperlane_types::isms::aggregation::IsmSet};
+
+pub const ISM_SET: Item<IsmSet> = Item::new("ism_set");
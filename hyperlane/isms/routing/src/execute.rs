@@ -0,0 +1,50 @@
+use {
+    crate::MODULES,
+    anyhow::ensure,
+    grug::{Addr, MutableCtx, QuerierExt, Response, StdResult},
+    hyperlane_types::{
+        isms::routing::{ExecuteMsg, InstantiateMsg},
+        mailbox::Domain,
+    },
+};
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn instantiate(ctx: MutableCtx, msg: InstantiateMsg) -> StdResult<Response> {
+    for (origin_domain, ism) in msg.modules {
+        MODULES.save(ctx.storage, origin_domain, &ism)?;
+    }
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
+    match msg {
+        ExecuteMsg::SetModule { origin_domain, ism } => set_module(ctx, origin_domain, ism),
+        ExecuteMsg::RemoveModule { origin_domain } => remove_module(ctx, origin_domain),
+    }
+}
+
+#[inline]
+fn set_module(ctx: MutableCtx, origin_domain: Domain, ism: Addr) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `set_module`"
+    );
+
+    MODULES.save(ctx.storage, origin_domain, &ism)?;
+
+    Ok(Response::new())
+}
+
+#[inline]
+fn remove_module(ctx: MutableCtx, origin_domain: Domain) -> anyhow::Result<Response> {
+    ensure!(
+        ctx.sender == ctx.querier.query_owner()?,
+        "only the chain owner can call `remove_module`"
+    );
+
+    MODULES.remove(ctx.storage, origin_domain);
+
+    Ok(Response::new())
+}
@@ -0,0 +1,79 @@
+use {
+    crate::MODULES,
+    anyhow::anyhow,
+    grug::{Addr, Bound, HexBinary, ImmutableCtx, Json, JsonSerExt, Order, QuerierExt, StdResult},
+    hyperlane_types::{
+        isms::{routing::QueryMsg, IsmQuery, IsmQueryResponse, QueryIsmRequest},
+        mailbox::{Domain, Message},
+    },
+    std::collections::BTreeMap,
+};
+
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> anyhow::Result<Json> {
+    match msg {
+        QueryMsg::Module { origin_domain } => {
+            let res = query_module(ctx, origin_domain)?;
+            res.to_json_value()
+        },
+        QueryMsg::Modules { start_after, limit } => {
+            let res = query_modules(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
+        QueryMsg::Ism(IsmQuery::Verify {
+            raw_message,
+            raw_metadata,
+        }) => {
+            let res = IsmQueryResponse::Verify(verify(ctx, raw_message, raw_metadata)?);
+            res.to_json_value()
+        },
+    }
+    .map_err(Into::into)
+}
+
+#[inline]
+fn query_module(ctx: ImmutableCtx, origin_domain: Domain) -> StdResult<Addr> {
+    MODULES.load(ctx.storage, origin_domain)
+}
+
+#[inline]
+fn query_modules(
+    ctx: ImmutableCtx,
+    start_after: Option<Domain>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Domain, Addr>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    MODULES
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
+
+fn verify(
+    ctx: ImmutableCtx,
+    raw_message: HexBinary,
+    raw_metadata: HexBinary,
+) -> anyhow::Result<()> {
+    let message = Message::decode(&raw_message)?;
+
+    let ism = MODULES
+        .may_load(ctx.storage, message.origin_domain)?
+        .ok_or_else(|| anyhow!("no ISM for origin domain {}", message.origin_domain))?;
+
+    ctx.querier
+        .query_wasm_smart(
+            ism,
+            QueryIsmRequest(IsmQuery::Verify {
+                raw_message,
+                raw_metadata,
+            }),
+        )
+        .map(|res| res.as_verify())
+        .map_err(|err| anyhow!("verification by ISM {ism} failed: {err}"))?;
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+use {
+    grug::{Addr, Map},
+    hyperlane_types::mailbox::Domain,
+};
+
+pub const MODULES: Map<Domain, Addr> = Map::new("module");
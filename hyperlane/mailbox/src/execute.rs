@@ -1,13 +1,17 @@
 use {
-    crate::{CONFIG, DELIVERIES, NONCE},
-    anyhow::{anyhow, ensure},
-    grug::{Addr, Coins, Hash, HexBinary, MutableCtx, QuerierExt, Response, StdResult},
+    crate::{CONFIG, DELIVERIES, FAILED_MESSAGES, NONCE},
+    anyhow::{anyhow, bail, ensure},
+    grug::{
+        Addr, Coins, Hash, HexBinary, MutableCtx, QuerierExt, Response, StdResult, SubMessage,
+        SubMsgResult, SudoCtx,
+    },
     hyperlane_types::{
         hooks::{self, HookMsg, HookQuery, QueryHookRequest},
         isms::{IsmQuery, QueryIsmRequest},
         mailbox::{
-            Dispatch, DispatchId, Domain, ExecuteMsg, InstantiateMsg, Message, Process, ProcessId,
-            MAILBOX_VERSION,
+            Dispatch, DispatchId, Domain, ExecuteMsg, FailedMessage, HandleFailed, HandleRetried,
+            InstantiateMsg, Message, Process, ProcessId, ReplyMsg, MAILBOX_VERSION,
+            MAX_FAILED_MESSAGE_ERROR_LEN,
         },
         recipients::{self, QueryRecipientRequest, RecipientMsg, RecipientQuery},
         Addr32,
@@ -42,6 +46,33 @@ pub fn execute(ctx: MutableCtx, msg: ExecuteMsg) -> anyhow::Result<Response> {
             raw_message,
             raw_metadata,
         } => process(ctx, raw_message, raw_metadata),
+        ExecuteMsg::RetryHandle { message_id } => retry_handle(ctx, message_id),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), grug::export)]
+pub fn reply(ctx: SudoCtx, msg: ReplyMsg, res: SubMsgResult) -> anyhow::Result<Response> {
+    match msg {
+        ReplyMsg::Handle {
+            message_id,
+            origin_domain,
+            sender,
+            recipient,
+            body,
+        } => {
+            // We only request a reply if the recipient fails.
+            let Err(error) = res else {
+                bail!("expecting handle to have failed");
+            };
+
+            handle_failed(ctx, message_id, FailedMessage {
+                origin_domain,
+                sender,
+                recipient,
+                body,
+                error,
+            })
+        },
     }
 }
 
@@ -173,15 +204,26 @@ fn process(
     // Mark the message as delivered.
     DELIVERIES.insert(ctx.storage, message_id)?;
 
+    // Call the recipient to handle the message. If it fails, instead of
+    // reverting, record the message as failed, so that it can be retried later.
     Ok(Response::new()
-        .add_message(grug::Message::execute(
-            recipient,
-            &recipients::ExecuteMsg::Recipient(RecipientMsg::Handle {
+        .add_submessage(SubMessage::reply_on_error(
+            grug::Message::execute(
+                recipient,
+                &recipients::ExecuteMsg::Recipient(RecipientMsg::Handle {
+                    origin_domain: message.origin_domain,
+                    sender: message.sender,
+                    body: message.body.clone(),
+                }),
+                Coins::new(),
+            )?,
+            &ReplyMsg::Handle {
+                message_id,
                 origin_domain: message.origin_domain,
                 sender: message.sender,
+                recipient: message.recipient,
                 body: message.body,
-            }),
-            Coins::new(),
+            },
         )?)
         .add_event(Process {
             origin_domain: message.origin_domain,
@@ -190,3 +232,48 @@ fn process(
         })?
         .add_event(ProcessId { message_id })?)
 }
+
+#[inline]
+fn retry_handle(ctx: MutableCtx, message_id: Hash) -> anyhow::Result<Response> {
+    let failed = FAILED_MESSAGES
+        .may_take(ctx.storage, message_id)?
+        .ok_or_else(|| anyhow!("no failed message with id {message_id}"))?;
+
+    // Unlike in `process`, the recipient is called as a regular message, so if
+    // it fails again, the removal of the failed message is reverted as well.
+    Ok(Response::new()
+        .add_message(grug::Message::execute(
+            failed.recipient.try_into()?,
+            &recipients::ExecuteMsg::Recipient(RecipientMsg::Handle {
+                origin_domain: failed.origin_domain,
+                sender: failed.sender,
+                body: failed.body,
+            }),
+            Coins::new(),
+        )?)
+        .add_event(HandleRetried { message_id })?)
+}
+
+#[inline]
+fn handle_failed(
+    ctx: SudoCtx,
+    message_id: Hash,
+    mut failed: FailedMessage,
+) -> anyhow::Result<Response> {
+    // The recipient's error can be arbitrarily long. Truncate it, so that
+    // storing it doesn't cost an unbounded amount of gas.
+    if failed.error.len() > MAX_FAILED_MESSAGE_ERROR_LEN {
+        let mut len = MAX_FAILED_MESSAGE_ERROR_LEN;
+        while !failed.error.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        failed.error.truncate(len);
+    }
+
+    let error = failed.error.clone();
+
+    FAILED_MESSAGES.save(ctx.storage, message_id, &failed)?;
+
+    Ok(Response::new().add_event(HandleFailed { message_id, error })?)
+}
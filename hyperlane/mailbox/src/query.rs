@@ -1,9 +1,19 @@
 use {
-    crate::{CONFIG, DELIVERIES, NONCE},
-    grug::{Hash256, ImmutableCtx, Json, JsonSerExt, StdResult},
-    hyperlane_types::mailbox::{Config, QueryMsg},
+    crate::{CONFIG, DELIVERIES, FAILED_MESSAGES, NONCE},
+    grug::{
+        Addr, Bound, Coins, Hash256, HexBinary, ImmutableCtx, Json, JsonSerExt, Order, QuerierExt,
+        StdResult,
+    },
+    hyperlane_types::{
+        hooks::{HookQuery, QueryHookRequest},
+        mailbox::{Config, Domain, FailedMessage, Message, QueryMsg, MAILBOX_VERSION},
+        Addr32,
+    },
+    std::collections::BTreeMap,
 };
 
+const DEFAULT_PAGE_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), grug::export)]
 pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
     match msg {
@@ -15,10 +25,37 @@ pub fn query(ctx: ImmutableCtx, msg: QueryMsg) -> StdResult<Json> {
             let res = query_nonce(ctx)?;
             res.to_json_value()
         },
+        QueryMsg::QuoteDispatch {
+            sender,
+            destination_domain,
+            recipient,
+            body,
+            metadata,
+            hook,
+        } => {
+            let res = query_quote_dispatch(
+                ctx,
+                sender,
+                destination_domain,
+                recipient,
+                body,
+                metadata.unwrap_or_default(),
+                hook,
+            )?;
+            res.to_json_value()
+        },
         QueryMsg::Delivered { message_id } => {
             let res = query_delivered(ctx, message_id);
             res.to_json_value()
         },
+        QueryMsg::FailedMessage { message_id } => {
+            let res = query_failed_message(ctx, message_id)?;
+            res.to_json_value()
+        },
+        QueryMsg::FailedMessages { start_after, limit } => {
+            let res = query_failed_messages(ctx, start_after, limit)?;
+            res.to_json_value()
+        },
     }
 }
 
@@ -32,7 +69,74 @@ fn query_nonce(ctx: ImmutableCtx) -> StdResult<u32> {
     NONCE.current(ctx.storage)
 }
 
+#[inline]
+fn query_quote_dispatch(
+    ctx: ImmutableCtx,
+    sender: Addr,
+    destination_domain: Domain,
+    recipient: Addr32,
+    body: HexBinary,
+    metadata: HexBinary,
+    hook: Option<Addr>,
+) -> StdResult<Coins> {
+    let cfg = CONFIG.load(ctx.storage)?;
+
+    // Compose the message as it would be dispatched next.
+    let raw_message = Message {
+        version: MAILBOX_VERSION,
+        nonce: NONCE.current(ctx.storage)?,
+        origin_domain: cfg.local_domain,
+        sender: Addr32::from(sender),
+        destination_domain,
+        recipient,
+        body,
+    }
+    .encode();
+
+    let mut fees = Coins::new();
+
+    for hook in [cfg.required_hook, hook.unwrap_or(cfg.default_hook)] {
+        let quote = ctx
+            .querier
+            .query_wasm_smart(
+                hook,
+                QueryHookRequest(HookQuery::QuoteDispatch {
+                    raw_message: raw_message.clone(),
+                    raw_metadata: metadata.clone(),
+                }),
+            )?
+            .as_quote_dispatch();
+
+        fees.insert_many(quote)?;
+    }
+
+    Ok(fees)
+}
+
 #[inline]
 fn query_delivered(ctx: ImmutableCtx, message_id: Hash256) -> bool {
     DELIVERIES.has(ctx.storage, message_id)
 }
+
+#[inline]
+fn query_failed_message(
+    ctx: ImmutableCtx,
+    message_id: Hash256,
+) -> StdResult<Option<FailedMessage>> {
+    FAILED_MESSAGES.may_load(ctx.storage, message_id)
+}
+
+#[inline]
+fn query_failed_messages(
+    ctx: ImmutableCtx,
+    start_after: Option<Hash256>,
+    limit: Option<u32>,
+) -> StdResult<BTreeMap<Hash256, FailedMessage>> {
+    let start = start_after.map(Bound::Exclusive);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+
+    FAILED_MESSAGES
+        .range(ctx.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect()
+}
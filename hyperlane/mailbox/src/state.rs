@@ -1,6 +1,6 @@
 use {
-    grug::{Counter, Hash256, Item, Set},
-    hyperlane_types::mailbox::Config,
+    grug::{Counter, Hash256, Item, Map, Set},
+    hyperlane_types::mailbox::{Config, FailedMessage},
 };
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -8,3 +8,5 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const NONCE: Counter<u32> = Counter::new("nonce", 0, 1);
 
 pub const DELIVERIES: Set<Hash256> = Set::new("delivery");
+
+pub const FAILED_MESSAGES: Map<Hash256, FailedMessage> = Map::new("failed_message");
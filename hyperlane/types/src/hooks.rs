@@ -1,7 +1,68 @@
+pub mod aggregation;
 pub mod fee;
+pub mod igp;
 pub mod merkle;
+pub mod pausable;
 
-use grug::{Coins, HexBinary};
+use {
+    anyhow::ensure,
+    grug::{Addr, Coins, HexBinary, Inner, NextNumber, PrevNumber, Uint128, Uint256},
+};
+
+pub const STANDARD_HOOK_METADATA_VARIANT: u16 = 1;
+
+// ----------------------------------- types -----------------------------------
+
+/// The standard metadata that senders provide to hooks when dispatching.
+///
+/// See: <https://docs.hyperlane.xyz/docs/reference/hooks/overview#standardhookmetadata>
+#[grug::derive(Serde)]
+pub struct StandardHookMetadata {
+    pub variant: u16,
+    pub value: Uint128,
+    pub gas_limit: Uint128,
+    pub refund_address: Addr,
+    pub custom: HexBinary,
+}
+
+impl StandardHookMetadata {
+    pub fn encode(&self) -> HexBinary {
+        let mut buf = Vec::with_capacity(86 + self.custom.len());
+        buf.extend(self.variant.to_be_bytes());
+        // Important: cast the numbers to 256-bit.
+        buf.extend(self.value.into_next().to_be_bytes());
+        buf.extend(self.gas_limit.into_next().to_be_bytes());
+        buf.extend_from_slice(self.refund_address.inner());
+        buf.extend_from_slice(&self.custom);
+        buf.into()
+    }
+
+    pub fn decode(buf: &[u8]) -> anyhow::Result<Self> {
+        ensure!(
+            buf.len() >= 86,
+            "standard hook metadata should be at least 86 bytes, got: {}",
+            buf.len()
+        );
+
+        let variant = u16::from_be_bytes(buf[0..2].try_into().unwrap());
+
+        ensure!(
+            variant == STANDARD_HOOK_METADATA_VARIANT,
+            "unknown standard hook metadata variant: {variant}"
+        );
+
+        Ok(Self {
+            variant,
+            value: Uint256::from_be_bytes(buf[2..34].try_into().unwrap()).checked_into_prev()?,
+            gas_limit: Uint256::from_be_bytes(buf[34..66].try_into().unwrap())
+                .checked_into_prev()?,
+            refund_address: Addr::from_inner(buf[66..86].try_into().unwrap()),
+            custom: buf[86..].to_vec().into(),
+        })
+    }
+}
+
+// --------------------------------- messages ----------------------------------
 
 #[grug::derive(Serde)]
 pub enum ExecuteMsg {
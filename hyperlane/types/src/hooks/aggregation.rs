@@ -0,0 +1,52 @@
+use {
+    crate::hooks::{HookMsg, HookQuery, HookQueryResponse},
+    grug::Addr,
+};
+
+// --------------------------------- messages ----------------------------------
+
+#[grug::derive(Serde)]
+pub struct InstantiateMsg {
+    /// Address of the mailbox contract.
+    pub mailbox: Addr,
+    /// The hooks to call, in order.
+    ///
+    /// Hooks that only accept calls from the mailbox must be instantiated with
+    /// this contract's address as their mailbox, since it's this contract that
+    /// calls them.
+    ///
+    /// To migrate hooks that the mailbox calls directly behind this contract,
+    /// such hooks need to be redeployed with this contract as their mailbox.
+    /// For the Merkle tree hook, this means starting over with an empty tree,
+    /// so the validators must be moved to checkpoint the new tree, and messages
+    /// dispatched before the migration must be delivered beforehand.
+    pub hooks: Vec<Addr>,
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Set the hooks to call.
+    ///
+    /// The same requirement as in the instantiate message applies to the hooks'
+    /// mailbox.
+    SetHooks { hooks: Vec<Addr> },
+    /// Required Hyperlane hook interface.
+    ///
+    /// Each hook is called with the amount it quotes for the message. Excess
+    /// funds are refunded to the refund address in the hook metadata, if any.
+    Hook(HookMsg),
+}
+
+#[grug::derive(Serde, QueryRequest)]
+pub enum QueryMsg {
+    /// Query the mailbox contract address.
+    #[returns(Addr)]
+    Mailbox {},
+    /// Query the hooks to call.
+    #[returns(Vec<Addr>)]
+    Hooks {},
+    /// Required Hyperlane hook interface.
+    /// The quote is the sum of the quotes of all hooks.
+    #[returns(HookQueryResponse)]
+    Hook(HookQuery),
+}
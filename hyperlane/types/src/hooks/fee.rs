@@ -1,6 +1,10 @@
 use {
-    crate::hooks::{HookMsg, HookQuery, HookQueryResponse},
-    grug::Addr,
+    crate::{
+        hooks::{HookMsg, HookQuery, HookQueryResponse},
+        mailbox::Domain,
+    },
+    grug::{Addr, Coins},
+    std::collections::BTreeMap,
 };
 
 // --------------------------------- messages ----------------------------------
@@ -9,10 +13,25 @@ use {
 pub struct InstantiateMsg {
     /// Address of the mailbox contract.
     pub mailbox: Addr,
+    /// Fees charged for dispatching messages to each destination domain.
+    ///
+    /// These are separate from the withdrawal fees of Warp routes, which go to
+    /// the taxman. Senders are expected to query the mailbox's `QuoteDispatch`
+    /// and attach the quote to the dispatch, as Warp does with the hook fee of
+    /// a transfer.
+    pub fees: BTreeMap<Domain, Coins>,
 }
 
 #[grug::derive(Serde)]
 pub enum ExecuteMsg {
+    /// Set the fee charged for dispatching messages to a destination domain.
+    /// An empty fee removes it.
+    SetFee {
+        destination_domain: Domain,
+        fee: Coins,
+    },
+    /// Send collected fees to a recipient.
+    Withdraw { recipient: Addr, coins: Coins },
     /// Required Hyperlane hook interface.
     Hook(HookMsg),
 }
@@ -22,6 +41,16 @@ pub enum QueryMsg {
     /// Query the mailbox contract address.
     #[returns(Addr)]
     Mailbox {},
+    /// Query the fee charged for dispatching messages to a destination domain.
+    #[returns(Coins)]
+    Fee { destination_domain: Domain },
+    /// Enumerate the fees charged for dispatching messages to all destination
+    /// domains.
+    #[returns(BTreeMap<Domain, Coins>)]
+    Fees {
+        start_after: Option<Domain>,
+        limit: Option<u32>,
+    },
     /// Required Hyperlane hook interface.
     #[returns(HookQueryResponse)]
     Hook(HookQuery),
@@ -0,0 +1,95 @@
+use {
+    crate::{
+        hooks::{HookMsg, HookQuery, HookQueryResponse},
+        mailbox::Domain,
+    },
+    grug::{Addr, Coin, Denom, Hash256, Udec128, Uint128},
+    std::collections::BTreeMap,
+};
+
+// ----------------------------------- types -----------------------------------
+
+/// Gas cost of delivering messages to a destination domain.
+#[grug::derive(Serde, Borsh)]
+pub struct GasOracle {
+    /// Gas price on the destination domain, in the smallest unit of its native
+    /// token.
+    pub gas_price: Uint128,
+    /// Amount of the gas token, in its smallest unit, that is worth one
+    /// smallest unit of the destination domain's native token.
+    pub token_exchange_rate: Udec128,
+    /// Gas used on the destination domain on top of the message's gas limit,
+    /// e.g. for verifying the message with the ISM.
+    pub gas_overhead: Uint128,
+}
+
+#[grug::derive(Serde, Borsh)]
+pub struct Config {
+    /// The token that gas payments are made in.
+    pub gas_denom: Denom,
+    /// The gas limit to quote for if the hook metadata doesn't specify one.
+    pub default_gas_limit: Uint128,
+}
+
+// --------------------------------- messages ----------------------------------
+
+#[grug::derive(Serde)]
+pub struct InstantiateMsg {
+    /// Address of the mailbox contract.
+    pub mailbox: Addr,
+    pub config: Config,
+    pub gas_oracles: BTreeMap<Domain, GasOracle>,
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Set the gas oracle of a destination domain.
+    SetGasOracle {
+        destination_domain: Domain,
+        gas_oracle: GasOracle,
+    },
+    /// Send the collected gas payments to a recipient, typically the relayer.
+    Claim { recipient: Addr },
+    /// Required Hyperlane hook interface.
+    Hook(HookMsg),
+}
+
+#[grug::derive(Serde, QueryRequest)]
+pub enum QueryMsg {
+    /// Query the mailbox contract address.
+    #[returns(Addr)]
+    Mailbox {},
+    /// Query the configuration.
+    #[returns(Config)]
+    Config {},
+    /// Query the gas oracle of a destination domain.
+    #[returns(GasOracle)]
+    GasOracle { destination_domain: Domain },
+    /// Enumerate the gas oracles of all destination domains.
+    #[returns(BTreeMap<Domain, GasOracle>)]
+    GasOracles {
+        start_after: Option<Domain>,
+        limit: Option<u32>,
+    },
+    /// Query the payment required for delivering a message to a destination
+    /// domain with the given gas limit.
+    #[returns(Coin)]
+    QuoteGasPayment {
+        destination_domain: Domain,
+        gas_limit: Uint128,
+    },
+    /// Required Hyperlane hook interface.
+    #[returns(HookQueryResponse)]
+    Hook(HookQuery),
+}
+
+// ---------------------------------- events -----------------------------------
+
+#[grug::derive(Serde)]
+#[grug::event("gas_payment")]
+pub struct GasPayment {
+    pub message_id: Hash256,
+    pub destination_domain: Domain,
+    pub gas_limit: Uint128,
+    pub payment: Uint128,
+}
@@ -0,0 +1,37 @@
+use {
+    crate::hooks::{HookMsg, HookQuery, HookQueryResponse},
+    grug::Addr,
+};
+
+// --------------------------------- messages ----------------------------------
+
+#[grug::derive(Serde)]
+pub struct InstantiateMsg {
+    /// Address of the mailbox contract.
+    pub mailbox: Addr,
+    pub paused: bool,
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Pause or unpause the hook.
+    ///
+    /// While paused, dispatching messages with this hook fails, which halts
+    /// outbound messaging if it's the mailbox's required hook.
+    SetPaused { paused: bool },
+    /// Required Hyperlane hook interface.
+    Hook(HookMsg),
+}
+
+#[grug::derive(Serde, QueryRequest)]
+pub enum QueryMsg {
+    /// Query the mailbox contract address.
+    #[returns(Addr)]
+    Mailbox {},
+    /// Query whether the hook is paused.
+    #[returns(bool)]
+    Paused {},
+    /// Required Hyperlane hook interface.
+    #[returns(HookQueryResponse)]
+    Hook(HookQuery),
+}
@@ -1,4 +1,6 @@
+pub mod aggregation;
 pub mod multisig;
+pub mod routing;
 
 use grug::HexBinary;
 
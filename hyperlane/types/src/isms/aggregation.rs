@@ -0,0 +1,96 @@
+use {
+    super::IsmQueryResponse,
+    crate::isms::IsmQuery,
+    anyhow::ensure,
+    grug::{Addr, HexBinary},
+};
+
+#[grug::derive(Serde, Borsh)]
+pub struct IsmSet {
+    /// The minimum number of ISMs that must verify a message.
+    pub threshold: u32,
+    // The order matters, as the metadata is matched to the ISMs by position.
+    pub isms: Vec<Addr>,
+}
+
+/// Metadata for the aggregation ISM, consisting of the metadata for each of
+/// the ISMs in the set, in the same order. `None` means the message is not to
+/// be verified by the corresponding ISM.
+///
+/// Encoded in the same format as Hyperlane's `AggregationIsmMetadata`: for
+/// each ISM, a pair of big-endian `u32` start and end offsets, followed by the
+/// metadata. A start offset of zero indicates no metadata.
+#[grug::derive(Serde)]
+pub struct Metadata(pub Vec<Option<HexBinary>>);
+
+impl Metadata {
+    pub fn encode(&self) -> HexBinary {
+        let mut buf = vec![0; self.0.len() * 8];
+        for (i, metadata) in self.0.iter().enumerate() {
+            if let Some(metadata) = metadata {
+                let start = buf.len() as u32;
+                buf.extend_from_slice(metadata);
+                let end = buf.len() as u32;
+
+                buf[i * 8..i * 8 + 4].copy_from_slice(&start.to_be_bytes());
+                buf[i * 8 + 4..i * 8 + 8].copy_from_slice(&end.to_be_bytes());
+            }
+        }
+        buf.into()
+    }
+
+    /// Decode the metadata for a set of `count` ISMs.
+    pub fn decode(buf: &[u8], count: usize) -> anyhow::Result<Self> {
+        ensure!(
+            buf.len() >= count * 8,
+            "aggregation ISM metadata should be at least {} bytes, got: {}",
+            count * 8,
+            buf.len()
+        );
+
+        let metadata = (0..count)
+            .map(|i| {
+                let start = u32::from_be_bytes(buf[i * 8..i * 8 + 4].try_into().unwrap()) as usize;
+                let end =
+                    u32::from_be_bytes(buf[i * 8 + 4..i * 8 + 8].try_into().unwrap()) as usize;
+
+                if start == 0 {
+                    return Ok(None);
+                }
+
+                ensure!(
+                    start <= end && end <= buf.len(),
+                    "invalid metadata range for ISM #{i}: {start}..{end}"
+                );
+
+                Ok(Some(buf[start..end].to_vec().into()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self(metadata))
+    }
+}
+
+// --------------------------------- messages ----------------------------------
+
+#[grug::derive(Serde)]
+pub struct InstantiateMsg {
+    pub threshold: u32,
+    pub isms: Vec<Addr>,
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Set the ISMs to aggregate and the threshold.
+    SetIsms { threshold: u32, isms: Vec<Addr> },
+}
+
+#[grug::derive(Serde, QueryRequest)]
+pub enum QueryMsg {
+    /// Query the ISMs being aggregated and the threshold.
+    #[returns(IsmSet)]
+    IsmSet {},
+    /// Required Hyperlane ISM interface.
+    #[returns(IsmQueryResponse)]
+    Ism(IsmQuery),
+}
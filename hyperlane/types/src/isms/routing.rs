@@ -0,0 +1,39 @@
+use {
+    super::IsmQueryResponse,
+    crate::{isms::IsmQuery, mailbox::Domain},
+    grug::Addr,
+    std::collections::BTreeMap,
+};
+
+// --------------------------------- messages ----------------------------------
+
+#[grug::derive(Serde)]
+pub struct InstantiateMsg {
+    pub modules: BTreeMap<Domain, Addr>,
+}
+
+#[grug::derive(Serde)]
+pub enum ExecuteMsg {
+    /// Set the ISM for verifying messages from an origin domain.
+    SetModule { origin_domain: Domain, ism: Addr },
+    /// Remove the ISM for an origin domain, so that messages from it are
+    /// rejected.
+    RemoveModule { origin_domain: Domain },
+}
+
+#[grug::derive(Serde, QueryRequest)]
+pub enum QueryMsg {
+    /// Query the ISM for an origin domain.
+    #[returns(Addr)]
+    Module { origin_domain: Domain },
+    /// Enumerate the ISMs of all origin domains.
+    #[returns(BTreeMap<Domain, Addr>)]
+    Modules {
+        start_after: Option<Domain>,
+        limit: Option<u32>,
+    },
+    /// Required Hyperlane ISM interface.
+    /// Verification is delegated to the ISM of the message's origin domain.
+    #[returns(IsmQueryResponse)]
+    Ism(IsmQuery),
+}
@@ -1,11 +1,15 @@
 use {
     crate::Addr32,
     anyhow::ensure,
-    grug::{Addr, Hash256, HexBinary, Inner},
+    grug::{Addr, Coins, Hash256, HexBinary, Inner},
+    std::collections::BTreeMap,
 };
 
 pub const MAILBOX_VERSION: u8 = 3;
 
+/// Maximum length, in bytes, of the error recorded for a failed message.
+pub const MAX_FAILED_MESSAGE_ERROR_LEN: usize = 1024;
+
 pub type Domain = u32;
 
 // ----------------------------------- types -----------------------------------
@@ -69,6 +73,18 @@ pub struct Config {
     pub required_hook: Addr,
 }
 
+/// A message whose recipient failed to handle it.
+#[grug::derive(Serde, Borsh)]
+pub struct FailedMessage {
+    pub origin_domain: Domain,
+    pub sender: Addr32,
+    pub recipient: Addr32,
+    pub body: HexBinary,
+    /// The error the recipient returned, truncated to
+    /// [`MAX_FAILED_MESSAGE_ERROR_LEN`] bytes.
+    pub error: String,
+}
+
 // --------------------------------- messages ----------------------------------
 
 #[grug::derive(Serde)]
@@ -87,10 +103,31 @@ pub enum ExecuteMsg {
         hook: Option<Addr>,
     },
     /// Receive a message.
+    ///
+    /// If the recipient fails to handle the message, the message is still
+    /// marked as delivered, and recorded as failed so that it can be retried.
     Process {
         raw_message: HexBinary,
         raw_metadata: HexBinary,
     },
+    /// Call the recipient of a failed message to handle it again.
+    /// Can be called by anyone.
+    ///
+    /// If the recipient fails again, the transaction reverts, and the message
+    /// remains recorded as failed.
+    RetryHandle { message_id: Hash256 },
+}
+
+#[grug::derive(Serde)]
+pub enum ReplyMsg {
+    /// The recipient failed to handle a message.
+    Handle {
+        message_id: Hash256,
+        origin_domain: Domain,
+        sender: Addr32,
+        recipient: Addr32,
+        body: HexBinary,
+    },
 }
 
 #[grug::derive(Serde, QueryRequest)]
@@ -101,9 +138,31 @@ pub enum QueryMsg {
     /// Query the current nonce.
     #[returns(u32)]
     Nonce {},
+    /// Query the fee required to dispatch a message, that is, the quotes of the
+    /// required hook and of the specified hook, or the default hook if not
+    /// specified.
+    #[returns(Coins)]
+    QuoteDispatch {
+        sender: Addr,
+        destination_domain: Domain,
+        recipient: Addr32,
+        body: HexBinary,
+        metadata: Option<HexBinary>,
+        hook: Option<Addr>,
+    },
     /// Query whether a message has been delivered.
+    /// This includes messages that the recipient failed to handle.
     #[returns(bool)]
     Delivered { message_id: Hash256 },
+    /// Query a message that the recipient failed to handle.
+    #[returns(Option<FailedMessage>)]
+    FailedMessage { message_id: Hash256 },
+    /// Enumerate messages that the recipients failed to handle.
+    #[returns(BTreeMap<Hash256, FailedMessage>)]
+    FailedMessages {
+        start_after: Option<Hash256>,
+        limit: Option<u32>,
+    },
 }
 
 // ---------------------------------- events -----------------------------------
@@ -136,3 +195,16 @@ pub struct Process {
 pub struct ProcessId {
     pub message_id: Hash256,
 }
+
+#[grug::derive(Serde)]
+#[grug::event("mailbox_handle_failed")]
+pub struct HandleFailed {
+    pub message_id: Hash256,
+    pub error: String,
+}
+
+#[grug::derive(Serde)]
+#[grug::event("mailbox_handle_retried")]
+pub struct HandleRetried {
+    pub message_id: Hash256,
+}